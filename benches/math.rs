@@ -0,0 +1,95 @@
+use {
+    criterion::{criterion_group, criterion_main, BenchmarkId, Criterion},
+    sim2d::math::{batch, noise::Noise, polygon, Mat3, Rect, Vec2},
+    std::hint::black_box,
+};
+
+/// Particle counts which cover the SIMD lanes and a large system.
+const COUNTS: [usize; 3] = [1_000, 100_000, 1_000_003];
+
+fn points(count: usize) -> Vec<Vec2> {
+    (0..count)
+        .map(|i| {
+            let t = i as f32 * 0.618;
+            Vec2::new(t.cos() * 100.0, t.sin() * 100.0)
+        })
+        .collect()
+}
+
+fn batch_operations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch");
+    for &count in &COUNTS {
+        let velocities = points(count);
+        let mut positions = points(count);
+        group.bench_with_input(
+            BenchmarkId::new("integrate", count),
+            &count,
+            |b, _| {
+                b.iter(|| {
+                    batch::integrate(&mut positions, &velocities, 1.0 / 60.0)
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("integrate_scalar", count),
+            &count,
+            |b, _| {
+                b.iter(|| {
+                    for (position, velocity) in
+                        positions.iter_mut().zip(&velocities)
+                    {
+                        *position += velocity * (1.0 / 60.0);
+                    }
+                })
+            },
+        );
+
+        let matrix =
+            Mat3::new_rotation(0.01).append_translation(&Vec2::new(1.0, 2.0));
+        group.bench_with_input(
+            BenchmarkId::new("transform", count),
+            &count,
+            |b, _| b.iter(|| batch::transform(&mut positions, &matrix)),
+        );
+
+        let bounds = Rect::from_center(Vec2::zeros(), Vec2::new(150.0, 150.0));
+        group.bench_with_input(
+            BenchmarkId::new("clamp", count),
+            &count,
+            |b, _| b.iter(|| batch::clamp(&mut positions, &bounds)),
+        );
+    }
+    group.finish();
+}
+
+fn polygons(c: &mut Criterion) {
+    let cloud = points(10_000);
+    c.bench_function("convex_hull 10k", |b| {
+        b.iter(|| polygon::convex_hull(black_box(&cloud)))
+    });
+
+    let hull = polygon::convex_hull(&cloud);
+    c.bench_function("contains_point", |b| {
+        b.iter(|| {
+            polygon::contains_point(black_box(&hull), Vec2::new(10.0, 20.0))
+        })
+    });
+}
+
+fn noise(c: &mut Criterion) {
+    let noise = Noise::new(1);
+    c.bench_function("simplex2 256x256", |b| {
+        b.iter(|| {
+            let mut sum = 0.0;
+            for y in 0..256 {
+                for x in 0..256 {
+                    sum += noise.simplex2(x as f32 * 0.01, y as f32 * 0.01);
+                }
+            }
+            sum
+        })
+    });
+}
+
+criterion_group!(benches, batch_operations, polygons, noise);
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+use {
+    ab_glyph::{Font, FontVec, PxScaleFont},
+    criterion::{criterion_group, criterion_main, Criterion},
+    sim2d::graphics::{CachedFont, Image},
+    std::hint::black_box,
+};
+
+/// The characters `AssetLoader::load_font` puts in every font atlas.
+const ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz\
+    ABCDEFGHIJKLMNOPQRSTUVWXYZ\
+    1234567890{}()[]*&^%$#@!+=\
+    -/\\\"'`;:<>.,_|";
+
+fn font(size: f32) -> PxScaleFont<FontVec> {
+    FontVec::try_from_vec(
+        include_bytes!("../fonts/RobotoMono-Medium.ttf").to_vec(),
+    )
+    .unwrap()
+    .into_scaled(size)
+}
+
+fn build_atlas(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_atlas");
+    for size in [16.0, 64.0] {
+        let font = font(size);
+        group.bench_function(format!("{}px", size), |b| {
+            b.iter(|| CachedFont::build_atlas(black_box(&font), ALPHABET))
+        });
+    }
+    group.finish();
+}
+
+fn layout(c: &mut Criterion) {
+    let font = font(24.0);
+    let (_, glyph_uvs) = CachedFont::build_atlas(&font, ALPHABET);
+    let cached_font = CachedFont::new(Image::none(), font, glyph_uvs);
+
+    let label = "fps: 60.0";
+    c.bench_function("layout label", |b| {
+        b.iter(|| cached_font.layout_paragraph_geometry(black_box(label)))
+    });
+
+    let paragraph = "The quick brown fox jumps over the lazy dog.\n".repeat(40);
+    c.bench_function("layout paragraph", |b| {
+        b.iter(|| cached_font.layout_paragraph_geometry(black_box(&paragraph)))
+    });
+}
+
+criterion_group!(benches, build_atlas, layout);
+criterion_main!(benches);
@@ -70,7 +70,12 @@ impl Sketch for BunnyMark {
         log::info!("Total Sprites: {}", self.sprites.len());
     }
 
-    fn key_pressed(&mut self, _: &mut Sim2D, key: glfw::Key) {
+    fn key_pressed(
+        &mut self,
+        _: &mut Sim2D,
+        key: glfw::Key,
+        _: glfw::Modifiers,
+    ) {
         if key == glfw::Key::Space {
             self.sprites.clear();
         }
@@ -17,7 +17,12 @@ impl Sketch for LissajousDiagram {
         self.next_t = 0.2;
     }
 
-    fn key_pressed(&mut self, sim: &mut Sim2D, key: glfw::Key) {
+    fn key_pressed(
+        &mut self,
+        sim: &mut Sim2D,
+        key: glfw::Key,
+        _modifiers: glfw::Modifiers,
+    ) {
         if key == glfw::Key::Space {
             sim.w.toggle_fullscreen();
         }
@@ -3,7 +3,7 @@ use {
     sim2d::{
         application::Application,
         graphics::{AssetLoader, Image},
-        math::Vec2,
+        math::{geom::circle_overlap, Vec2, Vec2Ext},
         Sim2D, Sketch,
     },
 };
@@ -124,7 +124,7 @@ impl Sketch for NBodySystem {
                 let start = sim.w.mouse_pos()
                     + ((2.0 + i as f32) / count as f32)
                         * 100.0
-                        * Vec2::new(angle.cos(), angle.sin());
+                        * Vec2::from_angle(angle);
                 self.add_planet(Planet {
                     pos: start,
                     prev_pos: start,
@@ -148,7 +148,12 @@ impl Sketch for NBodySystem {
         }
     }
 
-    fn key_pressed(&mut self, _sim: &mut Sim2D, _key: glfw::Key) {
+    fn key_pressed(
+        &mut self,
+        _sim: &mut Sim2D,
+        _key: glfw::Key,
+        _modifiers: glfw::Modifiers,
+    ) {
         self.planets.clear();
     }
 
@@ -193,17 +198,17 @@ impl NBodySystem {
                 self.planets[i].apply_force(force);
                 self.planets[j].apply_force(-force);
 
-                let min_distance = a.radius + b.radius;
-                if distance >= min_distance {
+                let Some(depth) =
+                    circle_overlap(a.pos, a.radius, b.pos, b.radius)
+                else {
                     continue;
-                }
+                };
 
-                let delta = distance - min_distance;
-                let i_amount = delta * b.mass / (a.mass + b.mass);
-                let j_amount = delta * a.mass / (a.mass + b.mass);
+                let i_amount = depth * b.mass / (a.mass + b.mass);
+                let j_amount = depth * a.mass / (a.mass + b.mass);
 
-                self.planets[i].pos += norm_dir * i_amount;
-                self.planets[j].pos -= norm_dir * j_amount;
+                self.planets[i].pos -= norm_dir * i_amount;
+                self.planets[j].pos += norm_dir * j_amount;
 
                 self.planets[i].collision_count += 1.0;
                 self.planets[j].collision_count += 1.0;
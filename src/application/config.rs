@@ -0,0 +1,177 @@
+#[cfg(feature = "config")]
+use anyhow::Context;
+use {
+    anyhow::Result,
+    std::path::{Path, PathBuf},
+};
+
+/// The environment variable which can point to a config file.
+const CONFIG_ENV_VAR: &str = "SIM2D_CONFIG";
+
+/// The config file used when the environment variable is not set.
+const DEFAULT_CONFIG_FILE: &str = "sim2d.toml";
+
+/// Window and application settings which can be changed without recompiling
+/// the sketch.
+///
+/// Every setting is optional. Missing settings leave the sketch's own choices
+/// in place. With the `config` feature the config is read from a TOML file:
+///
+/// ```toml
+/// width = 1920
+/// height = 1080
+/// fullscreen = true
+/// monitor = 1
+/// vsync = true
+/// asset_root = "assets"
+/// single_threaded = false
+/// ```
+///
+/// With the `serde` or `config` feature the config can also be stored in any
+/// other format serde supports. Missing fields are left unset.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+    any(feature = "serde", feature = "config"),
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct Sim2DConfig {
+    /// The window's width in screen coordinates.
+    pub width: Option<f32>,
+
+    /// The window's height in screen coordinates.
+    pub height: Option<f32>,
+
+    /// Start the sketch in fullscreen mode.
+    pub fullscreen: Option<bool>,
+
+    /// The index of the monitor to use for fullscreen mode. Defaults to the
+    /// primary monitor.
+    pub monitor: Option<usize>,
+
+    /// Wait for vertical blank before presenting frames.
+    pub vsync: Option<bool>,
+
+    /// The number of samples to use for multisample antialiasing.
+    ///
+    /// The renderer does not support multisampling yet, so any value above
+    /// 1 is ignored with a warning.
+    pub msaa: Option<u32>,
+
+    /// Relative asset paths are loaded relative to this directory.
+    pub asset_root: Option<PathBuf>,
+
+    /// Preload sketches on the main thread instead of a background thread.
+    ///
+    /// Everything then runs on one thread, which is easier to step through
+    /// in a debugger. The loading screen is not shown in this mode.
+    pub single_threaded: Option<bool>,
+}
+
+// Public API
+// ----------
+
+impl Sim2DConfig {
+    /// Load the config from the file named by the `SIM2D_CONFIG` environment
+    /// variable, or from `sim2d.toml` in the working directory.
+    ///
+    /// Returns the default config if neither file exists. Without the
+    /// `config` feature the file is ignored with a warning.
+    pub fn load_default() -> Result<Self> {
+        if let Some(path) = std::env::var_os(CONFIG_ENV_VAR) {
+            return Self::load_if_enabled(Path::new(&path));
+        }
+        let path = Path::new(DEFAULT_CONFIG_FILE);
+        if path.exists() {
+            Self::load_if_enabled(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Load the config from a file.
+    #[cfg(feature = "config")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let contents =
+            std::fs::read_to_string(path.as_ref()).with_context(|| {
+                format!("Unable to read config file {:?}", path.as_ref())
+            })?;
+        Self::parse(&contents).with_context(|| {
+            format!("Unable to parse config file {:?}", path.as_ref())
+        })
+    }
+
+    /// Parse the config from a string.
+    #[cfg(feature = "config")]
+    pub fn parse(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+}
+
+// Private API
+// -----------
+
+impl Sim2DConfig {
+    #[cfg(feature = "config")]
+    fn load_if_enabled(path: &Path) -> Result<Self> {
+        Self::load(path)
+    }
+
+    #[cfg(not(feature = "config"))]
+    fn load_if_enabled(path: &Path) -> Result<Self> {
+        log::warn!("Ignoring {:?}, enable the config feature to read it", path);
+        Ok(Self::default())
+    }
+}
+
+#[cfg(all(test, feature = "config"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_all_settings() -> Result<()> {
+        let config = Sim2DConfig::parse(indoc::indoc!(
+            r#"
+            # Gallery installation
+            width = 1920
+            height = 1080.5
+            fullscreen = true # show on the projector
+            monitor = 1
+            vsync = false
+            msaa = 4
+            asset_root = "assets # not a comment"
+            single_threaded = true
+            "#
+        ))?;
+        assert_eq!(
+            config,
+            Sim2DConfig {
+                width: Some(1920.0),
+                height: Some(1080.5),
+                fullscreen: Some(true),
+                monitor: Some(1),
+                vsync: Some(false),
+                msaa: Some(4),
+                asset_root: Some("assets # not a comment".into()),
+                single_threaded: Some(true),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reject_invalid_settings() {
+        assert!(Sim2DConfig::parse("widht = 100").is_err());
+        assert!(Sim2DConfig::parse("fullscreen = 1").is_err());
+        assert!(Sim2DConfig::parse("monitor = -1").is_err());
+        assert!(Sim2DConfig::parse("asset_root = assets").is_err());
+    }
+
+    #[test]
+    fn parse_escaped_strings() -> Result<()> {
+        let config =
+            Sim2DConfig::parse(r#"asset_root = "C:\\sketch \"one\"""#)?;
+        assert_eq!(config.asset_root, Some(r#"C:\sketch "one""#.into()));
+        Ok(())
+    }
+}
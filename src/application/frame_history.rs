@@ -0,0 +1,235 @@
+use {
+    anyhow::{bail, Context, Result},
+    color_quant::NeuQuant,
+    image::{imageops, RgbaImage},
+    std::{
+        collections::VecDeque,
+        fs::File,
+        io::BufWriter,
+        path::{Path, PathBuf},
+        thread::JoinHandle,
+    },
+};
+
+/// How many frames per second are kept in the history.
+const FRAMES_PER_SECOND: f64 = 20.0;
+
+/// Frames are shrunk to fit this width before they are kept, which bounds the
+/// memory used by the history and the size of exported GIFs.
+const MAX_WIDTH: u32 = 480;
+
+/// The number of frames sampled when choosing an exported GIF's palette.
+const PALETTE_SAMPLE_FRAMES: usize = 8;
+
+/// A rolling buffer of recently rendered frames, used to export the last few
+/// seconds of a sketch as a GIF.
+#[derive(Default)]
+pub(crate) struct FrameHistory {
+    /// How many seconds of frames to keep. Zero disables the history.
+    duration: f64,
+
+    /// The time since the history was enabled, in seconds.
+    now: f64,
+
+    /// When the most recent capture was requested. Captures arrive a few
+    /// frames after they are requested.
+    last_capture: Option<f64>,
+    frames: VecDeque<(f64, RgbaImage)>,
+    exports: Vec<JoinHandle<()>>,
+}
+
+impl FrameHistory {
+    /// Keep the last `seconds` of frames, or stop keeping frames if `seconds`
+    /// is zero.
+    pub fn set_duration(&mut self, seconds: f64) {
+        self.duration = seconds.max(0.0);
+        if self.duration == 0.0 {
+            self.frames.clear();
+            self.last_capture = None;
+        }
+    }
+
+    /// Advance the history's clock. Returns the frame's time when the frame
+    /// about to be rendered should be captured. Pass the time to `push`
+    /// along with the captured frame.
+    pub fn advance(&mut self, dt: f64) -> Option<f64> {
+        if self.duration == 0.0 {
+            return None;
+        }
+        self.now += dt;
+        let due = match self.last_capture {
+            Some(last) => self.now - last >= 1.0 / FRAMES_PER_SECOND,
+            None => true,
+        };
+        if !due {
+            return None;
+        }
+        self.last_capture = Some(self.now);
+        Some(self.now)
+    }
+
+    /// Shrink and keep a frame captured at `time`, dropping frames which are
+    /// too old.
+    pub fn push(&mut self, frame: &RgbaImage, time: f64) {
+        if self.duration == 0.0 {
+            // The history was disabled while the frame was being captured.
+            return;
+        }
+        let (w, h) = frame.dimensions();
+        let frame = if w > MAX_WIDTH {
+            let height = ((h as u64 * MAX_WIDTH as u64) / w as u64).max(1);
+            imageops::thumbnail(frame, MAX_WIDTH, height as u32)
+        } else {
+            frame.clone()
+        };
+
+        self.frames.push_back((time, frame));
+        while let Some(&(time, _)) = self.frames.front() {
+            if self.now - time <= self.duration {
+                break;
+            }
+            self.frames.pop_front();
+        }
+    }
+
+    /// Encode the last `seconds` of frames as a looping GIF on a background
+    /// thread.
+    pub fn export_gif(&mut self, path: PathBuf, seconds: f64) -> Result<()> {
+        if self.duration == 0.0 {
+            bail!(
+                "Frame history is disabled, call \
+                 sim.record_frame_history(seconds) before exporting a GIF"
+            );
+        }
+        let frames: Vec<(f64, RgbaImage)> = self
+            .frames
+            .iter()
+            .filter(|(time, _)| self.now - time <= seconds)
+            .cloned()
+            .collect();
+        if frames.is_empty() {
+            bail!("No frames have been recorded yet");
+        }
+
+        self.exports.retain(|export| !export.is_finished());
+        self.exports.push(std::thread::spawn(move || {
+            match encode_gif(&path, &frames) {
+                Ok(()) => log::info!("Saved GIF {:?}", path),
+                Err(error) => {
+                    log::error!("Unable to save GIF {:?}: {:?}", path, error)
+                }
+            }
+        }));
+        Ok(())
+    }
+}
+
+impl Drop for FrameHistory {
+    /// Wait for exports to finish so they aren't cut off when the app exits.
+    fn drop(&mut self) {
+        for export in self.exports.drain(..) {
+            if export.join().is_err() {
+                log::error!("A GIF export thread panicked");
+            }
+        }
+    }
+}
+
+/// Write frames to a GIF using one palette for every frame, with
+/// Floyd-Steinberg dithering to hide the reduced number of colors.
+fn encode_gif(path: &Path, frames: &[(f64, RgbaImage)]) -> Result<()> {
+    let (width, height) = frames[0].1.dimensions();
+    let palette = choose_palette(frames);
+
+    let file = File::create(path)
+        .with_context(|| format!("Unable to create {:?}", path))?;
+    let mut encoder = gif::Encoder::new(
+        BufWriter::new(file),
+        width as u16,
+        height as u16,
+        &palette.color_map_rgb(),
+    )?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    for (index, (time, image)) in frames.iter().enumerate() {
+        if image.dimensions() != (width, height) {
+            // The window was resized part way through the recording.
+            continue;
+        }
+        let next_time = frames
+            .get(index + 1)
+            .map(|(next, _)| *next)
+            .unwrap_or(time + 1.0 / FRAMES_PER_SECOND);
+
+        let mut dithered = image.clone();
+        for pixel in dithered.pixels_mut() {
+            pixel.0[3] = 255;
+        }
+        imageops::dither(&mut dithered, &palette);
+        let indices: Vec<u8> = dithered
+            .pixels()
+            .map(|pixel| palette.index_of(&pixel.0) as u8)
+            .collect();
+
+        let frame = gif::Frame {
+            width: width as u16,
+            height: height as u16,
+            // GIF delays are in hundredths of a second and most viewers
+            // ignore delays shorter than 2.
+            delay: (((next_time - time) * 100.0).round() as u16).max(2),
+            buffer: indices.into(),
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&frame)?;
+    }
+    Ok(())
+}
+
+/// Build a 256 color palette from a sample of the frames.
+fn choose_palette(frames: &[(f64, RgbaImage)]) -> NeuQuant {
+    let step = (frames.len() / PALETTE_SAMPLE_FRAMES).max(1);
+    let mut pixels = Vec::new();
+    for (_, image) in frames.iter().step_by(step) {
+        // GIF frames are written opaque, so ignore alpha when choosing
+        // colors.
+        pixels.extend(image.pixels().flat_map(|pixel| {
+            let [r, g, b, _] = pixel.0;
+            [r, g, b, 255]
+        }));
+    }
+    NeuQuant::new(10, 256, &pixels)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encoded_gif_keeps_frames_and_delays() -> Result<()> {
+        let frames = [0.0, 0.05, 0.2]
+            .iter()
+            .enumerate()
+            .map(|(index, &time)| {
+                let shade = index as u8 * 100;
+                let image =
+                    RgbaImage::from_pixel(4, 3, [shade, 0, 255, 255].into());
+                (time, image)
+            })
+            .collect::<Vec<_>>();
+        let path = std::env::temp_dir()
+            .join(format!("sim2d-history-{}.gif", std::process::id()));
+
+        encode_gif(&path, &frames)?;
+        let mut decoder =
+            gif::DecodeOptions::new().read_info(File::open(&path)?)?;
+        let mut delays = vec![];
+        while let Some(frame) = decoder.read_next_frame()? {
+            assert_eq!((frame.width, frame.height), (4, 3));
+            delays.push(frame.delay);
+        }
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(delays, vec![5, 15, 5]);
+        Ok(())
+    }
+}
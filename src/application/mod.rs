@@ -1,44 +1,118 @@
 //! Provides structures for running a stateful single-window GLFW application.
 
+pub(crate) mod config;
+pub(crate) mod frame_history;
 mod loading_sketch;
 mod logging;
 mod timer;
+mod transition;
 
 use {
-    self::timer::Timer,
+    self::{timer::Timer, transition::TransitionState},
     crate::{
-        graphics::{Assets, NewAssets, Renderer, G2D},
+        graphics::{Assets, Image, NewAssets, Renderer, G2D},
         sim2d::Sim2D,
+        window::MouseGesture,
         DynSketch, Sketch,
     },
     anyhow::Result,
     glfw::WindowEvent,
     loading_sketch::LoadingSketch,
-    std::{sync::mpsc::Receiver, thread::JoinHandle},
+    std::{
+        panic::AssertUnwindSafe,
+        sync::{mpsc::Receiver, Once},
+        thread::JoinHandle,
+    },
 };
 
-type PreloadJoinHandle = JoinHandle<Result<(DynSketch, NewAssets)>>;
+/// How long to block waiting for events while the window is minimized.
+const SUSPENDED_EVENT_TIMEOUT: f64 = 0.1;
 
-use {ab_glyph::Font, anyhow::Context};
+type PreloadResult = Result<(DynSketch, NewAssets)>;
+
+/// A sketch being preloaded, either on a background thread or already
+/// finished on the main thread.
+enum Preload {
+    Thread(JoinHandle<PreloadResult>),
+    Finished(Box<PreloadResult>),
+}
+
+impl Preload {
+    fn is_finished(&self) -> bool {
+        match self {
+            Preload::Thread(handle) => handle.is_finished(),
+            Preload::Finished(_) => true,
+        }
+    }
+
+    fn join(self) -> PreloadResult {
+        match self {
+            Preload::Thread(handle) => handle.join().unwrap(),
+            Preload::Finished(result) => *result,
+        }
+    }
+}
 
-pub use crate::window::{GlfwWindow, WindowState};
+pub use {
+    self::{
+        config::Sim2DConfig,
+        timer::{
+            FrameStats, TimingStats, HISTOGRAM_BUCKETS, HISTOGRAM_BUCKET_WIDTH,
+        },
+        transition::Transition,
+    },
+    crate::window::{GlfwWindow, WindowHints, WindowState},
+};
+use {ab_glyph::Font, anyhow::Context};
 
 /// Every sketch is comprised of a State type and a GLFW window.
 /// Sketches automatically pause if they are minimized or the window is
-/// resized such that there is no drawing area.
+/// resized such that there is no drawing area. Rendering is always skipped
+/// while paused, but sketches can keep updating with
+/// `sim.set_update_while_minimized()`.
 pub struct Application {
-    loading_join_handle: Option<PreloadJoinHandle>,
+    loading_join_handle: Option<Preload>,
 
     sim: Sim2D,
     loading_sketch: LoadingSketch,
     sketch: DynSketch,
+    next_sketch: Option<DynSketch>,
+    transition: TransitionState,
 
     paused: bool,
+    iconified: bool,
+    zero_sized: bool,
     timer: Timer,
+    config: Sim2DConfig,
+    frame_limit: Option<u64>,
+    frame_count: u64,
 
     assets: Assets,
     renderer: Renderer,
     window: GlfwWindow,
+    #[cfg(all(feature = "tablet", target_os = "linux"))]
+    tablet: crate::window::tablet::Tablet,
+
+    /// Captures which were requested but haven't been read back yet.
+    pending_captures: Vec<PendingCapture>,
+
+    /// The texture which holds the outgoing sketch's last frame during a
+    /// crossfade. It is created for the first crossfade and reused after.
+    last_frame: Option<Image>,
+}
+
+/// Who a requested capture is for.
+struct PendingCapture {
+    id: u64,
+
+    /// The sketch asked for the frame with `capture_frame`.
+    for_sketch: bool,
+
+    /// The frame history's time for the frame, if it should be kept.
+    history_time: Option<f64>,
+
+    /// The frame is the outgoing sketch's last frame for a crossfade.
+    for_transition: bool,
 }
 
 // Public API
@@ -48,22 +122,106 @@ impl Application {
     ///
     /// The window title is just the Application state struct's type name.
     pub fn run<S>(sketch: S) -> Result<()>
+    where
+        S: Sketch + Send + 'static,
+    {
+        Self::run_with_window_hints(sketch, WindowHints::default())
+    }
+
+    /// Create and run the Application until the window is closed.
+    ///
+    /// The window hints control properties which can only be set when the
+    /// window is created, like decorations and transparency.
+    ///
+    /// The config is loaded with `Sim2DConfig::load_default()`.
+    pub fn run_with_window_hints<S>(sketch: S, hints: WindowHints) -> Result<()>
     where
         S: Sketch + Send + 'static,
     {
         crate::application::logging::setup();
-        let window_title = std::any::type_name::<S>();
-        let (window, event_receiver) = GlfwWindow::new(window_title)?;
+        let config = Sim2DConfig::load_default()?;
+        Self::run_with_config(sketch, hints, config)
+    }
 
-        Self::new(window, sketch)?.main_loop(event_receiver)
+    /// Create and run the Application until the window is closed.
+    ///
+    /// The config's window settings are applied before each sketch's setup
+    /// method is called, so sketches can still override them.
+    pub fn run_with_config<S>(
+        sketch: S,
+        hints: WindowHints,
+        config: Sim2DConfig,
+    ) -> Result<()>
+    where
+        S: Sketch + Send + 'static,
+    {
+        Self::run_app(sketch, hints, config, None)
+    }
+
+    /// Create and run the Application for exactly `frame_count` frames, then
+    /// exit.
+    ///
+    /// Frames are only counted once the sketch has finished loading, so the
+    /// sketch's setup runs followed by exactly `frame_count` update and render
+    /// cycles. Frames drawn while the sketch is paused don't count. This is
+    /// useful for scripted rendering.
+    pub fn run_frames<S>(sketch: S, frame_count: u64) -> Result<()>
+    where
+        S: Sketch + Send + 'static,
+    {
+        crate::application::logging::setup();
+        let config = Sim2DConfig::load_default()?;
+        Self::run_app(sketch, WindowHints::default(), config, Some(frame_count))
     }
 }
 
 // Private API
 
 impl Application {
+    /// Create and run the Application until the window is closed or the
+    /// frame limit is reached.
+    fn run_app<S>(
+        sketch: S,
+        hints: WindowHints,
+        config: Sim2DConfig,
+        frame_limit: Option<u64>,
+    ) -> Result<()>
+    where
+        S: Sketch + Send + 'static,
+    {
+        crate::application::logging::setup();
+        install_panic_hook();
+        let window_title = std::any::type_name::<S>();
+        let (window, event_receiver) = GlfwWindow::new(window_title, &hints)?;
+
+        let mut app = Self::new(window, sketch, config)?;
+        app.frame_limit = frame_limit;
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            app.main_loop(event_receiver)
+        }));
+        match result {
+            Ok(result) => result,
+            Err(panic) => {
+                // Wait for the GPU to finish before any Vulkan resources are
+                // destroyed so unwinding doesn't leave the device hung.
+                if let Err(error) = app.renderer.wait_idle() {
+                    log::error!(
+                        "Unable to wait for the device after a panic!\n{:?}",
+                        error
+                    );
+                }
+                drop(app);
+                std::panic::resume_unwind(panic)
+            }
+        }
+    }
+
     /// Create a new running application.
-    fn new<S>(window: GlfwWindow, sketch: S) -> Result<Self>
+    fn new<S>(
+        window: GlfwWindow,
+        sketch: S,
+        config: Sim2DConfig,
+    ) -> Result<Self>
     where
         S: Sketch + Send + 'static,
     {
@@ -71,6 +229,12 @@ impl Application {
 
         let render_device = unsafe { window.create_render_device()? };
         let mut assets = Assets::new(render_device.clone());
+        if let Some(asset_root) = &config.asset_root {
+            assets.set_asset_root(asset_root);
+        }
+        if config.msaa.is_some_and(|samples| samples > 1) {
+            log::warn!("MSAA is not supported yet, ignoring the msaa setting");
+        }
         let barriers = {
             let mut asset_loader = assets.take_asset_loader();
 
@@ -90,6 +254,8 @@ impl Application {
         let renderer = Renderer::new(
             render_device,
             window.get_framebuffer_size(),
+            window.is_framebuffer_transparent(),
+            config.vsync.unwrap_or(false),
             assets.textures(),
             &barriers,
         )?;
@@ -102,13 +268,24 @@ impl Application {
             sim,
             loading_sketch: loading.clone(),
             sketch: Box::new(loading),
+            next_sketch: None,
+            transition: TransitionState::new(),
 
             timer: Timer::new(),
             paused: false,
+            iconified: false,
+            zero_sized: false,
+            config,
+            frame_limit: None,
+            frame_count: 0,
 
             assets,
             renderer,
             window,
+            #[cfg(all(feature = "tablet", target_os = "linux"))]
+            tablet: crate::window::tablet::Tablet::open(),
+            pending_captures: vec![],
+            last_frame: None,
         };
 
         app.spawn_load_thread(Box::new(sketch))?;
@@ -117,23 +294,54 @@ impl Application {
     }
 
     fn main_loop(
-        mut self,
+        &mut self,
         event_receiver: Receiver<(f64, WindowEvent)>,
     ) -> Result<()> {
         while !(self.window.should_close()) {
             self.join_load_thread()?;
+            if self.is_frame_limit_reached() {
+                break;
+            }
 
-            self.window.glfw.poll_events();
+            if self.paused {
+                // Block instead of spinning while there is nothing to draw.
+                self.window
+                    .glfw
+                    .wait_events_timeout(SUSPENDED_EVENT_TIMEOUT);
+            } else {
+                self.renderer.wait_for_next_frame()?;
+                self.window.glfw.poll_events();
+            }
             for (_, window_event) in glfw::flush_messages(&event_receiver) {
                 self.handle_event(window_event)?;
             }
+            #[cfg(all(feature = "tablet", target_os = "linux"))]
+            self.tablet.update(&mut self.sim.w);
             self.window.update_window_to_match(&mut self.sim.w)?;
+            #[cfg(feature = "clipboard")]
+            self.paste_image()?;
 
-            if !self.paused {
-                self.update()?;
+            if self.paused {
+                if self.sim.update_while_minimized {
+                    self.simulate();
+                }
+            } else {
+                let is_counted = self.is_counting_frames();
+                let updated = self.simulate();
+                self.render()?;
+                if is_counted && updated {
+                    self.frame_count += 1;
+                }
 
-                if !self.is_loading() {
+                if !self.is_loading() && self.next_sketch.is_none() {
                     if let Some(next_sketch) = self.sketch.load_sketch() {
+                        self.next_sketch = Some(next_sketch);
+                        self.transition.begin_out(self.sim.transition);
+                    }
+                }
+
+                if self.transition.is_covered() {
+                    if let Some(next_sketch) = self.next_sketch.take() {
                         self.spawn_load_thread(next_sketch)?;
                     }
                 }
@@ -142,21 +350,79 @@ impl Application {
         Ok(())
     }
 
+    /// Load the clipboard's image as a new texture and hand it to the
+    /// sketch, if the sketch asked for it.
+    #[cfg(feature = "clipboard")]
+    fn paste_image(&mut self) -> Result<()> {
+        // The asset loader belongs to the load thread until it finishes, so
+        // the request waits until then.
+        if self.is_loading() || !std::mem::take(&mut self.sim.paste_requested) {
+            return Ok(());
+        }
+        let pixels = match read_clipboard_image() {
+            Ok(pixels) => pixels,
+            Err(error) => {
+                log::warn!("No image to paste: {:?}", error);
+                return Ok(());
+            }
+        };
+
+        let name = format!("clipboard-{}", self.assets.textures().len());
+        let image = self.upload_image(pixels, true, name)?;
+        self.sketch.image_pasted(&mut self.sim, image);
+        Ok(())
+    }
+
+    /// Upload `pixels` as a new texture.
+    ///
+    /// The asset loader must not be in use by the load thread.
+    fn upload_image(
+        &mut self,
+        pixels: ::image::RgbaImage,
+        generate_mipmaps: bool,
+        name: String,
+    ) -> Result<Image> {
+        let mut asset_loader = self.assets.take_asset_loader();
+        let image = asset_loader.load_image(pixels, generate_mipmaps, name);
+        let image_acquire_barriers =
+            self.assets.new_assets(NewAssets::new(asset_loader)?);
+        self.renderer
+            .update_textures(self.assets.textures(), &image_acquire_barriers)?;
+        Ok(image)
+    }
+
     fn is_loading(&self) -> bool {
         self.loading_join_handle.is_some()
     }
 
+    /// Frames count toward the frame limit once the sketch has loaded and
+    /// until it hands off to another sketch.
+    fn is_counting_frames(&self) -> bool {
+        !self.is_loading() && self.next_sketch.is_none()
+    }
+
+    fn is_frame_limit_reached(&self) -> bool {
+        self.is_counting_frames()
+            && self
+                .frame_limit
+                .is_some_and(|limit| self.frame_count >= limit)
+    }
+
     fn spawn_load_thread(&mut self, mut sketch: DynSketch) -> Result<()> {
         self.sketch = Box::new(self.loading_sketch.clone());
         self.sketch.setup(&mut self.sim);
         self.window.update_window_to_match(&mut self.sim.w)?;
 
         let mut asset_loader = self.assets.take_asset_loader();
-        let join_handle: PreloadJoinHandle =
-            std::thread::spawn(move || -> Result<(DynSketch, NewAssets)> {
-                sketch.preload(&mut asset_loader)?;
-                Ok((sketch, NewAssets::new(asset_loader)?))
-            });
+        let preload = move || -> PreloadResult {
+            sketch.preload(&mut asset_loader)?;
+            Ok((sketch, NewAssets::new(asset_loader)?))
+        };
+        let join_handle = if self.config.single_threaded.unwrap_or(false) {
+            Preload::Finished(Box::new(preload()))
+        } else {
+            Preload::Thread(std::thread::spawn(preload))
+        };
 
         debug_assert!(self.loading_join_handle.is_none());
         self.loading_join_handle = Some(join_handle);
@@ -172,7 +438,7 @@ impl Application {
 
         if is_finished {
             let handle = self.loading_join_handle.take().unwrap();
-            let (sketch, new_assets) = handle.join().unwrap()?;
+            let (sketch, new_assets) = handle.join()?;
             self.sketch = sketch;
             let image_acquire_barriers = self.assets.new_assets(new_assets);
             self.renderer.update_textures(
@@ -181,14 +447,32 @@ impl Application {
             )?;
 
             self.sim.g = G2D::new(&self.assets);
+            self.apply_config();
             self.sketch.setup(&mut self.sim);
             self.window.update_window_to_match(&mut self.sim.w)?;
+            self.transition.begin_in();
         }
         Ok(())
     }
 
+    /// Apply the config's window settings to the window state.
+    fn apply_config(&mut self) {
+        let w = &mut self.sim.w;
+        if self.config.width.is_some() || self.config.height.is_some() {
+            w.resize(
+                self.config.width.unwrap_or(w.width()),
+                self.config.height.unwrap_or(w.height()),
+            );
+        }
+        w.set_fullscreen_monitor(self.config.monitor);
+        if let Some(fullscreen) = self.config.fullscreen {
+            w.set_fullscreen(fullscreen);
+        }
+    }
+
     fn handle_event(&mut self, window_event: WindowEvent) -> Result<()> {
         self.window.handle_event(&mut self.sim.w, &window_event)?;
+        self.sketch.raw_event(&mut self.sim, &window_event);
         match window_event {
             WindowEvent::MouseButton(_, glfw::Action::Press, _) => {
                 self.sketch.mouse_pressed(&mut self.sim);
@@ -196,52 +480,224 @@ impl Application {
             WindowEvent::MouseButton(_, glfw::Action::Release, _) => {
                 self.sketch.mouse_released(&mut self.sim);
             }
-            WindowEvent::Key(key, _scancode, glfw::Action::Press, _) => {
-                self.sketch.key_pressed(&mut self.sim, key);
+            WindowEvent::Key(glfw::Key::F9, _, glfw::Action::Press, _)
+                if self.sim.debug_keybindings =>
+            {
+                self.sim.toggle_pause();
+            }
+            WindowEvent::Key(glfw::Key::F10, _, glfw::Action::Press, _)
+                if self.sim.debug_keybindings =>
+            {
+                self.sim.step_frame();
+            }
+            WindowEvent::Key(
+                key,
+                _scancode,
+                glfw::Action::Press,
+                modifiers,
+            ) => {
+                self.sketch.key_pressed(&mut self.sim, key, modifiers);
             }
             WindowEvent::Key(key, _scancode, glfw::Action::Release, _) => {
                 self.sketch.key_released(&mut self.sim, key);
             }
+            WindowEvent::Char(c) => {
+                self.sketch.char_typed(&mut self.sim, c);
+            }
             WindowEvent::CursorPos(_, _) => {
                 self.sketch.mouse_moved(&mut self.sim);
             }
+            WindowEvent::Scroll(dx, dy) => {
+                self.sketch.scrolled(&mut self.sim, dx as f32, dy as f32);
+            }
+            WindowEvent::ContentScale(_, _) => {
+                self.sketch.content_scale_changed(&mut self.sim);
+            }
             WindowEvent::Pos(_, _) => {
                 self.timer.reset_frame_time();
             }
             WindowEvent::FramebufferSize(width, height) => {
-                let was_paused = self.paused;
-                self.paused = width == 0 || height == 0;
-
-                if was_paused && !self.paused {
-                    self.timer.reset_frame_time();
-                }
+                self.zero_sized = width == 0 || height == 0;
+                self.update_paused();
+            }
+            WindowEvent::Iconify(iconified) => {
+                self.iconified = iconified;
+                self.update_paused();
             }
             _ => (),
         }
+
+        match self.sim.w.take_mouse_gesture() {
+            Some(MouseGesture::Click) => {
+                self.sketch.mouse_clicked(&mut self.sim);
+            }
+            Some(MouseGesture::DoubleClick) => {
+                self.sketch.mouse_double_clicked(&mut self.sim);
+            }
+            None => (),
+        }
         Ok(())
     }
 
-    fn update(&mut self) -> Result<()> {
-        if let Some(avg_times) = self.timer.report_avg_times() {
-            (
-                self.sim.avg_frame_time,
-                self.sim.avg_sim_time,
-                self.sim.avg_render_time,
-            ) = avg_times;
+    /// Pause when the window is minimized or has no drawing area.
+    fn update_paused(&mut self) {
+        let was_paused = self.paused;
+        self.paused = self.iconified || self.zero_sized;
+
+        if was_paused && !self.paused {
+            self.timer.reset_frame_time();
+        }
+    }
+
+    /// Update the sketch unless it is paused. Returns true when the sketch's
+    /// update was called.
+    fn simulate(&mut self) -> bool {
+        if let Some(frame_stats) = self.timer.report_stats() {
+            self.sim.frame_stats = frame_stats;
         }
 
         let total_dt = self.timer.frame_tick_tock();
-        self.sim.delta_time = total_dt.as_secs_f32();
+        self.sim.delta_time = total_dt.as_secs_f64();
 
         self.timer.simulation_tick();
-        self.sketch.update(&mut self.sim);
-        self.timer.simulation_tock();
+        let update = !self.sim.paused || self.sim.step_requested;
+        if update {
+            // Sprites are only cleared when the sketch updates so the last
+            // frame keeps rendering while paused.
+            self.sim.step_requested = false;
+            self.sim.g.reset();
+            self.sketch.update(&mut self.sim);
+            // The background is replaced on every update, so the last one
+            // keeps drawing while the sketch is paused.
+            self.renderer
+                .set_background_shader(self.sim.background_shader.take());
+            self.transition.advance(self.sim.unscaled_dt());
+            self.transition.draw(&mut self.sim.g, &self.sim.w);
+        }
+        self.sim.w.end_frame();
+        self.sim.last_update_time = self.timer.simulation_tock();
+        update
+    }
 
+    fn render(&mut self) -> Result<()> {
         self.timer.render_tick();
+        let for_sketch = std::mem::take(&mut self.sim.capture_requested);
+        let history_time =
+            self.sim.frame_history.advance(self.sim.unscaled_dt_f64());
+        // Keep asking until a capture is in flight, since a full capture
+        // queue drops the request.
+        let for_transition = self.transition.needs_last_frame()
+            && !self.pending_captures.iter().any(|p| p.for_transition);
+        if for_sketch || history_time.is_some() || for_transition {
+            if let Some(id) = self.renderer.request_capture() {
+                self.add_pending_capture(PendingCapture {
+                    id,
+                    for_sketch,
+                    history_time,
+                    for_transition,
+                });
+            }
+        }
         self.renderer
-            .render(self.window.get_framebuffer_size(), &mut self.sim.g)?;
-        self.timer.render_tock();
+            .update_images(std::mem::take(&mut self.sim.image_updates));
+        self.renderer
+            .render(self.window.get_framebuffer_size(), &self.sim.g)?;
+        self.sim.last_render_timings = self.renderer.last_frame_timings();
+        self.timer.render_tock(self.sim.last_render_timings);
+        if let Some((id, frame)) = self.renderer.take_capture() {
+            // Captures finish in order, so older requests were dropped.
+            self.pending_captures.retain(|pending| pending.id >= id);
+            if let Some(index) =
+                self.pending_captures.iter().position(|p| p.id == id)
+            {
+                let pending = self.pending_captures.remove(index);
+                if let Some(time) = pending.history_time {
+                    self.sim.frame_history.push(&frame, time);
+                }
+                if pending.for_transition {
+                    let image = self.store_last_frame(frame.clone())?;
+                    self.transition.set_last_frame(image);
+                }
+                if pending.for_sketch {
+                    self.sim.captured_frame = Some(frame);
+                }
+            }
+        }
 
         Ok(())
     }
+
+    /// Copy the outgoing sketch's last frame into the crossfade texture.
+    ///
+    /// Textures are never freed, so the texture is only uploaded once. Later
+    /// frames are scaled to its size if the window was resized and replace
+    /// its pixels before the next frame is drawn.
+    fn store_last_frame(&mut self, frame: ::image::RgbaImage) -> Result<Image> {
+        let image = match self.last_frame {
+            Some(image) => image,
+            None => {
+                let image =
+                    self.upload_image(frame, false, "last-frame".to_owned())?;
+                self.last_frame = Some(image);
+                return Ok(image);
+            }
+        };
+        let (width, height) = (image.width() as u32, image.height() as u32);
+        let frame = if frame.dimensions() == (width, height) {
+            frame
+        } else {
+            ::image::imageops::resize(
+                &frame,
+                width,
+                height,
+                ::image::imageops::FilterType::Triangle,
+            )
+        };
+        self.renderer.update_images(vec![(image, frame)]);
+        Ok(image)
+    }
+
+    /// Remember who a capture is for until it is read back. Requests which
+    /// share an id are for the same frame.
+    fn add_pending_capture(&mut self, capture: PendingCapture) {
+        match self
+            .pending_captures
+            .iter_mut()
+            .find(|p| p.id == capture.id)
+        {
+            Some(pending) => {
+                pending.for_sketch |= capture.for_sketch;
+                pending.history_time =
+                    pending.history_time.or(capture.history_time);
+                pending.for_transition |= capture.for_transition;
+            }
+            None => self.pending_captures.push(capture),
+        }
+    }
+}
+
+#[cfg(feature = "clipboard")]
+fn read_clipboard_image() -> Result<::image::RgbaImage> {
+    let image = arboard::Clipboard::new()?.get_image()?;
+    ::image::RgbaImage::from_raw(
+        image.width as u32,
+        image.height as u32,
+        image.bytes.into_owned(),
+    )
+    .context("The clipboard image has an unexpected size")
+}
+
+/// Log panics before the default hook runs.
+///
+/// The log file keeps the panic message even when the console isn't visible,
+/// like when the sketch is launched from a file browser.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            log::error!("Sketch panicked!\n{}", info);
+            default_hook(info);
+        }));
+    });
 }
@@ -1,4 +1,45 @@
-use std::time::{Duration, Instant};
+use {
+    crate::graphics::RenderTimings,
+    std::time::{Duration, Instant},
+};
+
+/// The number of buckets in each timing histogram.
+pub const HISTOGRAM_BUCKETS: usize = 16;
+
+/// The width of each histogram bucket.
+pub const HISTOGRAM_BUCKET_WIDTH: Duration = Duration::from_millis(2);
+
+/// Statistics for every sample recorded during one report period.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct TimingStats {
+    pub average: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+
+    /// The number of samples which fell into each bucket. Bucket `i` counts
+    /// durations in `[i * HISTOGRAM_BUCKET_WIDTH, (i + 1) *
+    /// HISTOGRAM_BUCKET_WIDTH)` and the last bucket also counts everything
+    /// longer.
+    pub histogram: [u32; HISTOGRAM_BUCKETS],
+}
+
+/// Timing statistics for the whole frame and each stage of the frame.
+///
+/// Statistics are reported once per second.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct FrameStats {
+    pub frame: TimingStats,
+    pub simulation: TimingStats,
+    pub render: TimingStats,
+
+    /// The render time split into the renderer's stages.
+    pub acquire: TimingStats,
+    pub upload: TimingStats,
+    pub draw: TimingStats,
+    pub present: TimingStats,
+}
 
 /// A simple timer for measuring the time between two locations in code.
 pub struct Timer {
@@ -9,9 +50,13 @@ pub struct Timer {
     simulation_time: TickTock,
     render_time: TickTock,
 
-    avg_frame_time: RollingAverage,
-    avg_simulation_time: RollingAverage,
-    avg_render_time: RollingAverage,
+    frame_samples: Samples,
+    simulation_samples: Samples,
+    render_samples: Samples,
+    acquire_samples: Samples,
+    upload_samples: Samples,
+    draw_samples: Samples,
+    present_samples: Samples,
 }
 
 impl Timer {
@@ -25,9 +70,13 @@ impl Timer {
             simulation_time: TickTock::new(),
             render_time: TickTock::new(),
 
-            avg_frame_time: RollingAverage::new(),
-            avg_simulation_time: RollingAverage::new(),
-            avg_render_time: RollingAverage::new(),
+            frame_samples: Samples::default(),
+            simulation_samples: Samples::default(),
+            render_samples: Samples::default(),
+            acquire_samples: Samples::default(),
+            upload_samples: Samples::default(),
+            draw_samples: Samples::default(),
+            present_samples: Samples::default(),
         }
     }
 
@@ -37,23 +86,25 @@ impl Timer {
 
     pub fn frame_tick_tock(&mut self) -> Duration {
         let duration = self.frame_time.tock();
-        self.avg_frame_time.record(duration);
+        self.frame_samples.record(duration);
 
         self.frame_time.tick();
         duration
     }
 
-    pub fn report_avg_times(
-        &mut self,
-    ) -> Option<(Duration, Duration, Duration)> {
+    pub fn report_stats(&mut self) -> Option<FrameStats> {
         if Instant::now() >= self.next_report_time {
             self.next_report_time = Instant::now() + self.report_rate;
 
-            Some((
-                self.avg_frame_time.report(),
-                self.avg_simulation_time.report(),
-                self.avg_render_time.report(),
-            ))
+            Some(FrameStats {
+                frame: self.frame_samples.report(),
+                simulation: self.simulation_samples.report(),
+                render: self.render_samples.report(),
+                acquire: self.acquire_samples.report(),
+                upload: self.upload_samples.report(),
+                draw: self.draw_samples.report(),
+                present: self.present_samples.report(),
+            })
         } else {
             None
         }
@@ -63,46 +114,66 @@ impl Timer {
         self.simulation_time.tick();
     }
 
-    pub fn simulation_tock(&mut self) {
+    pub fn simulation_tock(&mut self) -> Duration {
         let duration = self.simulation_time.tock();
-        self.avg_simulation_time.record(duration);
+        self.simulation_samples.record(duration);
+        duration
     }
 
     pub fn render_tick(&mut self) {
         self.render_time.tick();
     }
 
-    pub fn render_tock(&mut self) {
+    pub fn render_tock(&mut self, timings: RenderTimings) {
         let duration = self.render_time.tock();
-        self.avg_render_time.record(duration);
+        self.render_samples.record(duration);
+        self.acquire_samples.record(timings.acquire);
+        self.upload_samples.record(timings.upload);
+        self.draw_samples.record(timings.draw);
+        self.present_samples.record(timings.present);
     }
 }
 
-/// Keep track of a rolling average duration.
+/// Keep every duration recorded since the last report.
 #[derive(Default)]
-struct RollingAverage {
-    average: Duration,
-    samples: u32,
+struct Samples {
+    samples: Vec<Duration>,
 }
 
-impl RollingAverage {
-    /// Create a new rolling average.
-    pub fn new() -> Self {
-        Self::default()
-    }
-
+impl Samples {
     /// Record a duration.
     pub fn record(&mut self, d: Duration) {
-        self.average = ((self.average * self.samples) + d) / (self.samples + 1);
-        self.samples += 1;
+        self.samples.push(d);
     }
 
-    /// Get the average recorded duration since the last report.
-    pub fn report(&mut self) -> Duration {
-        let result = self.average;
-        self.average = Duration::ZERO;
-        self.samples = 0;
-        result
+    /// Get statistics for the recorded durations since the last report.
+    pub fn report(&mut self) -> TimingStats {
+        if self.samples.is_empty() {
+            return TimingStats::default();
+        }
+        self.samples.sort_unstable();
+
+        let mut histogram = [0; HISTOGRAM_BUCKETS];
+        for sample in &self.samples {
+            let bucket = (sample.as_nanos() / HISTOGRAM_BUCKET_WIDTH.as_nanos())
+                as usize;
+            histogram[bucket.min(HISTOGRAM_BUCKETS - 1)] += 1;
+        }
+
+        let count = self.samples.len();
+        let percentile =
+            |p: usize| self.samples[(count * p / 100).min(count - 1)];
+        let stats = TimingStats {
+            average: self.samples.iter().sum::<Duration>() / count as u32,
+            min: self.samples[0],
+            max: self.samples[count - 1],
+            p95: percentile(95),
+            p99: percentile(99),
+            histogram,
+        };
+
+        self.samples.clear();
+        stats
     }
 }
 
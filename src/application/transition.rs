@@ -0,0 +1,198 @@
+use {
+    crate::{
+        graphics::{Image, G2D},
+        math::Vec2,
+        window::WindowState,
+    },
+    std::time::Duration,
+};
+
+/// Controls how the screen changes when a sketch hands off to the next sketch
+/// with `Sketch::load_sketch`.
+///
+/// The first half of the duration covers the outgoing sketch, the screen
+/// stays covered while the next sketch loads, and the second half reveals the
+/// incoming sketch. A crossfade spends the whole duration revealing.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Transition {
+    /// Swap to the loading screen immediately.
+    #[default]
+    Cut,
+
+    /// Fade out to a solid color, then fade in to the next sketch.
+    Fade { color: [f32; 4], duration: Duration },
+
+    /// Wipe a solid color across the screen from left to right, then keep
+    /// wiping to the right to reveal the next sketch.
+    Wipe { color: [f32; 4], duration: Duration },
+
+    /// Fade from the outgoing sketch straight to the next sketch.
+    ///
+    /// The outgoing sketch's last frame stays on screen while the next
+    /// sketch loads, then fades out over the whole duration.
+    Crossfade { duration: Duration },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Phase {
+    Idle,
+    Out,
+    Covered,
+    In,
+}
+
+/// Tracks the progress of the current transition.
+pub(crate) struct TransitionState {
+    transition: Transition,
+    phase: Phase,
+    elapsed: f32,
+    last_frame: Option<Image>,
+}
+
+impl TransitionState {
+    pub fn new() -> Self {
+        Self {
+            transition: Transition::Cut,
+            phase: Phase::Idle,
+            elapsed: 0.0,
+            last_frame: None,
+        }
+    }
+
+    /// Begin covering the outgoing sketch.
+    pub fn begin_out(&mut self, transition: Transition) {
+        self.transition = transition;
+        self.phase = Phase::Out;
+        self.elapsed = 0.0;
+        self.last_frame = None;
+        self.advance(0.0);
+    }
+
+    /// True while a crossfade waits for a copy of the outgoing sketch's last
+    /// frame.
+    pub fn needs_last_frame(&self) -> bool {
+        matches!(self.transition, Transition::Crossfade { .. })
+            && self.phase == Phase::Out
+            && self.last_frame.is_none()
+    }
+
+    /// Use `image` as the outgoing sketch's last frame for a crossfade.
+    pub fn set_last_frame(&mut self, image: Image) {
+        self.last_frame = Some(image);
+        self.advance(0.0);
+    }
+
+    /// Begin revealing the incoming sketch.
+    ///
+    /// Does nothing unless the screen was covered by a call to `begin_out`.
+    pub fn begin_in(&mut self) {
+        if self.phase == Phase::Covered {
+            self.phase = Phase::In;
+            self.elapsed = 0.0;
+            self.advance(0.0);
+        }
+    }
+
+    /// True when the outgoing sketch is completely covered.
+    pub fn is_covered(&self) -> bool {
+        self.phase == Phase::Covered
+    }
+
+    /// Advance the transition by `dt` seconds.
+    pub fn advance(&mut self, dt: f32) {
+        self.elapsed += dt;
+        if self.progress() < 1.0 || self.needs_last_frame() {
+            return;
+        }
+        match self.phase {
+            Phase::Out => self.phase = Phase::Covered,
+            Phase::In => {
+                self.phase = Phase::Idle;
+                self.last_frame = None;
+            }
+            _ => (),
+        }
+    }
+
+    /// Draw the transition on top of everything the sketch drew this frame.
+    pub fn draw(&self, g: &mut G2D, w: &WindowState) {
+        let amount = match self.phase {
+            Phase::Idle => return,
+            Phase::Out => self.progress(),
+            Phase::Covered => 1.0,
+            Phase::In => 1.0 - self.progress(),
+        };
+
+        let original_color = g.fill_color;
+        let original_image = g.image;
+        let original_blend_mode = g.blend_mode;
+        let original_clip = g.clip;
+        g.image = crate::graphics::Image::none();
+        g.blend_mode = crate::graphics::BlendMode::Alpha;
+        g.clip = None;
+
+        let top_left = Vec2::new(w.width() * -0.5, w.height() * 0.5);
+        match self.transition {
+            Transition::Cut => (),
+            Transition::Fade { color, .. } => {
+                g.fill_color = color;
+                g.fill_color[3] *= amount;
+                g.rect(top_left, Vec2::new(w.width(), w.height()), 0.0);
+            }
+            Transition::Wipe { color, .. } => {
+                g.fill_color = color;
+                let covered = w.width() * amount;
+                let left = if self.phase == Phase::In {
+                    top_left.x + w.width() - covered
+                } else {
+                    top_left.x
+                };
+                g.rect(
+                    Vec2::new(left, top_left.y),
+                    Vec2::new(covered, w.height()),
+                    0.0,
+                );
+            }
+            Transition::Crossfade { .. } => {
+                if let Some(last_frame) = self.last_frame {
+                    g.image = last_frame;
+                    g.fill_color = [1.0, 1.0, 1.0, amount];
+                    g.rect(top_left, Vec2::new(w.width(), w.height()), 0.0);
+                }
+            }
+        }
+
+        g.fill_color = original_color;
+        g.image = original_image;
+        g.blend_mode = original_blend_mode;
+        g.clip = original_clip;
+    }
+}
+
+impl TransitionState {
+    /// How long the current phase lasts in seconds.
+    fn phase_duration(&self) -> f32 {
+        match (self.transition, self.phase) {
+            (Transition::Cut, _) => 0.0,
+            (Transition::Fade { duration, .. }, _)
+            | (Transition::Wipe { duration, .. }, _) => {
+                duration.as_secs_f32() * 0.5
+            }
+            // A crossfade covers the outgoing sketch as soon as its last
+            // frame is available and spends the whole duration fading.
+            (Transition::Crossfade { duration }, Phase::In) => {
+                duration.as_secs_f32()
+            }
+            (Transition::Crossfade { .. }, _) => 0.0,
+        }
+    }
+
+    fn progress(&self) -> f32 {
+        let phase_duration = self.phase_duration();
+        if phase_duration <= 0.0 {
+            1.0
+        } else {
+            (self.elapsed / phase_duration).min(1.0)
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use {
+    realfft::{RealFftPlanner, RealToComplex},
+    std::{collections::VecDeque, sync::Arc},
+};
+
+/// How many analyses of spectral flux are averaged to decide whether the
+/// current flux is an onset. About one second at 60 frames per second.
+const FLUX_HISTORY: usize = 60;
+
+/// Turns a stream of mono samples into a waveform, a magnitude spectrum, and
+/// onsets.
+///
+/// `Input` uses an analyzer for microphone audio, but samples can come from
+/// anywhere. Call `push` with new samples as they arrive, then `analyze`
+/// once per frame.
+pub struct Analyzer {
+    sample_rate: u32,
+    fft: Arc<dyn RealToComplex<f32>>,
+
+    /// The most recent samples, oldest first.
+    samples: VecDeque<f32>,
+    waveform: Vec<f32>,
+    spectrum: Vec<f32>,
+    previous_spectrum: Vec<f32>,
+    level: f32,
+
+    flux_history: VecDeque<f32>,
+    onset: bool,
+    analyses_since_onset: u32,
+
+    /// Onsets are reported when spectral flux is this many times larger than
+    /// its recent average.
+    pub onset_threshold: f32,
+
+    /// Flux below this is never an onset, so noise in a quiet room isn't
+    /// mistaken for a beat.
+    pub onset_floor: f32,
+
+    /// The fewest analyses between two onsets.
+    pub onset_holdoff: u32,
+}
+
+// Public API
+// ----------
+
+impl Analyzer {
+    /// Create an analyzer.
+    ///
+    /// # Params
+    ///
+    /// * `sample_rate` - the number of samples per second, used to convert
+    ///   spectrum bins into frequencies
+    /// * `window_size` - how many of the most recent samples are analyzed, 1024
+    ///   or 2048 are good choices. Larger windows resolve frequencies better
+    ///   but react to changes more slowly.
+    pub fn new(sample_rate: u32, window_size: usize) -> Self {
+        assert!(window_size >= 2, "The window needs at least two samples");
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(window_size);
+        let bins = window_size / 2 + 1;
+        Self {
+            sample_rate,
+            fft,
+            samples: vec![0.0; window_size].into(),
+            waveform: vec![0.0; window_size],
+            spectrum: vec![0.0; bins],
+            previous_spectrum: vec![0.0; bins],
+            level: 0.0,
+            flux_history: VecDeque::with_capacity(FLUX_HISTORY),
+            onset: false,
+            analyses_since_onset: u32::MAX,
+            onset_threshold: 1.5,
+            onset_floor: 0.01,
+            onset_holdoff: 6,
+        }
+    }
+
+    /// Add samples to the end of the window. Only the most recent
+    /// `window_size` samples are kept.
+    pub fn push(&mut self, samples: &[f32]) {
+        let window_size = self.waveform.len();
+        let skip = samples.len().saturating_sub(window_size);
+        self.samples.extend(&samples[skip..]);
+        let excess = self.samples.len() - window_size;
+        self.samples.drain(..excess);
+    }
+
+    /// Analyze the current window.
+    pub fn analyze(&mut self) {
+        for (dst, src) in self.waveform.iter_mut().zip(&self.samples) {
+            *dst = *src;
+        }
+
+        let sum_of_squares: f32 = self.waveform.iter().map(|s| s * s).sum();
+        self.level = (sum_of_squares / self.waveform.len() as f32).sqrt();
+
+        self.update_spectrum();
+        self.update_onset();
+    }
+
+    /// The samples in the window as of the last `analyze`, oldest first.
+    /// Values are in the range -1 to 1.
+    pub fn waveform(&self) -> &[f32] {
+        &self.waveform
+    }
+
+    /// The magnitude of each frequency bin as of the last `analyze`.
+    ///
+    /// Bin 0 is the constant offset and the last bin is half the sample
+    /// rate. A full scale sine wave has a magnitude of about 1 in its bin.
+    pub fn spectrum(&self) -> &[f32] {
+        &self.spectrum
+    }
+
+    /// The center frequency of a spectrum bin, in hertz.
+    pub fn bin_frequency(&self, bin: usize) -> f32 {
+        bin as f32 * self.sample_rate as f32 / self.waveform.len() as f32
+    }
+
+    /// The average magnitude of the bins between two frequencies, in hertz.
+    /// Useful for splitting the spectrum into bass, mids, and highs.
+    pub fn band(&self, low: f32, high: f32) -> f32 {
+        let bins_per_hz = self.waveform.len() as f32 / self.sample_rate as f32;
+        let last = self.spectrum.len() - 1;
+        let first_bin =
+            ((low * bins_per_hz).round().max(0.0) as usize).min(last);
+        let last_bin = ((high * bins_per_hz).round().max(0.0) as usize)
+            .clamp(first_bin, last);
+        let bins = &self.spectrum[first_bin..=last_bin];
+        bins.iter().sum::<f32>() / bins.len() as f32
+    }
+
+    /// The root mean square of the waveform, roughly how loud it is.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// True when the last `analyze` detected a sudden increase in energy,
+    /// such as a drum hit or a clap.
+    pub fn onset(&self) -> bool {
+        self.onset
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+// Private API
+// -----------
+
+impl Analyzer {
+    fn update_spectrum(&mut self) {
+        let size = self.waveform.len();
+        let mut input = self.fft.make_input_vec();
+        for (i, (dst, sample)) in
+            input.iter_mut().zip(&self.waveform).enumerate()
+        {
+            // A Hann window keeps the edges of the window from smearing
+            // energy across the spectrum.
+            let phase = std::f32::consts::TAU * i as f32 / (size - 1) as f32;
+            *dst = sample * 0.5 * (1.0 - phase.cos());
+        }
+        let mut output = self.fft.make_output_vec();
+        self.fft
+            .process(&mut input, &mut output)
+            .expect("The FFT buffers are sized by the planner");
+
+        std::mem::swap(&mut self.spectrum, &mut self.previous_spectrum);
+        // The Hann window halves the average amplitude, so scale by 4/size
+        // instead of 2/size.
+        let scale = 4.0 / size as f32;
+        for (magnitude, bin) in self.spectrum.iter_mut().zip(&output) {
+            *magnitude = bin.norm() * scale;
+        }
+    }
+
+    /// Spectral flux onset detection: an onset is when the total increase
+    /// in magnitude across all bins spikes above its recent average.
+    fn update_onset(&mut self) {
+        let flux: f32 = self
+            .spectrum
+            .iter()
+            .zip(&self.previous_spectrum)
+            .map(|(current, previous)| (current - previous).max(0.0))
+            .sum();
+
+        let average = if self.flux_history.is_empty() {
+            0.0
+        } else {
+            self.flux_history.iter().sum::<f32>()
+                / self.flux_history.len() as f32
+        };
+
+        self.analyses_since_onset = self.analyses_since_onset.saturating_add(1);
+        self.onset = flux > self.onset_floor
+            && flux > average * self.onset_threshold
+            && self.analyses_since_onset > self.onset_holdoff;
+        if self.onset {
+            self.analyses_since_onset = 0;
+        }
+
+        if self.flux_history.len() == FLUX_HISTORY {
+            self.flux_history.pop_front();
+        }
+        self.flux_history.push_back(flux);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, approx::assert_relative_eq};
+
+    #[test]
+    fn test_spectrum_and_onset() {
+        let mut analyzer = Analyzer::new(1024, 1024);
+        analyzer.analyze();
+        assert!(!analyzer.onset());
+
+        // A full scale sine wave at 64hz lands in bin 64.
+        let sine: Vec<f32> = (0..1024)
+            .map(|i| (std::f32::consts::TAU * 64.0 * i as f32 / 1024.0).sin())
+            .collect();
+        analyzer.push(&sine);
+        analyzer.analyze();
+
+        let peak = analyzer
+            .spectrum()
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap()
+            .0;
+        assert_eq!(peak, 64);
+        assert_relative_eq!(analyzer.bin_frequency(peak), 64.0);
+        assert_relative_eq!(analyzer.spectrum()[64], 1.0, epsilon = 1e-3);
+        assert_relative_eq!(analyzer.level(), 0.5f32.sqrt(), epsilon = 1e-3);
+        assert!(analyzer.onset());
+
+        // A steady tone is not an onset.
+        analyzer.push(&sine);
+        analyzer.analyze();
+        assert!(!analyzer.onset());
+    }
+}
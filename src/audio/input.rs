@@ -0,0 +1,172 @@
+use {
+    super::Analyzer,
+    anyhow::{bail, Context, Result},
+    rodio::cpal::{
+        self,
+        traits::{DeviceTrait, HostTrait, StreamTrait},
+        FromSample, SampleFormat, SizedSample, Stream, StreamConfig,
+    },
+    std::sync::{Arc, Mutex},
+};
+
+/// The number of samples analyzed each frame.
+const WINDOW_SIZE: usize = 1024;
+
+/// Samples waiting for `update` beyond this are dropped, so a stalled sketch
+/// doesn't buffer audio without bound.
+const MAX_PENDING: usize = WINDOW_SIZE * 8;
+
+/// Audio captured from an input device such as a microphone, analyzed once
+/// per frame for audio-reactive sketches.
+///
+/// ```ignore
+/// fn update(&mut self, sim: &mut Sim2D) {
+///     self.mic.update();
+///     let bass = self.mic.analyzer().band(20.0, 150.0);
+///     if self.mic.onset() {
+///         // flash on the beat
+///     }
+/// }
+/// ```
+///
+/// Multi-channel input is mixed down to mono. Capture stops when the input
+/// is dropped.
+pub struct Input {
+    analyzer: Analyzer,
+
+    /// Mono samples from the audio thread which haven't been analyzed yet.
+    pending: Arc<Mutex<Vec<f32>>>,
+
+    /// The samples received by the most recent update.
+    received: Vec<f32>,
+    _stream: Stream,
+}
+
+// Public API
+// ----------
+
+impl Input {
+    /// Start capturing from the system's default input device.
+    pub fn open_default() -> Result<Self> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .context("No audio input device is available!")?;
+        let supported = device
+            .default_input_config()
+            .context("Unable to get the audio input config!")?;
+        let config = supported.config();
+
+        let pending = Arc::new(Mutex::new(Vec::with_capacity(MAX_PENDING)));
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => {
+                build_stream::<f32>(&device, &config, pending.clone())
+            }
+            SampleFormat::I16 => {
+                build_stream::<i16>(&device, &config, pending.clone())
+            }
+            SampleFormat::U16 => {
+                build_stream::<u16>(&device, &config, pending.clone())
+            }
+            SampleFormat::I32 => {
+                build_stream::<i32>(&device, &config, pending.clone())
+            }
+            format => bail!("Unsupported audio input format {:?}", format),
+        }?;
+        stream
+            .play()
+            .context("Unable to start the audio input stream!")?;
+
+        Ok(Self {
+            analyzer: Analyzer::new(config.sample_rate.0, WINDOW_SIZE),
+            pending,
+            received: Vec::with_capacity(MAX_PENDING),
+            _stream: stream,
+        })
+    }
+
+    /// Analyze the audio received since the last update. Call once per
+    /// frame.
+    pub fn update(&mut self) {
+        self.received.clear();
+        {
+            let mut pending = self.pending.lock().unwrap();
+            std::mem::swap(&mut *pending, &mut self.received);
+        }
+        self.analyzer.push(&self.received);
+        self.analyzer.analyze();
+    }
+
+    /// The mono samples received by the last update, oldest first. Unlike
+    /// the waveform, these don't overlap from one frame to the next, so
+    /// they can be fed to decoders like `timecode::LtcDecoder`.
+    pub fn samples(&self) -> &[f32] {
+        &self.received
+    }
+
+    /// The input device's sample rate in Hz.
+    pub fn sample_rate(&self) -> u32 {
+        self.analyzer.sample_rate()
+    }
+
+    /// The most recent samples, oldest first. See `Analyzer::waveform`.
+    pub fn waveform(&self) -> &[f32] {
+        self.analyzer.waveform()
+    }
+
+    /// Frequency magnitudes. See `Analyzer::spectrum`.
+    pub fn spectrum(&self) -> &[f32] {
+        self.analyzer.spectrum()
+    }
+
+    /// See `Analyzer::level`.
+    pub fn level(&self) -> f32 {
+        self.analyzer.level()
+    }
+
+    /// True on frames with a beat-like jump in energy.
+    pub fn onset(&self) -> bool {
+        self.analyzer.onset()
+    }
+
+    pub fn analyzer(&self) -> &Analyzer {
+        &self.analyzer
+    }
+
+    /// Access the analyzer to tune onset detection.
+    pub fn analyzer_mut(&mut self) -> &mut Analyzer {
+        &mut self.analyzer
+    }
+}
+
+// Private API
+// -----------
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    pending: Arc<Mutex<Vec<f32>>>,
+) -> Result<Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    let stream = device
+        .build_input_stream(
+            config,
+            move |data: &[T], _| {
+                let mut pending = pending.lock().unwrap();
+                for frame in data.chunks(channels) {
+                    let sum: f32 =
+                        frame.iter().map(|&s| f32::from_sample_(s)).sum();
+                    pending.push(sum / frame.len() as f32);
+                }
+                let excess = pending.len().saturating_sub(MAX_PENDING);
+                pending.drain(..excess);
+            },
+            |error| log::warn!("Audio input error: {}", error),
+            None,
+        )
+        .context("Unable to build the audio input stream!")?;
+    Ok(stream)
+}
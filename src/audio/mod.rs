@@ -0,0 +1,13 @@
+//! Audio input for audio-reactive sketches and sound playback.
+//!
+//! This module is only available with the `audio` feature.
+
+mod analyzer;
+mod input;
+mod player;
+
+pub use self::{
+    analyzer::Analyzer,
+    input::Input,
+    player::{Player, Sound, Voice},
+};
@@ -0,0 +1,246 @@
+use {
+    anyhow::{Context, Result},
+    rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source},
+    std::{
+        io::BufReader,
+        path::Path,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+        time::Duration,
+    },
+};
+
+/// Plays sounds on the system's default output device.
+///
+/// Sounds mix with each other and play on a background thread, so sketches
+/// only need to start and stop them:
+///
+/// ```ignore
+/// let player = Player::new()?;
+/// let hit = Sound::load("assets/hit.wav")?;
+/// let mut music = player.play_loop(&Sound::load("assets/music.ogg")?)?;
+/// music.set_volume(0.5);
+///
+/// // in update
+/// if sim.w.was_key_pressed(Key::Space) {
+///     player.play(&hit)?;
+/// }
+/// music.set_pan(sim.w.mouse_pos().x / (sim.w.width() * 0.5));
+/// ```
+pub struct Player {
+    handle: OutputStreamHandle,
+
+    /// Sound stops when the stream is dropped.
+    _stream: OutputStream,
+}
+
+/// Decoded audio, ready to play any number of times.
+///
+/// Sounds are always stereo. Cloning is cheap because the samples are
+/// shared.
+#[derive(Debug, Clone)]
+pub struct Sound {
+    /// Interleaved left and right samples.
+    samples: Arc<[f32]>,
+    sample_rate: u32,
+}
+
+/// A playing sound with its own volume and pan.
+///
+/// The sound stops when the voice is dropped, so keep it for as long as it
+/// should play.
+pub struct Voice {
+    sink: Sink,
+    pan: Arc<AtomicU32>,
+}
+
+// Public API
+// ----------
+
+impl Player {
+    /// Open the default output device.
+    pub fn new() -> Result<Self> {
+        let (stream, handle) = OutputStream::try_default()
+            .context("Unable to open the audio output device!")?;
+        Ok(Self {
+            handle,
+            _stream: stream,
+        })
+    }
+
+    /// Play a sound once at full volume. Use `play_voice` to control the
+    /// sound while it plays.
+    pub fn play(&self, sound: &Sound) -> Result<()> {
+        self.start(sound, false)?.sink.detach();
+        Ok(())
+    }
+
+    /// Play a sound once.
+    pub fn play_voice(&self, sound: &Sound) -> Result<Voice> {
+        self.start(sound, false)
+    }
+
+    /// Play a sound over and over until the voice is stopped or dropped.
+    pub fn play_loop(&self, sound: &Sound) -> Result<Voice> {
+        self.start(sound, true)
+    }
+}
+
+impl Sound {
+    /// Load and decode a wav, ogg vorbis, flac, or mp3 file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Unable to open sound {:?}", path))?;
+        let decoder = Decoder::new(BufReader::new(file))
+            .with_context(|| format!("Unable to decode sound {:?}", path))?;
+        let channels = decoder.channels();
+        let sample_rate = decoder.sample_rate();
+        let samples: Vec<f32> = decoder.convert_samples().collect();
+        Ok(Self::from_samples(channels, sample_rate, &samples))
+    }
+
+    /// Create a sound from interleaved samples in the range -1 to 1.
+    ///
+    /// Mono samples are played on both channels. Channels beyond the
+    /// first two are dropped.
+    pub fn from_samples(
+        channels: u16,
+        sample_rate: u32,
+        samples: &[f32],
+    ) -> Self {
+        let stereo: Vec<f32> = match channels {
+            0 => vec![],
+            1 => samples.iter().flat_map(|&s| [s, s]).collect(),
+            n => samples
+                .chunks_exact(n as usize)
+                .flat_map(|frame| [frame[0], frame[1]])
+                .collect(),
+        };
+        Self {
+            samples: stereo.into(),
+            sample_rate: sample_rate.max(1),
+        }
+    }
+
+    /// How long the sound plays for.
+    pub fn duration(&self) -> Duration {
+        let frames = self.samples.len() / 2;
+        Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
+    }
+}
+
+impl Voice {
+    /// Set the volume, where 1.0 is the sound's original volume.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.sink.set_volume(volume.max(0.0));
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.sink.volume()
+    }
+
+    /// Move the sound between the left (-1.0) and right (1.0) speakers.
+    /// Defaults to 0.0, which plays both channels at full volume.
+    pub fn set_pan(&mut self, pan: f32) {
+        self.pan
+            .store(pan.clamp(-1.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn pan(&self) -> f32 {
+        f32::from_bits(self.pan.load(Ordering::Relaxed))
+    }
+
+    pub fn pause(&mut self) {
+        self.sink.pause();
+    }
+
+    pub fn resume(&mut self) {
+        self.sink.play();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.sink.is_paused()
+    }
+
+    /// Stop the sound. A stopped voice can't be restarted.
+    pub fn stop(&mut self) {
+        self.sink.stop();
+    }
+
+    /// True once the sound has played to the end or has been stopped.
+    pub fn is_finished(&self) -> bool {
+        self.sink.empty()
+    }
+}
+
+// Private API
+// -----------
+
+impl Player {
+    fn start(&self, sound: &Sound, looping: bool) -> Result<Voice> {
+        let sink =
+            Sink::try_new(&self.handle).context("Unable to play a sound!")?;
+        let pan = Arc::new(AtomicU32::new(0.0f32.to_bits()));
+        sink.append(SoundSource {
+            sound: sound.clone(),
+            pos: 0,
+            looping,
+            pan: pan.clone(),
+        });
+        Ok(Voice { sink, pan })
+    }
+}
+
+/// Plays a sound's samples, applying a pan which can change while the
+/// sound plays.
+struct SoundSource {
+    sound: Sound,
+    pos: usize,
+    looping: bool,
+    pan: Arc<AtomicU32>,
+}
+
+impl Iterator for SoundSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.pos == self.sound.samples.len() {
+            if !self.looping || self.sound.samples.is_empty() {
+                return None;
+            }
+            self.pos = 0;
+        }
+        let sample = self.sound.samples[self.pos];
+        let pan = f32::from_bits(self.pan.load(Ordering::Relaxed));
+        // Samples alternate between the left and right channels.
+        let right = self.pos % 2 == 1;
+        let gain = if right { 1.0 + pan } else { 1.0 - pan };
+        self.pos += 1;
+        Some(sample * gain.min(1.0))
+    }
+}
+
+impl Source for SoundSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sound.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        if self.looping {
+            None
+        } else {
+            Some(self.sound.duration())
+        }
+    }
+}
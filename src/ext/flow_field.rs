@@ -0,0 +1,121 @@
+use {
+    crate::{
+        graphics::{Image, G2D},
+        math::{noise::Noise, Grid2, Vec2, Vec2Ext},
+    },
+    std::f32::consts::TAU,
+};
+
+/// A grid of vectors covering part of the world, used to steer particles.
+///
+/// Fill the field from noise, or set the vectors directly, then move
+/// particles along the sampled direction:
+///
+/// ```ignore
+/// let mut field = FlowField::covering(window_size, 20.0);
+/// field.fill_with_noise(&noise, 0.005, self.t * 0.1);
+/// for particle in &mut self.particles {
+///     particle.vel += field.sample(particle.pos) * speed * dt;
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowField {
+    grid: Grid2<Vec2>,
+
+    /// The color used by `draw`.
+    pub debug_color: [f32; 4],
+}
+
+// Public API
+// ----------
+
+impl FlowField {
+    /// Create a field of zero vectors with `columns` by `rows` cells,
+    /// centered on the origin.
+    pub fn new(columns: usize, rows: usize, cell_size: f32) -> Self {
+        Self {
+            grid: Grid2::new(columns, rows, Vec2::zeros())
+                .with_cell_size(cell_size),
+            debug_color: [1.0, 1.0, 1.0, 0.35],
+        }
+    }
+
+    /// Create a field centered on the origin with enough cells to cover
+    /// `size`, such as the window's size.
+    pub fn covering(size: Vec2, cell_size: f32) -> Self {
+        let columns = (size.x / cell_size).ceil().max(1.0) as usize;
+        let rows = (size.y / cell_size).ceil().max(1.0) as usize;
+        Self::new(columns, rows, cell_size)
+    }
+
+    /// Point every cell in a direction chosen by Perlin noise.
+    ///
+    /// # Params
+    ///
+    /// * `noise` - the noise source
+    /// * `scale` - multiplies world positions before sampling noise. Smaller
+    ///   values give smoother fields
+    /// * `z` - the third noise coordinate. Changing it over time animates the
+    ///   field
+    pub fn fill_with_noise(&mut self, noise: &Noise, scale: f32, z: f32) {
+        self.fill_with(|pos| {
+            let angle = noise.perlin3(pos.x * scale, pos.y * scale, z) * TAU;
+            Vec2::from_angle(angle)
+        });
+    }
+
+    /// Set every cell by calling `f` with the world position of the cell's
+    /// center.
+    pub fn fill_with<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Vec2) -> Vec2,
+    {
+        let width = self.grid.width();
+        for index in 0..self.grid.cells().len() {
+            let center = self.grid.cell_center(index % width, index / width);
+            self.grid.cells_mut()[index] = f(center);
+        }
+    }
+
+    /// The underlying grid, for reading or setting individual cells.
+    pub fn grid(&self) -> &Grid2<Vec2> {
+        &self.grid
+    }
+
+    pub fn grid_mut(&mut self) -> &mut Grid2<Vec2> {
+        &mut self.grid
+    }
+
+    /// The vector at a world position, blended from the four nearest cells.
+    ///
+    /// Positions outside of the field use the nearest edge cells.
+    pub fn sample(&self, pos: Vec2) -> Vec2 {
+        if self.grid.cells().is_empty() {
+            return Vec2::zeros();
+        }
+        self.grid.sample_bilinear(pos)
+    }
+
+    /// Draw each cell's vector as a line from the cell's center.
+    ///
+    /// Vectors are scaled so a unit vector reaches the edge of its cell. The
+    /// sketch's fill color and image are left unchanged.
+    pub fn draw(&self, g: &mut G2D) {
+        let original_color = g.fill_color;
+        let original_image = g.image;
+        g.image = Image::none();
+        g.fill_color = self.debug_color;
+
+        let half_cell = self.grid.cell_size() * 0.5;
+        for (x, y, &direction) in self.grid.iter() {
+            let center = self.grid.cell_center(x, y);
+            let end = center + direction * half_cell;
+            if end != center {
+                g.line(center, end);
+            }
+        }
+
+        g.fill_color = original_color;
+        g.image = original_image;
+    }
+}
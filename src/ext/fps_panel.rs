@@ -0,0 +1,263 @@
+use {
+    crate::{
+        graphics::{FontId, Image},
+        math::Vec2,
+        Sim2D,
+    },
+    std::time::Duration,
+};
+
+/// The corner of the window where a panel is drawn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// A line of timing information which can be shown by an FpsPanel.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PanelStat {
+    /// Frames per second.
+    Fps,
+
+    /// The average frame time.
+    FrameTime,
+
+    /// The fastest and slowest recent frames.
+    FrameTimeRange,
+
+    /// The 95th and 99th percentile frame times.
+    FrameTimePercentiles,
+
+    /// The average time spent in the sketch's update.
+    SimTime,
+
+    /// The average time spent rendering.
+    RenderTime,
+
+    /// Acquire, upload, draw, and present times for the renderer.
+    RenderBreakdown,
+
+    /// The number of sprites drawn so far this frame.
+    SpriteCount,
+}
+
+/// A configurable panel of frame timing information.
+///
+/// Build the panel once and call `draw` at the end of each update so the
+/// sprite count includes everything the sketch drew:
+///
+/// ```ignore
+/// FpsPanel::default()
+///     .corner(Corner::TopRight)
+///     .background([0.0, 0.0, 0.0, 0.5])
+///     .stats(&[PanelStat::Fps, PanelStat::SpriteCount])
+///     .line(format!("Particles: {}", self.particles.len()))
+///     .draw(sim);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FpsPanel {
+    corner: Corner,
+    text_color: Option<[f32; 4]>,
+    background: Option<[f32; 4]>,
+    font: Option<FontId>,
+    stats: Vec<PanelStat>,
+    lines: Vec<String>,
+}
+
+impl Default for FpsPanel {
+    /// The panel drawn by `draw_fps_panel`: every stat except the sprite
+    /// count, in the top left corner, using the current font and fill color.
+    fn default() -> Self {
+        Self {
+            corner: Corner::TopLeft,
+            text_color: None,
+            background: None,
+            font: None,
+            stats: vec![
+                PanelStat::Fps,
+                PanelStat::FrameTime,
+                PanelStat::FrameTimeRange,
+                PanelStat::FrameTimePercentiles,
+                PanelStat::SimTime,
+                PanelStat::RenderTime,
+                PanelStat::RenderBreakdown,
+            ],
+            lines: vec![],
+        }
+    }
+}
+
+// Public API
+// ----------
+
+impl FpsPanel {
+    pub fn corner(mut self, corner: Corner) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    /// The text color. Defaults to the current fill color.
+    pub fn text_color(mut self, color: [f32; 4]) -> Self {
+        self.text_color = Some(color);
+        self
+    }
+
+    /// Draw a solid rectangle behind the text. There is no background by
+    /// default.
+    pub fn background(mut self, color: [f32; 4]) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// The font for the panel's text. Defaults to the current font.
+    pub fn font(mut self, font: FontId) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    /// Replace the stats shown by the panel. Stats are shown in the given
+    /// order.
+    pub fn stats(mut self, stats: &[PanelStat]) -> Self {
+        self.stats = stats.to_vec();
+        self
+    }
+
+    /// Add a line of custom text below the stats.
+    pub fn line(mut self, line: impl Into<String>) -> Self {
+        self.lines.push(line.into());
+        self
+    }
+
+    /// Draw the panel.
+    ///
+    /// The sketch's font, fill color, and image are left unchanged.
+    pub fn draw(&self, sim: &mut Sim2D) {
+        let text = self.text(sim);
+
+        let original_font = sim.g.font;
+        let original_color = sim.g.fill_color;
+        let original_image = sim.g.image;
+
+        if let Some(font) = self.font {
+            sim.g.font = font;
+        }
+        let size = sim.g.text_size(&text);
+        let top_left = self.top_left(sim, size);
+
+        if let Some(background) = self.background {
+            sim.g.fill_color = background;
+            sim.g.image = Image::none();
+            sim.g.rect(
+                top_left + Vec2::new(-PADDING, PADDING),
+                size + Vec2::new(PADDING, PADDING) * 2.0,
+                0.0,
+            );
+        }
+
+        sim.g.fill_color = self.text_color.unwrap_or(original_color);
+        sim.g.text(top_left, text);
+
+        sim.g.font = original_font;
+        sim.g.fill_color = original_color;
+        sim.g.image = original_image;
+    }
+}
+
+// Private API
+// -----------
+
+/// Space between the text and the edge of the background.
+const PADDING: f32 = 4.0;
+
+impl FpsPanel {
+    fn text(&self, sim: &Sim2D) -> String {
+        let stats = sim.frame_stats();
+        let mut text = String::new();
+        let mut push = |label: &str, value: String| {
+            text.push_str(&format!("|{:>12}: {}\n", label, value));
+        };
+
+        for stat in &self.stats {
+            match stat {
+                PanelStat::Fps => push(
+                    "FPS",
+                    rounded(1.0 / sim.avg_frame_time().as_secs_f32())
+                        .to_string(),
+                ),
+                PanelStat::FrameTime => {
+                    push("Frame Time", ms(&stats.frame.average))
+                }
+                PanelStat::FrameTimeRange => push(
+                    "min/max",
+                    format!(
+                        "{} / {}",
+                        ms(&stats.frame.min),
+                        ms(&stats.frame.max)
+                    ),
+                ),
+                PanelStat::FrameTimePercentiles => push(
+                    "p95/p99",
+                    format!(
+                        "{} / {}",
+                        ms(&stats.frame.p95),
+                        ms(&stats.frame.p99)
+                    ),
+                ),
+                PanelStat::SimTime => {
+                    push("Sim Time", ms(&stats.simulation.average))
+                }
+                PanelStat::RenderTime => {
+                    push("Render Time", ms(&stats.render.average))
+                }
+                PanelStat::RenderBreakdown => {
+                    push("acquire", ms(&stats.acquire.average));
+                    push("upload", ms(&stats.upload.average));
+                    push("draw", ms(&stats.draw.average));
+                    push("present", ms(&stats.present.average));
+                }
+                PanelStat::SpriteCount => {
+                    push("Sprites", sim.g.sprite_count().to_string())
+                }
+            }
+        }
+
+        for line in &self.lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    }
+
+    /// The position of the text's top left corner.
+    fn top_left(&self, sim: &Sim2D, size: Vec2) -> Vec2 {
+        let half_w = sim.w.width() * 0.5;
+        let half_h = sim.w.height() * 0.5;
+        let margin = if self.background.is_some() {
+            PADDING
+        } else {
+            0.0
+        };
+        let left = -half_w + margin;
+        let right = half_w - margin - size.x;
+        let top = half_h - margin;
+        let bottom = -half_h + margin + size.y;
+        match self.corner {
+            Corner::TopLeft => Vec2::new(left, top),
+            Corner::TopRight => Vec2::new(right, top),
+            Corner::BottomLeft => Vec2::new(left, bottom),
+            Corner::BottomRight => Vec2::new(right, bottom),
+        }
+    }
+}
+
+/// Round a floating point number to have 2 decimal places.
+fn rounded(f: f32) -> f32 {
+    (f * 100.0).round() / 100.0
+}
+
+fn ms(d: &Duration) -> String {
+    format!("{}ms", rounded(d.as_secs_f32() * 1000.0))
+}
@@ -0,0 +1,150 @@
+use {
+    crate::Sim2D,
+    anyhow::{Context, Result},
+    image::RgbaImage,
+    std::{
+        path::PathBuf,
+        sync::mpsc::{self, SyncSender},
+        thread::JoinHandle,
+    },
+};
+
+/// How many captured frames can wait for the writer thread before capture
+/// blocks.
+const QUEUE_LENGTH: usize = 16;
+
+/// Saves every Nth rendered frame as a numbered PNG, for turning a sketch
+/// into a video or an image sequence.
+///
+/// PNGs are encoded and written on a background thread so saving doesn't
+/// slow the sketch down. Call `update` once per update:
+///
+/// ```ignore
+/// fn update(&mut self, sim: &mut Sim2D) {
+///     self.exporter.update(sim);
+///     // ...
+/// }
+/// ```
+///
+/// Frames are saved as `frame_00000.png`, `frame_00001.png`, and so on.
+pub struct FrameExporter {
+    directory: PathBuf,
+    every_nth: u64,
+    enabled: bool,
+
+    /// Frames since the last capture, wrapping at `every_nth`.
+    frame_count: u64,
+    next_index: u64,
+    sender: Option<SyncSender<(PathBuf, RgbaImage)>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+// Public API
+// ----------
+
+impl FrameExporter {
+    /// Create a disabled exporter which writes frames into `directory`.
+    ///
+    /// The directory is created if it doesn't exist.
+    pub fn new(directory: impl Into<PathBuf>) -> Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory).with_context(|| {
+            format!("Unable to create frame directory {:?}", directory)
+        })?;
+        Ok(Self {
+            directory,
+            every_nth: 1,
+            enabled: false,
+            frame_count: 0,
+            next_index: 0,
+            sender: None,
+            writer: None,
+        })
+    }
+
+    /// Save one of every `n` frames. Defaults to every frame.
+    pub fn every(mut self, n: u64) -> Self {
+        self.every_nth = n.max(1);
+        self
+    }
+
+    /// Start or stop saving frames.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn toggle_enabled(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// How many frames have been sent to be saved.
+    pub fn frames_saved(&self) -> u64 {
+        self.next_index
+    }
+
+    /// Send the most recently captured frame to be saved, then capture the
+    /// current frame if it is one of the frames to keep.
+    pub fn update(&mut self, sim: &mut Sim2D) {
+        if let Some(frame) = sim.take_captured_frame() {
+            self.save(frame);
+        }
+        if !self.enabled {
+            return;
+        }
+        if self.frame_count == 0 {
+            sim.capture_frame();
+        }
+        self.frame_count = (self.frame_count + 1) % self.every_nth;
+    }
+}
+
+impl Drop for FrameExporter {
+    /// Wait for every queued frame to be written.
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            if writer.join().is_err() {
+                log::error!("The frame writer thread panicked");
+            }
+        }
+    }
+}
+
+// Private API
+// -----------
+
+impl FrameExporter {
+    fn save(&mut self, frame: RgbaImage) {
+        let path = self
+            .directory
+            .join(format!("frame_{:05}.png", self.next_index));
+        self.next_index += 1;
+
+        if self.sender.is_none() {
+            let (sender, receiver) = mpsc::sync_channel(QUEUE_LENGTH);
+            self.sender = Some(sender);
+            self.writer = Some(std::thread::spawn(move || {
+                for (path, frame) in receiver {
+                    write_frame(path, frame);
+                }
+            }));
+        }
+
+        // Blocks when the writer falls behind, which slows the sketch down
+        // rather than letting queued frames use unbounded memory.
+        let sender = self.sender.as_ref().unwrap();
+        if sender.send((path, frame)).is_err() {
+            log::error!("The frame writer thread has stopped");
+        }
+    }
+}
+
+fn write_frame(path: PathBuf, frame: RgbaImage) {
+    if let Err(error) = frame.save(&path) {
+        log::error!("Unable to save frame {:?}: {:?}", path, error);
+    }
+}
@@ -0,0 +1,147 @@
+//! Debug shapes for visualizing a simulation's internal state.
+//!
+//! Gizmos draw with the current fill color and line width, but always
+//! untextured. Every gizmo can be switched off at once with `set_enabled`, so
+//! debug drawing can stay in a sketch and be hidden with a key press.
+
+use {
+    crate::{
+        graphics::{Image, G2D},
+        math::{Rect, Vec2, Vec2Ext},
+    },
+    std::sync::atomic::{AtomicBool, Ordering},
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// The number of line segments used for a full circle in `angle_arc`.
+const SEGMENTS_PER_TURN: f32 = 48.0;
+
+/// Enable or disable every gizmo. Gizmos are enabled by default.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn toggle_enabled() {
+    ENABLED.fetch_xor(true, Ordering::Relaxed);
+}
+
+/// Draw an arrow from `start` to `end` with the head at `end`.
+pub fn arrow(g: &mut G2D, start: Vec2, end: Vec2) {
+    with_plain_lines(g, |g| draw_arrow(g, start, end));
+}
+
+/// Draw an x-shaped cross centered on `center`.
+pub fn cross(g: &mut G2D, center: Vec2, size: f32) {
+    with_plain_lines(g, |g| {
+        let half = size * 0.5;
+        g.line(
+            center + Vec2::new(-half, -half),
+            center + Vec2::new(half, half),
+        );
+        g.line(
+            center + Vec2::new(-half, half),
+            center + Vec2::new(half, -half),
+        );
+    });
+}
+
+/// Draw a small cross at `pos` with a text label beside it.
+pub fn labeled_point(g: &mut G2D, pos: Vec2, label: impl AsRef<str>) {
+    let size = 6.0 + g.line_width * 2.0;
+    cross(g, pos, size);
+    if is_enabled() {
+        g.text(pos + Vec2::new(size, size), label);
+    }
+}
+
+/// Draw the outline of a rectangle.
+pub fn bounding_box(g: &mut G2D, rect: &Rect) {
+    with_plain_lines(g, |g| {
+        let top_left = Vec2::new(rect.min.x, rect.max.y);
+        let bottom_right = Vec2::new(rect.max.x, rect.min.y);
+        g.line(rect.min, top_left);
+        g.line(top_left, rect.max);
+        g.line(rect.max, bottom_right);
+        g.line(bottom_right, rect.min);
+    });
+}
+
+/// Draw an arrow from `pos` showing a velocity.
+///
+/// # Params
+///
+/// * `scale` - multiplies the velocity to get the arrow's length, so small or
+///   large velocities can be made visible
+pub fn velocity(g: &mut G2D, pos: Vec2, velocity: Vec2, scale: f32) {
+    arrow(g, pos, pos + velocity * scale);
+}
+
+/// Draw an arc around `center` from `start_angle` to `end_angle`, plus the
+/// two radii which bound it.
+///
+/// Angles are in radians, counter-clockwise from the positive x axis.
+pub fn angle_arc(
+    g: &mut G2D,
+    center: Vec2,
+    radius: f32,
+    start_angle: f32,
+    end_angle: f32,
+) {
+    with_plain_lines(g, |g| {
+        let sweep = end_angle - start_angle;
+        let segments = ((sweep.abs() / std::f32::consts::TAU)
+            * SEGMENTS_PER_TURN)
+            .ceil()
+            .max(1.0) as usize;
+
+        let point_at = |angle: f32| center + Vec2::from_angle(angle) * radius;
+        g.line(center, point_at(start_angle));
+        g.line(center, point_at(end_angle));
+
+        let mut previous = point_at(start_angle);
+        for i in 1..=segments {
+            let t = i as f32 / segments as f32;
+            let next = point_at(start_angle + sweep * t);
+            g.line(previous, next);
+            previous = next;
+        }
+    });
+}
+
+// Private API
+// -----------
+
+/// Call `f` with the image cleared so lines are drawn untextured, then
+/// restore the image. Does nothing when gizmos are disabled.
+fn with_plain_lines<F>(g: &mut G2D, f: F)
+where
+    F: FnOnce(&mut G2D),
+{
+    if !is_enabled() {
+        return;
+    }
+    let original_image = g.image;
+    g.image = Image::none();
+    f(g);
+    g.image = original_image;
+}
+
+fn draw_arrow(g: &mut G2D, start: Vec2, end: Vec2) {
+    let d = end - start;
+    let length = d.magnitude();
+    if length <= f32::EPSILON {
+        return;
+    }
+    g.line(start, end);
+
+    let head_length = (g.line_width * 6.0).max(8.0).min(length * 0.5);
+    let back = -d / length * head_length;
+    let side = back.perpendicular() * 0.5;
+    g.line(end, end + back + side);
+    g.line(end, end + back - side);
+}
@@ -0,0 +1,115 @@
+use crate::{graphics::Image, math::Vec2, Sim2D};
+
+/// Colors and line widths for `draw_grid`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GridStyle {
+    /// The color of ordinary grid lines.
+    pub minor_color: [f32; 4],
+
+    /// The color of every `major_every`th grid line.
+    pub major_color: [f32; 4],
+
+    /// The color of the x and y axes.
+    pub axis_color: [f32; 4],
+
+    /// How many minor lines there are between major lines. Zero disables
+    /// major lines.
+    pub major_every: u32,
+
+    pub minor_width: f32,
+    pub major_width: f32,
+    pub axis_width: f32,
+}
+
+impl Default for GridStyle {
+    fn default() -> Self {
+        Self {
+            minor_color: [0.5, 0.5, 0.5, 0.25],
+            major_color: [0.5, 0.5, 0.5, 0.6],
+            axis_color: [0.9, 0.3, 0.3, 0.9],
+            major_every: 5,
+            minor_width: 1.0,
+            major_width: 1.0,
+            axis_width: 2.0,
+        }
+    }
+}
+
+/// Draw a grid of lines `spacing` world units apart which covers the window,
+/// with the x and y axes highlighted.
+///
+/// The grid is drawn in the same coordinates as every other sprite, so it
+/// lines up with the sketch's world space. Call it at the start of update so
+/// the grid is drawn behind everything else. The sketch's fill color, line
+/// width, and image are left unchanged.
+pub fn draw_grid(sim: &mut Sim2D, spacing: f32, style: &GridStyle) {
+    if spacing <= 0.0 {
+        return;
+    }
+
+    let half_w = sim.w.width() * 0.5;
+    let half_h = sim.w.height() * 0.5;
+
+    let original_color = sim.g.fill_color;
+    let original_width = sim.g.line_width;
+    let original_image = sim.g.image;
+    sim.g.image = Image::none();
+
+    // Draw each kind of line in its own pass so axes and major lines are
+    // always on top of minor lines.
+    for kind in [LineKind::Minor, LineKind::Major, LineKind::Axis] {
+        let (color, width) = kind.style(style);
+        sim.g.fill_color = color;
+        sim.g.line_width = width;
+
+        let first_x = (-half_w / spacing).ceil() as i64;
+        let last_x = (half_w / spacing).floor() as i64;
+        for i in (first_x..=last_x).filter(|&i| kind.matches(i, style)) {
+            let x = i as f32 * spacing;
+            sim.g.line(Vec2::new(x, -half_h), Vec2::new(x, half_h));
+        }
+
+        let first_y = (-half_h / spacing).ceil() as i64;
+        let last_y = (half_h / spacing).floor() as i64;
+        for i in (first_y..=last_y).filter(|&i| kind.matches(i, style)) {
+            let y = i as f32 * spacing;
+            sim.g.line(Vec2::new(-half_w, y), Vec2::new(half_w, y));
+        }
+    }
+
+    sim.g.fill_color = original_color;
+    sim.g.line_width = original_width;
+    sim.g.image = original_image;
+}
+
+// Private API
+// -----------
+
+#[derive(Copy, Clone)]
+enum LineKind {
+    Minor,
+    Major,
+    Axis,
+}
+
+impl LineKind {
+    /// Check if the line at `index` multiples of the spacing is this kind of
+    /// line.
+    fn matches(self, index: i64, style: &GridStyle) -> bool {
+        let is_major =
+            style.major_every > 0 && index % style.major_every as i64 == 0;
+        match self {
+            LineKind::Axis => index == 0,
+            LineKind::Major => index != 0 && is_major,
+            LineKind::Minor => index != 0 && !is_major,
+        }
+    }
+
+    fn style(self, style: &GridStyle) -> ([f32; 4], f32) {
+        match self {
+            LineKind::Minor => (style.minor_color, style.minor_width),
+            LineKind::Major => (style.major_color, style.major_width),
+            LineKind::Axis => (style.axis_color, style.axis_width),
+        }
+    }
+}
@@ -1,35 +1,45 @@
-use {
-    crate::{math::Vec2, Sim2D},
-    std::time::Duration,
+pub mod gizmos;
+pub mod palette;
+pub mod spatial;
+pub mod steering;
+pub mod turtle;
+pub mod ui;
+
+mod flow_field;
+mod fps_panel;
+mod frame_exporter;
+mod grid;
+mod ndi_output;
+mod params;
+mod particles;
+mod perf_hud;
+mod plot;
+#[cfg(feature = "remote")]
+mod remote_control;
+
+#[cfg(feature = "remote")]
+pub use self::remote_control::RemoteControl;
+pub use self::{
+    flow_field::FlowField,
+    fps_panel::{Corner, FpsPanel, PanelStat},
+    frame_exporter::FrameExporter,
+    grid::{draw_grid, GridStyle},
+    ndi_output::NdiOutput,
+    palette::Palette,
+    params::Params,
+    particles::{ColorCurve, Curve, ParticleEmitter},
+    perf_hud::draw_perf_hud,
+    plot::Plot,
+    spatial::{QuadTree, SpatialHash},
+    steering::{Agent, Flocking},
+    turtle::{LSystem, Turtle},
 };
+use crate::Sim2D;
 
 /// Render the current FPS and simulation timing information to the top left
 /// of the screen.
+///
+/// Use `FpsPanel` to choose the corner, colors, font, and stats.
 pub fn draw_fps_panel(sim: &mut Sim2D) {
-    sim.g.text(
-        Vec2::new(sim.w.width() * -0.5, sim.w.height() * 0.5),
-        format!(
-            indoc::indoc!(
-                "
-                |         FPS: {}
-                |  Frame Time: {}ms
-                |    Sim Time: {}ms
-                | Render Time: {}ms
-                "
-            ),
-            rounded(1.0 / sim.avg_frame_time().as_secs_f32()),
-            get_rounded_ms(sim.avg_frame_time()),
-            get_rounded_ms(sim.avg_sim_time()),
-            get_rounded_ms(sim.avg_render_time()),
-        ),
-    );
-}
-
-/// Round a floating point number to have 2 decimal places.
-fn rounded(f: f32) -> f32 {
-    (f * 100.0).round() / 100.0
-}
-
-fn get_rounded_ms(d: &Duration) -> f32 {
-    rounded(d.as_secs_f32() * 1000.0)
+    FpsPanel::default().draw(sim);
 }
@@ -0,0 +1,232 @@
+use {
+    crate::Sim2D,
+    anyhow::{bail, Context, Result},
+    image::RgbaImage,
+    libloading::Library,
+    std::{
+        ffi::{c_void, CString},
+        os::raw::{c_char, c_int},
+        path::PathBuf,
+        ptr,
+    },
+};
+
+/// NDI's FourCC code for 8 bit RGBA pixels.
+const FOURCC_RGBA: c_int = i32::from_le_bytes(*b"RGBA");
+
+/// NDI_frame_format_type_progressive
+const FRAME_FORMAT_PROGRESSIVE: c_int = 1;
+
+/// NDIlib_send_timecode_synthesize, NDI picks the timecode.
+const TIMECODE_SYNTHESIZE: i64 = i64::MAX;
+
+/// Shares rendered frames with other applications on the network, such as
+/// OBS or Resolume, as an NDI source.
+///
+/// The NDI runtime is loaded when the output is created, so it must be
+/// installed but isn't needed to build Sim2D. Call `update` once per update:
+///
+/// ```ignore
+/// fn update(&mut self, sim: &mut Sim2D) {
+///     self.ndi.update(sim);
+///     // ...
+/// }
+/// ```
+///
+/// Frames are read back with `Sim2D::capture_frame`, so they are sent a few
+/// updates after they are drawn. The
+/// output can't be combined with anything else which takes captured frames,
+/// such as `FrameExporter`.
+pub struct NdiOutput {
+    api: NdiApi,
+    sender: *mut c_void,
+    enabled: bool,
+
+    /// Keeps the source name alive for the sender.
+    _name: CString,
+}
+
+// Public API
+// ----------
+
+impl NdiOutput {
+    /// Create an enabled NDI source called `name`.
+    pub fn new(name: &str) -> Result<Self> {
+        let api = NdiApi::load()?;
+        let name =
+            CString::new(name).context("The NDI name can't contain nul")?;
+        let settings = SendCreate {
+            p_ndi_name: name.as_ptr(),
+            p_groups: ptr::null(),
+            clock_video: false,
+            clock_audio: false,
+        };
+        let sender = unsafe { (api.send_create)(&settings) };
+        if sender.is_null() {
+            bail!("Unable to create the NDI sender {:?}", name);
+        }
+        Ok(Self {
+            api,
+            sender,
+            enabled: true,
+            _name: name,
+        })
+    }
+
+    /// Start or stop sending frames. Receivers keep showing the last frame
+    /// while sending is stopped.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Send the most recently captured frame, then capture the current
+    /// frame.
+    pub fn update(&mut self, sim: &mut Sim2D) {
+        if let Some(frame) = sim.take_captured_frame() {
+            self.send(&frame);
+        }
+        if self.enabled {
+            sim.capture_frame();
+        }
+    }
+
+    /// Send a single image. Blocks while NDI compresses the frame.
+    pub fn send(&mut self, frame: &RgbaImage) {
+        let frame = VideoFrame {
+            xres: frame.width() as c_int,
+            yres: frame.height() as c_int,
+            four_cc: FOURCC_RGBA,
+            frame_rate_n: 60_000,
+            frame_rate_d: 1_000,
+            picture_aspect_ratio: 0.0,
+            frame_format_type: FRAME_FORMAT_PROGRESSIVE,
+            timecode: TIMECODE_SYNTHESIZE,
+            p_data: frame.as_ptr(),
+            line_stride_in_bytes: frame.width() as c_int * 4,
+            p_metadata: ptr::null(),
+            timestamp: 0,
+        };
+        unsafe {
+            // The synchronous send copies the pixels before it returns.
+            (self.api.send_send_video_v2)(self.sender, &frame);
+        }
+    }
+}
+
+impl Drop for NdiOutput {
+    fn drop(&mut self) {
+        unsafe {
+            (self.api.send_destroy)(self.sender);
+        }
+    }
+}
+
+// Private API
+// -----------
+
+/// NDIlib_send_create_t
+#[repr(C)]
+struct SendCreate {
+    p_ndi_name: *const c_char,
+    p_groups: *const c_char,
+    clock_video: bool,
+    clock_audio: bool,
+}
+
+/// NDIlib_video_frame_v2_t
+#[repr(C)]
+struct VideoFrame {
+    xres: c_int,
+    yres: c_int,
+    four_cc: c_int,
+    frame_rate_n: c_int,
+    frame_rate_d: c_int,
+    picture_aspect_ratio: f32,
+    frame_format_type: c_int,
+    timecode: i64,
+    p_data: *const u8,
+    line_stride_in_bytes: c_int,
+    p_metadata: *const c_char,
+    timestamp: i64,
+}
+
+/// The NDI runtime functions used by `NdiOutput`.
+struct NdiApi {
+    send_create: unsafe extern "C" fn(*const SendCreate) -> *mut c_void,
+    send_destroy: unsafe extern "C" fn(*mut c_void),
+    send_send_video_v2: unsafe extern "C" fn(*mut c_void, *const VideoFrame),
+    destroy: unsafe extern "C" fn(),
+
+    /// The function pointers are only valid while the library is loaded.
+    _library: Library,
+}
+
+impl NdiApi {
+    fn load() -> Result<Self> {
+        let library = runtime_paths()
+            .into_iter()
+            .find_map(|path| unsafe { Library::new(&path).ok() })
+            .context(
+                "Unable to load the NDI runtime, is it installed? See \
+                 https://ndi.video/tools/",
+            )?;
+        unsafe {
+            let initialize = *library
+                .get::<unsafe extern "C" fn() -> bool>(b"NDIlib_initialize\0")
+                .context("NDIlib_initialize is missing")?;
+            let api = Self {
+                send_create: *library
+                    .get(b"NDIlib_send_create\0")
+                    .context("NDIlib_send_create is missing")?,
+                send_destroy: *library
+                    .get(b"NDIlib_send_destroy\0")
+                    .context("NDIlib_send_destroy is missing")?,
+                send_send_video_v2: *library
+                    .get(b"NDIlib_send_send_video_v2\0")
+                    .context("NDIlib_send_send_video_v2 is missing")?,
+                destroy: *library
+                    .get(b"NDIlib_destroy\0")
+                    .context("NDIlib_destroy is missing")?,
+                _library: library,
+            };
+            if !initialize() {
+                bail!("NDI is not supported on this CPU");
+            }
+            Ok(api)
+        }
+    }
+}
+
+impl Drop for NdiApi {
+    fn drop(&mut self) {
+        unsafe { (self.destroy)() }
+    }
+}
+
+/// Where the NDI runtime might be, in the order to try them.
+fn runtime_paths() -> Vec<PathBuf> {
+    let file_name = if cfg!(target_os = "windows") {
+        "Processing.NDI.Lib.x64.dll"
+    } else if cfg!(target_os = "macos") {
+        "libndi.dylib"
+    } else {
+        "libndi.so"
+    };
+    let mut paths: Vec<PathBuf> = ["NDI_RUNTIME_DIR_V6", "NDI_RUNTIME_DIR_V5"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(|dir| PathBuf::from(dir).join(file_name))
+        .collect();
+    paths.push(file_name.into());
+    if cfg!(target_os = "macos") {
+        paths.push("/usr/local/lib/libndi.dylib".into());
+    } else if cfg!(target_os = "linux") {
+        paths.push("libndi.so.6".into());
+        paths.push("libndi.so.5".into());
+    }
+    paths
+}
@@ -0,0 +1,293 @@
+//! Color palettes and color harmonies.
+//!
+//! Colors are `[f32; 4]` RGBA values in the 0-1 range, the same as
+//! `G2D::fill_color`. Palettes can be loaded from the GIMP `.gpl` and hex
+//! list formats which sites like Lospec export:
+//!
+//! ```ignore
+//! let palette = Palette::load("assets/palettes/sweetie-16.gpl")?;
+//! sim.g.fill_color = palette.get(i);          // cycles through the colors
+//! sim.g.fill_color = palette.sample(t);       // blends between neighbors
+//! let [a, b, c] = palette::triadic(palette.get(0));
+//! ```
+
+use {
+    crate::math::random::Random,
+    anyhow::{bail, Context, Result},
+    std::path::Path,
+};
+
+/// An ordered list of colors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    colors: Vec<[f32; 4]>,
+}
+
+// Public API
+// ----------
+
+impl Palette {
+    /// Create a palette from a list of colors. Panics if `colors` is empty.
+    pub fn new(colors: Vec<[f32; 4]>) -> Self {
+        assert!(!colors.is_empty(), "A palette needs at least one color");
+        Self { colors }
+    }
+
+    /// Load a palette file. Files ending in `.gpl` are read as GIMP palettes
+    /// and everything else is read as a hex list.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Unable to read palette {:?}", path))?;
+        let is_gpl = path
+            .extension()
+            .map(|extension| extension.eq_ignore_ascii_case("gpl"))
+            .unwrap_or(false);
+        let palette = if is_gpl {
+            Self::from_gpl(&contents)
+        } else {
+            Self::from_hex_list(&contents)
+        };
+        palette.with_context(|| format!("Unable to parse palette {:?}", path))
+    }
+
+    /// Parse a list of hex colors, one per line, like `ff0044` or `#ff0044`.
+    /// Blank lines and lines starting with `;` are ignored.
+    pub fn from_hex_list(contents: &str) -> Result<Self> {
+        let mut colors = vec![];
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            let color = parse_hex(line).with_context(|| {
+                format!("Invalid color on line {}", index + 1)
+            })?;
+            colors.push(color);
+        }
+        Self::from_parsed(colors)
+    }
+
+    /// Parse a GIMP palette, where each color is a line of three 0-255
+    /// channels followed by an optional name.
+    pub fn from_gpl(contents: &str) -> Result<Self> {
+        let mut lines = contents.lines().enumerate();
+        match lines.next() {
+            Some((_, header)) if header.trim() == "GIMP Palette" => (),
+            _ => bail!("Expected the file to start with 'GIMP Palette'"),
+        }
+
+        let mut colors = vec![];
+        for (index, line) in lines {
+            let line = line.trim();
+            if line.is_empty()
+                || line.starts_with('#')
+                || line.starts_with("Name:")
+                || line.starts_with("Columns:")
+            {
+                continue;
+            }
+            let channels = line
+                .split_whitespace()
+                .take(3)
+                .map(|channel| channel.parse::<u8>())
+                .collect::<Result<Vec<u8>, _>>()
+                .with_context(|| {
+                    format!("Invalid color on line {}", index + 1)
+                })?;
+            if channels.len() != 3 {
+                bail!("Expected three channels on line {}", index + 1);
+            }
+            colors.push(from_bytes(channels[0], channels[1], channels[2]));
+        }
+        Self::from_parsed(colors)
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Always false, palettes have at least one color.
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    pub fn colors(&self) -> &[[f32; 4]] {
+        &self.colors
+    }
+
+    /// Get a color by index, wrapping around so any index cycles through the
+    /// palette.
+    pub fn get(&self, index: usize) -> [f32; 4] {
+        self.colors[index % self.colors.len()]
+    }
+
+    /// Treat the palette as a gradient and blend between the two nearest
+    /// colors. `t` is clamped to the range 0-1, where 0 is the first color and
+    /// 1 is the last.
+    pub fn sample(&self, t: f32) -> [f32; 4] {
+        let last = self.colors.len() - 1;
+        let position = t.clamp(0.0, 1.0) * last as f32;
+        let index = (position.floor() as usize).min(last);
+        let next = (index + 1).min(last);
+        lerp(
+            self.colors[index],
+            self.colors[next],
+            position - index as f32,
+        )
+    }
+
+    /// Like `sample`, but `t` wraps around and the last color blends back
+    /// into the first, so the palette can be cycled smoothly over time.
+    pub fn sample_cyclic(&self, t: f32) -> [f32; 4] {
+        let count = self.colors.len();
+        let position = t.rem_euclid(1.0) * count as f32;
+        let index = (position.floor() as usize).min(count - 1);
+        lerp(
+            self.colors[index],
+            self.colors[(index + 1) % count],
+            position - index as f32,
+        )
+    }
+
+    /// Pick a color at random.
+    pub fn random(&self, rng: &mut Random) -> [f32; 4] {
+        self.colors[rng.random_range(0..self.colors.len())]
+    }
+}
+
+/// Parse a color like `ff0044`, `#ff0044`, or `#ff004480`. Colors without an
+/// alpha channel are opaque.
+pub fn parse_hex(hex: &str) -> Result<[f32; 4]> {
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() != 6 && hex.len() != 8 {
+        bail!("Expected 6 or 8 hex digits, found {:?}", hex);
+    }
+    let channel = |index: usize| {
+        u8::from_str_radix(hex.get(index..index + 2).unwrap_or(""), 16)
+            .with_context(|| format!("Invalid hex color {:?}", hex))
+    };
+    let mut color = from_bytes(channel(0)?, channel(2)?, channel(4)?);
+    if hex.len() == 8 {
+        color[3] = channel(6)? as f32 / 255.0;
+    }
+    Ok(color)
+}
+
+/// Blend between two colors, including alpha.
+pub fn lerp(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    let mut color = a;
+    for (channel, target) in color.iter_mut().zip(b) {
+        *channel += (target - *channel) * t;
+    }
+    color
+}
+
+/// Rotate a color's hue by a fraction of a full turn, keeping its
+/// saturation, value, and alpha.
+pub fn rotate_hue(color: [f32; 4], turns: f32) -> [f32; 4] {
+    let [h, s, v, a] = rgb_to_hsv(color);
+    hsv_to_rgb([(h + turns).rem_euclid(1.0), s, v, a])
+}
+
+/// The color on the opposite side of the color wheel.
+pub fn complementary(color: [f32; 4]) -> [f32; 4] {
+    rotate_hue(color, 0.5)
+}
+
+/// The color plus the two colors a third of the color wheel away.
+pub fn triadic(color: [f32; 4]) -> [[f32; 4]; 3] {
+    [
+        color,
+        rotate_hue(color, 1.0 / 3.0),
+        rotate_hue(color, 2.0 / 3.0),
+    ]
+}
+
+/// The color plus its neighbors `spread` turns away on either side of the
+/// color wheel. A spread of 1/12 gives the classic analogous scheme.
+pub fn analogous(color: [f32; 4], spread: f32) -> [[f32; 4]; 3] {
+    [rotate_hue(color, -spread), color, rotate_hue(color, spread)]
+}
+
+/// Convert RGBA to HSVA. Every component, including hue, is in the range 0-1.
+pub fn rgb_to_hsv([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let hue = if delta <= 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    let saturation = if max <= 0.0 { 0.0 } else { delta / max };
+    [hue / 6.0, saturation, max, a]
+}
+
+/// Convert HSVA to RGBA. Every component, including hue, is in the range 0-1.
+pub fn hsv_to_rgb([h, s, v, a]: [f32; 4]) -> [f32; 4] {
+    let sector = h.rem_euclid(1.0) * 6.0;
+    let c = v * s;
+    let x = c * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    [r + m, g + m, b + m, a]
+}
+
+// Private API
+// -----------
+
+impl Palette {
+    fn from_parsed(colors: Vec<[f32; 4]>) -> Result<Self> {
+        if colors.is_empty() {
+            bail!("The palette has no colors");
+        }
+        Ok(Self { colors })
+    }
+}
+
+fn from_bytes(r: u8, g: u8, b: u8) -> [f32; 4] {
+    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, approx::assert_relative_eq};
+
+    #[test]
+    fn test_parse_and_harmonies() -> Result<()> {
+        let hex = Palette::from_hex_list("; lospec\nff0000\n#0000ff\n")?;
+        let gpl = Palette::from_gpl(indoc::indoc!(
+            "
+            GIMP Palette
+            Name: Test
+            Columns: 2
+            #
+            255   0   0	red
+              0   0 255	blue
+            "
+        ))?;
+        assert_eq!(hex, gpl);
+        assert_eq!(hex.get(3), [0.0, 0.0, 1.0, 1.0]);
+        assert_relative_eq!(&hex.sample(0.5)[..], &[0.5, 0.0, 0.5, 1.0][..]);
+        assert!(parse_hex("#12345").is_err());
+
+        let [red, green, blue] = triadic([1.0, 0.0, 0.0, 1.0]);
+        assert_relative_eq!(&red[..], &[1.0, 0.0, 0.0, 1.0][..]);
+        assert_relative_eq!(&green[..], &[0.0, 1.0, 0.0, 1.0][..]);
+        assert_relative_eq!(&blue[..], &[0.0, 0.0, 1.0, 1.0][..]);
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use {
+    super::ui::Ui,
+    crate::{math::Vec2, Sim2D},
+    std::{collections::HashMap, ops::Range, path::PathBuf},
+};
+#[cfg(feature = "config")]
+use {
+    anyhow::{Context, Result},
+    std::path::Path,
+    toml::{Table, Value},
+};
+
+/// A registry of named values which can be tuned while a sketch runs.
+///
+/// Parameters are declared the first time they are read, so a sketch can
+/// read them wherever it needs them:
+///
+/// ```ignore
+/// fn update(&mut self, sim: &mut Sim2D) {
+///     let gravity = self.params.f32("gravity", 9.8, 0.0..50.0);
+///     let trails = self.params.bool("trails", true);
+///     // ...
+///     self.params.draw_ui(sim, Vec2::new(-sim.w.width() * 0.5, 0.0));
+/// }
+/// ```
+///
+/// With the `config` feature, params created with `load` remember their file.
+/// The UI has a save button which writes every value back to that file, and
+/// the saved values replace the defaults the next time the sketch runs. The
+/// file is TOML, like `Sim2DConfig`.
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    #[cfg_attr(not(feature = "config"), allow(dead_code))]
+    path: Option<PathBuf>,
+    params: Vec<Param>,
+
+    /// Values read from the file for parameters which haven't been declared
+    /// yet.
+    saved: HashMap<String, SavedValue>,
+
+    ui: Ui,
+}
+
+#[derive(Debug, Clone)]
+struct Param {
+    name: String,
+    kind: ParamKind,
+}
+
+#[derive(Debug, Clone)]
+enum ParamKind {
+    F32 { value: f32, range: Range<f32> },
+    Bool(bool),
+}
+
+/// A value from the params file.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "config"), allow(dead_code))]
+enum SavedValue {
+    Number(f32),
+    Bool(bool),
+}
+
+// Public API
+// ----------
+
+impl Params {
+    /// Create params which are not saved to a file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create params which are saved to `path`, reading any values which
+    /// were saved before. A missing file is not an error.
+    #[cfg(feature = "config")]
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let saved = read_params(&path)?
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let value = match value {
+                    Value::Float(value) => SavedValue::Number(value as f32),
+                    Value::Integer(value) => SavedValue::Number(value as f32),
+                    Value::Boolean(value) => SavedValue::Bool(value),
+                    _ => return None,
+                };
+                Some((name, value))
+            })
+            .collect();
+        Ok(Self {
+            path: Some(path),
+            saved,
+            ..Self::default()
+        })
+    }
+
+    /// Read a number, declaring it with `default` and a slider range the
+    /// first time it is read.
+    pub fn f32(&mut self, name: &str, default: f32, range: Range<f32>) -> f32 {
+        let index = self.index_or_insert(name, |saved| {
+            let value = match saved {
+                Some(SavedValue::Number(value)) => value,
+                _ => default,
+            };
+            ParamKind::F32 { value, range }
+        });
+        match self.params[index].kind {
+            ParamKind::F32 { value, .. } => value,
+            ParamKind::Bool(_) => {
+                panic!("Param {} was declared as a bool", name)
+            }
+        }
+    }
+
+    /// Read a flag, declaring it with `default` the first time it is read.
+    pub fn bool(&mut self, name: &str, default: bool) -> bool {
+        let index = self.index_or_insert(name, |saved| {
+            ParamKind::Bool(match saved {
+                Some(SavedValue::Bool(value)) => value,
+                _ => default,
+            })
+        });
+        match self.params[index].kind {
+            ParamKind::Bool(value) => value,
+            ParamKind::F32 { .. } => {
+                panic!("Param {} was declared as an f32", name)
+            }
+        }
+    }
+
+    /// Draw a widget for every declared parameter, plus a save button if the
+    /// params have a file. Returns true when any value changed.
+    pub fn draw_ui(&mut self, sim: &mut Sim2D, top_left: Vec2) -> bool {
+        self.ui.begin(&sim.w, top_left);
+
+        let mut changed = false;
+        for param in &mut self.params {
+            changed |= match &mut param.kind {
+                ParamKind::F32 { value, range } => self.ui.slider(
+                    &mut sim.g,
+                    &param.name,
+                    value,
+                    range.start..=range.end,
+                ),
+                ParamKind::Bool(value) => {
+                    self.ui.toggle(&mut sim.g, &param.name, value)
+                }
+            };
+        }
+
+        #[cfg(feature = "config")]
+        if self.path.is_some() && self.ui.button(&mut sim.g, "save params") {
+            if let Err(error) = self.save() {
+                log::error!("Unable to save params: {:?}", error);
+            }
+        }
+        changed
+    }
+
+    /// Set a declared number, clamped to its range. Returns false when there
+    /// is no number with that name.
+    pub fn set_f32(&mut self, name: &str, new_value: f32) -> bool {
+        match self.param_mut(name) {
+            Some(ParamKind::F32 { value, range }) => {
+                *value = new_value.clamp(range.start, range.end);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Set a declared flag. Returns false when there is no flag with that
+    /// name.
+    pub fn set_bool(&mut self, name: &str, new_value: bool) -> bool {
+        match self.param_mut(name) {
+            Some(ParamKind::Bool(value)) => {
+                *value = new_value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true when the mouse is over the params UI.
+    pub fn is_using_mouse(&self) -> bool {
+        self.ui.is_using_mouse()
+    }
+
+    /// Write every declared value to the params file. Values already in the
+    /// file for params which haven't been declared yet are kept.
+    ///
+    /// Does nothing for params created with `new`.
+    #[cfg(feature = "config")]
+    pub fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let mut table = read_params(path)?;
+        self.write_values(&mut table);
+        write_params(path, &toml::to_string(&table)?)
+    }
+}
+
+// Private API
+// -----------
+
+impl Params {
+    fn index_or_insert<F>(&mut self, name: &str, create: F) -> usize
+    where
+        F: FnOnce(Option<SavedValue>) -> ParamKind,
+    {
+        if let Some(index) = self.params.iter().position(|p| p.name == name) {
+            return index;
+        }
+        let kind = create(self.saved.remove(name));
+        self.params.push(Param {
+            name: name.to_owned(),
+            kind,
+        });
+        self.params.len() - 1
+    }
+
+    fn param_mut(&mut self, name: &str) -> Option<&mut ParamKind> {
+        self.params
+            .iter_mut()
+            .find(|param| param.name == name)
+            .map(|param| &mut param.kind)
+    }
+
+    /// Describe every declared param for remote control clients.
+    #[cfg(feature = "remote")]
+    pub(super) fn to_json(&self) -> serde_json::Value {
+        let params = self
+            .params
+            .iter()
+            .map(|param| match &param.kind {
+                ParamKind::F32 { value, range } => serde_json::json!({
+                    "name": param.name,
+                    "type": "f32",
+                    "value": value,
+                    "min": range.start,
+                    "max": range.end,
+                }),
+                ParamKind::Bool(value) => serde_json::json!({
+                    "name": param.name,
+                    "type": "bool",
+                    "value": value,
+                }),
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({ "params": params })
+    }
+
+    /// Set every declared value in `table`. Numbers which aren't finite
+    /// can't be read back as a param, so they are skipped.
+    #[cfg(feature = "config")]
+    fn write_values(&self, table: &mut Table) {
+        for param in &self.params {
+            let value = match param.kind {
+                ParamKind::F32 { value, .. } if !value.is_finite() => {
+                    log::warn!(
+                        "Not saving param {}, {} is not a finite number",
+                        param.name,
+                        value
+                    );
+                    continue;
+                }
+                ParamKind::F32 { value, .. } => Value::Float(value as f64),
+                ParamKind::Bool(value) => Value::Boolean(value),
+            };
+            table.insert(param.name.clone(), value);
+        }
+    }
+}
+
+/// Read every value in a params file. A missing file has no values.
+#[cfg(feature = "config")]
+fn read_params(path: &Path) -> Result<Table> {
+    if !path.exists() {
+        return Ok(Table::new());
+    }
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Unable to read params file {:?}", path))?;
+    toml::from_str(&contents)
+        .with_context(|| format!("Unable to parse params file {:?}", path))
+}
+
+#[cfg(feature = "config")]
+fn write_params(path: &Path, contents: &str) -> Result<()> {
+    std::fs::write(path, contents)
+        .with_context(|| format!("Unable to write params file {:?}", path))?;
+    log::info!("Saved params to {:?}", path);
+    Ok(())
+}
+
+#[cfg(all(test, feature = "config"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn saved_values_can_be_read_back() -> Result<()> {
+        let mut params = Params::new();
+        params.f32("wind speed.x = ", 2.5, 0.0..10.0);
+        params.f32("broken", f32::NAN, 0.0..1.0);
+        params.bool("trails", true);
+
+        let mut table = toml::from_str::<Table>("broken = 0.5\nold = 1\n")?;
+        params.write_values(&mut table);
+        let saved = toml::from_str::<Table>(&toml::to_string(&table)?)?;
+
+        assert_eq!(saved["wind speed.x = "].as_float(), Some(2.5));
+        assert_eq!(saved["broken"].as_float(), Some(0.5));
+        assert_eq!(saved["trails"].as_bool(), Some(true));
+        assert_eq!(saved["old"].as_integer(), Some(1));
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use {
+    super::palette,
+    crate::{
+        graphics::{Image, G2D},
+        math::{random::Random, Vec2, Vec2Ext},
+        Sim2D,
+    },
+    std::ops::Range,
+};
+
+/// A value which changes over a particle's life.
+///
+/// Keys are (t, value) pairs where t is the fraction of the particle's
+/// lifetime in the range 0-1. Values between keys are linearly interpolated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve {
+    keys: Vec<(f32, f32)>,
+}
+
+/// A color which changes over a particle's life. Works like `Curve`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorCurve {
+    keys: Vec<(f32, [f32; 4])>,
+}
+
+/// Spawns, simulates, and draws simple particles on the CPU.
+///
+/// Configure the emitter with its public fields, then update and draw it
+/// every frame:
+///
+/// ```ignore
+/// let mut sparks = ParticleEmitter {
+///     spawn_rate: 200.0,
+///     spread: 0.4,
+///     gravity: Vec2::new(0.0, -300.0),
+///     size: Curve::linear(6.0, 0.0),
+///     color: ColorCurve::linear([1.0, 0.8, 0.2, 1.0], [1.0, 0.1, 0.0, 0.0]),
+///     ..ParticleEmitter::default()
+/// };
+///
+/// // in update
+/// sparks.position = sim.w.mouse_pos();
+/// sparks.update(sim);
+/// sparks.draw(&mut sim.g);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    /// Where new particles spawn.
+    pub position: Vec2,
+
+    /// New particles per second. Zero only spawns particles with `burst`.
+    pub spawn_rate: f32,
+
+    /// The range each particle's lifetime is picked from, in seconds.
+    pub lifetime: Range<f32>,
+
+    /// The range each particle's initial speed is picked from.
+    pub speed: Range<f32>,
+
+    /// The direction particles are launched in, in radians.
+    pub direction: f32,
+
+    /// How far particles can stray from `direction` on either side, in
+    /// radians. PI sends particles in every direction.
+    pub spread: f32,
+
+    /// Acceleration applied to every particle.
+    pub gravity: Vec2,
+
+    /// The fraction of velocity lost per second.
+    pub drag: f32,
+
+    /// Multiplies each particle's velocity over its life.
+    pub speed_over_life: Curve,
+
+    /// The particle's width and height over its life.
+    pub size: Curve,
+
+    pub color: ColorCurve,
+
+    /// The image drawn for each particle. Defaults to a plain square.
+    pub image: Image,
+
+    /// Particles beyond this count are not spawned.
+    pub max_particles: usize,
+
+    particles: Vec<Particle>,
+
+    /// Fractional particles carried over between frames so low spawn rates
+    /// still spawn at the right average rate.
+    spawn_debt: f32,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Particle {
+    pos: Vec2,
+    vel: Vec2,
+    age: f32,
+    lifetime: f32,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            position: Vec2::zeros(),
+            spawn_rate: 50.0,
+            lifetime: 1.0..2.0,
+            speed: 50.0..100.0,
+            direction: std::f32::consts::FRAC_PI_2,
+            spread: std::f32::consts::PI,
+            gravity: Vec2::zeros(),
+            drag: 0.0,
+            speed_over_life: Curve::constant(1.0),
+            size: Curve::constant(4.0),
+            color: ColorCurve::linear(
+                [1.0, 1.0, 1.0, 1.0],
+                [1.0, 1.0, 1.0, 0.0],
+            ),
+            image: Image::none(),
+            max_particles: 10_000,
+            particles: vec![],
+            spawn_debt: 0.0,
+        }
+    }
+}
+
+// Public API
+// ----------
+
+impl Curve {
+    /// Create a curve from (t, value) keys. Keys are sorted by t. Panics if
+    /// there are no keys.
+    pub fn new(mut keys: Vec<(f32, f32)>) -> Self {
+        assert!(!keys.is_empty(), "A curve needs at least one key");
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { keys }
+    }
+
+    pub fn constant(value: f32) -> Self {
+        Self::new(vec![(0.0, value)])
+    }
+
+    /// A curve which goes from `start` at birth to `end` at death.
+    pub fn linear(start: f32, end: f32) -> Self {
+        Self::new(vec![(0.0, start), (1.0, end)])
+    }
+
+    /// The value at `t`. Values before the first key and after the last key
+    /// are held constant.
+    pub fn sample(&self, t: f32) -> f32 {
+        let (a, b, amount) = find_keys(&self.keys, t);
+        a + (b - a) * amount
+    }
+}
+
+impl ColorCurve {
+    /// Create a curve from (t, color) keys. Keys are sorted by t. Panics if
+    /// there are no keys.
+    pub fn new(mut keys: Vec<(f32, [f32; 4])>) -> Self {
+        assert!(!keys.is_empty(), "A curve needs at least one key");
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { keys }
+    }
+
+    pub fn constant(color: [f32; 4]) -> Self {
+        Self::new(vec![(0.0, color)])
+    }
+
+    /// A curve which goes from `start` at birth to `end` at death.
+    pub fn linear(start: [f32; 4], end: [f32; 4]) -> Self {
+        Self::new(vec![(0.0, start), (1.0, end)])
+    }
+
+    /// The color at `t`. Colors before the first key and after the last key
+    /// are held constant.
+    pub fn sample(&self, t: f32) -> [f32; 4] {
+        let (a, b, amount) = find_keys(&self.keys, t);
+        palette::lerp(a, b, amount)
+    }
+}
+
+impl ParticleEmitter {
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            position,
+            ..Self::default()
+        }
+    }
+
+    /// Spawn particles for this frame, then age and move every particle.
+    pub fn update(&mut self, sim: &mut Sim2D) {
+        let dt = sim.dt();
+        self.spawn_debt += self.spawn_rate.max(0.0) * dt;
+        let count = self.spawn_debt.floor();
+        self.spawn_debt -= count;
+        self.burst(count as usize, &mut sim.rng);
+        self.step(dt);
+    }
+
+    /// Immediately spawn `count` particles, such as for an explosion.
+    pub fn burst(&mut self, count: usize, rng: &mut Random) {
+        let available = self.max_particles.saturating_sub(self.particles.len());
+        for _ in 0..count.min(available) {
+            let angle =
+                self.direction + random_in(rng, -self.spread..self.spread);
+            let speed = random_in(rng, self.speed.clone());
+            self.particles.push(Particle {
+                pos: self.position,
+                vel: Vec2::from_angle(angle) * speed,
+                age: 0.0,
+                lifetime: random_in(rng, self.lifetime.clone())
+                    .max(f32::EPSILON),
+            });
+        }
+    }
+
+    /// Age and move every particle by `dt` seconds without spawning.
+    pub fn step(&mut self, dt: f32) {
+        let drag = (1.0 - self.drag * dt).clamp(0.0, 1.0);
+        for particle in &mut self.particles {
+            particle.age += dt;
+            let t = particle.age / particle.lifetime;
+            particle.vel = (particle.vel + self.gravity * dt) * drag;
+            particle.pos += particle.vel * self.speed_over_life.sample(t) * dt;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    /// Draw every living particle, oldest first. The sketch's fill color and
+    /// image are left unchanged.
+    pub fn draw(&self, g: &mut G2D) {
+        let original_color = g.fill_color;
+        let original_image = g.image;
+        g.image = self.image;
+        for particle in &self.particles {
+            let t = particle.age / particle.lifetime;
+            let size = self.size.sample(t);
+            g.fill_color = self.color.sample(t);
+            g.rect_centered(particle.pos, Vec2::new(size, size), 0.0);
+        }
+        g.fill_color = original_color;
+        g.image = original_image;
+    }
+
+    /// The number of living particles.
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Remove every particle.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.spawn_debt = 0.0;
+    }
+}
+
+// Private API
+// -----------
+
+/// Find the keys on either side of `t` and how far `t` is between them.
+fn find_keys<T: Copy>(keys: &[(f32, T)], t: f32) -> (T, T, f32) {
+    let next = keys.iter().position(|&(key_t, _)| key_t > t);
+    match next {
+        None => {
+            let last = keys[keys.len() - 1].1;
+            (last, last, 0.0)
+        }
+        Some(0) => (keys[0].1, keys[0].1, 0.0),
+        Some(index) => {
+            let (t0, a) = keys[index - 1];
+            let (t1, b) = keys[index];
+            (a, b, (t - t0) / (t1 - t0))
+        }
+    }
+}
+
+/// Pick a value from a range which may be empty, in which case its start is
+/// used.
+fn random_in(rng: &mut Random, range: Range<f32>) -> f32 {
+    if range.end > range.start {
+        rng.random_range(range)
+    } else {
+        range.start
+    }
+}
@@ -0,0 +1,148 @@
+use {
+    crate::{
+        graphics::{vulkan_api::SpriteData, Image, G2D},
+        math::Vec2,
+        Sim2D,
+    },
+    std::time::Duration,
+};
+
+/// The width of the timing bar in screen units.
+const BAR_WIDTH: f32 = 260.0;
+
+const BAR_HEIGHT: f32 = 14.0;
+
+/// The frame time which fills the whole bar.
+const BAR_SPAN: Duration = Duration::from_micros(33_333);
+
+/// The frame time marked on the bar, a 60hz frame.
+const BUDGET: Duration = Duration::from_micros(16_667);
+
+/// Space between the panel's contents and its edge.
+const PADDING: f32 = 6.0;
+
+const STAGE_COLORS: [[f32; 4]; 5] = [
+    [0.35, 0.75, 0.95, 1.0],
+    [0.55, 0.55, 0.55, 1.0],
+    [0.95, 0.65, 0.25, 1.0],
+    [0.45, 0.85, 0.45, 1.0],
+    [0.85, 0.45, 0.85, 1.0],
+];
+
+/// Draw a panel in the top right corner with a stacked bar of the previous
+/// frame's CPU time, split into update and each render stage, along with
+/// sprite, vertex, draw call, and memory counts.
+///
+/// Call at the end of update so the counts include everything the sketch
+/// drew. The bar spans 33ms and has a marker at 16.7ms for a 60hz frame.
+/// Render stages are measured on the CPU: `draw` is the time to record draw
+/// commands and GPU execution time shows up in `acquire` when the CPU has to
+/// wait for the GPU.
+///
+/// The sketch's fill color and image are left unchanged.
+pub fn draw_perf_hud(sim: &mut Sim2D) {
+    let render = sim.last_render_timings();
+    let stages = [
+        ("update", sim.last_update_time()),
+        ("acquire", render.acquire),
+        ("upload", render.upload),
+        ("draw", render.draw),
+        ("present", render.present),
+    ];
+    let total: Duration = stages.iter().map(|(_, duration)| *duration).sum();
+
+    // The renderer draws every sprite as two triangles, with a draw call for
+    // each batch of sprites which share a blend mode and clip rect, plus one
+    // for the background shader.
+    let sprites = sim.g.sprite_count();
+    let draw_calls =
+        sim.g.sprite_batches().len() + sim.background_shader.is_some() as usize;
+    let counts = format!(
+        "sprites: {}\nvertices: {}\ndraw calls: {}\nsprite data: {:.1} KiB",
+        sprites,
+        sprites * 6,
+        draw_calls,
+        (sprites * std::mem::size_of::<SpriteData>()) as f32 / 1024.0,
+    );
+
+    let g = &mut sim.g;
+    let original_color = g.fill_color;
+    let original_image = g.image;
+    g.image = Image::none();
+
+    let title = format!("frame: {}", ms(total));
+    let line_height = g.text_size("M").y;
+    let counts_size = g.text_size(&counts);
+    let content_height = line_height * (1 + stages.len()) as f32
+        + BAR_HEIGHT
+        + counts_size.y
+        + PADDING * 3.0;
+    let panel_size =
+        Vec2::new(BAR_WIDTH + PADDING * 2.0, content_height + PADDING * 2.0);
+    let panel_top_left =
+        Vec2::new(sim.w.width() * 0.5 - panel_size.x, sim.w.height() * 0.5);
+
+    g.fill_color = [0.0, 0.0, 0.0, 0.6];
+    g.rect(panel_top_left, panel_size, 0.0);
+
+    let left = panel_top_left.x + PADDING;
+    let mut top = panel_top_left.y - PADDING;
+
+    g.fill_color = [1.0, 1.0, 1.0, 1.0];
+    g.text(Vec2::new(left, top), title);
+    top -= line_height + PADDING;
+
+    draw_bar(g, Vec2::new(left, top), &stages);
+    top -= BAR_HEIGHT + PADDING;
+
+    for (index, (label, duration)) in stages.iter().enumerate() {
+        g.fill_color = STAGE_COLORS[index];
+        g.text(
+            Vec2::new(left, top),
+            format!("{}: {}", label, ms(*duration)),
+        );
+        top -= line_height;
+    }
+    top -= PADDING;
+
+    g.fill_color = [1.0, 1.0, 1.0, 1.0];
+    g.text(Vec2::new(left, top), counts);
+
+    g.fill_color = original_color;
+    g.image = original_image;
+}
+
+// Private API
+// -----------
+
+fn draw_bar(g: &mut G2D, top_left: Vec2, stages: &[(&str, Duration)]) {
+    g.fill_color = [0.2, 0.2, 0.2, 1.0];
+    g.rect(top_left, Vec2::new(BAR_WIDTH, BAR_HEIGHT), 0.0);
+
+    let mut x = top_left.x;
+    let right = top_left.x + BAR_WIDTH;
+    for (index, (_, duration)) in stages.iter().enumerate() {
+        let width = (bar_fraction(*duration) * BAR_WIDTH).min(right - x);
+        if width > 0.0 {
+            g.fill_color = STAGE_COLORS[index];
+            g.rect(Vec2::new(x, top_left.y), Vec2::new(width, BAR_HEIGHT), 0.0);
+            x += width;
+        }
+    }
+
+    let budget_x = top_left.x + bar_fraction(BUDGET) * BAR_WIDTH;
+    g.fill_color = [1.0, 1.0, 1.0, 0.9];
+    g.rect(
+        Vec2::new(budget_x - 1.0, top_left.y + 2.0),
+        Vec2::new(2.0, BAR_HEIGHT + 4.0),
+        0.0,
+    );
+}
+
+fn bar_fraction(duration: Duration) -> f32 {
+    duration.as_secs_f32() / BAR_SPAN.as_secs_f32()
+}
+
+fn ms(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
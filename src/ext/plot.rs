@@ -0,0 +1,165 @@
+use {
+    crate::{
+        graphics::{Image, G2D},
+        math::Vec2,
+    },
+    std::collections::VecDeque,
+};
+
+/// A live line graph of the most recent values of some quantity.
+///
+/// Push one value per frame, or whenever the quantity changes, and draw the
+/// plot at the end of update:
+///
+/// ```ignore
+/// self.energy_plot.push(self.total_energy());
+/// self.energy_plot.draw(&mut sim.g, Vec2::new(-300.0, 200.0), size);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Plot {
+    label: String,
+    values: VecDeque<f32>,
+    capacity: usize,
+    range: Option<(f32, f32)>,
+
+    pub line_color: [f32; 4],
+    pub text_color: [f32; 4],
+    pub background: Option<[f32; 4]>,
+}
+
+// Public API
+// ----------
+
+impl Plot {
+    /// Create a plot which keeps the last `capacity` values.
+    pub fn new(label: impl Into<String>, capacity: usize) -> Self {
+        let capacity = capacity.max(2);
+        Self {
+            label: label.into(),
+            values: VecDeque::with_capacity(capacity),
+            capacity,
+            range: None,
+            line_color: [0.3, 0.9, 0.4, 1.0],
+            text_color: [1.0, 1.0, 1.0, 1.0],
+            background: Some([0.0, 0.0, 0.0, 0.5]),
+        }
+    }
+
+    /// Use a fixed vertical range instead of fitting the range to the
+    /// values. Values outside of the range are clamped to the edge.
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min.min(max), min.max(max)));
+        self
+    }
+
+    /// Add a value, dropping the oldest value if the plot is full.
+    pub fn push(&mut self, value: f32) {
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// The most recently pushed value.
+    pub fn latest(&self) -> Option<f32> {
+        self.values.back().copied()
+    }
+
+    /// The values from oldest to newest.
+    pub fn values(&self) -> impl Iterator<Item = f32> + '_ {
+        self.values.iter().copied()
+    }
+
+    /// Draw the plot with its label, latest value, and vertical range.
+    ///
+    /// The graph fills the rectangle with its top left corner at `top_left`.
+    /// The newest value is on the right. The sketch's fill color, line width,
+    /// and image are left unchanged.
+    pub fn draw(&self, g: &mut G2D, top_left: Vec2, size: Vec2) {
+        let original_color = g.fill_color;
+        let original_width = g.line_width;
+        let original_image = g.image;
+        g.image = Image::none();
+        g.line_width = 1.0;
+
+        if let Some(background) = self.background {
+            g.fill_color = background;
+            g.rect(top_left, size, 0.0);
+        }
+
+        let (min, max) = self.vertical_range();
+        let span = if max > min { max - min } else { 1.0 };
+        let bottom_left = top_left - Vec2::new(0.0, size.y);
+        let step = size.x / (self.capacity - 1) as f32;
+
+        // Right-align the values so the graph scrolls in from the right.
+        let offset = self.capacity - self.values.len();
+        let point_at = |index: usize, value: f32| {
+            let t = ((value - min) / span).clamp(0.0, 1.0);
+            bottom_left + Vec2::new((index + offset) as f32 * step, t * size.y)
+        };
+
+        g.fill_color = self.line_color;
+        let mut points = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| point_at(index, value));
+        if let Some(mut previous) = points.next() {
+            for point in points {
+                if point != previous {
+                    g.line(previous, point);
+                }
+                previous = point;
+            }
+        }
+
+        g.fill_color = self.text_color;
+        let latest = self
+            .latest()
+            .map(|value| format!("{:.3}", value))
+            .unwrap_or_default();
+        g.text(top_left, format!("{}: {}", self.label, latest));
+        let max_label = format!("{:.3}", max);
+        let max_width = g.text_size(&max_label).x;
+        g.text(top_left + Vec2::new(size.x - max_width, 0.0), max_label);
+        let min_label = format!("{:.3}", min);
+        let min_size = g.text_size(&min_label);
+        g.text(
+            bottom_left + Vec2::new(size.x - min_size.x, min_size.y),
+            min_label,
+        );
+
+        g.fill_color = original_color;
+        g.line_width = original_width;
+        g.image = original_image;
+    }
+}
+
+// Private API
+// -----------
+
+impl Plot {
+    /// The values which map to the bottom and top of the graph.
+    fn vertical_range(&self) -> (f32, f32) {
+        if let Some(range) = self.range {
+            return range;
+        }
+        let (min, max) = self
+            .values
+            .iter()
+            .filter(|value| value.is_finite())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &v| {
+                (min.min(v), max.max(v))
+            });
+        if min > max {
+            (0.0, 1.0)
+        } else {
+            (min, max)
+        }
+    }
+}
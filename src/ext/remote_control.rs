@@ -0,0 +1,328 @@
+use {
+    super::Params,
+    anyhow::{anyhow, bail, Context, Result},
+    serde_json::Value,
+    std::{
+        io::{ErrorKind, Read, Write},
+        net::{SocketAddr, TcpListener, TcpStream},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc::{self, Receiver, Sender},
+            Arc, Mutex,
+        },
+        thread::JoinHandle,
+        time::Duration,
+    },
+    tungstenite::{Message, WebSocket},
+};
+
+/// How long background threads wait before checking for work or shutdown.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Requests larger than this are dropped instead of buffered.
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+
+/// The page served to browsers which don't ask for a WebSocket.
+const CONTROL_PAGE: &str = include_str!("./remote_control.html");
+
+/// Exposes `Params` to web browsers on the local network, so a sketch
+/// running full-screen can be tuned from a phone.
+///
+/// Opening `http://<machine address>:<port>/` shows a slider or checkbox for
+/// every declared param. The page talks to the sketch over a WebSocket on
+/// the same port. Call `update` once per frame:
+///
+/// ```ignore
+/// // in setup
+/// self.remote = Some(RemoteControl::bind(8080)?);
+///
+/// // in update
+/// let speed = self.params.f32("speed", 1.0, 0.0..10.0);
+/// if let Some(remote) = &mut self.remote {
+///     remote.update(&mut self.params);
+/// }
+/// ```
+///
+/// This is only available with the `remote` feature. There is no
+/// authentication, so only use it on trusted networks.
+///
+/// # Protocol
+///
+/// The sketch sends `{"params": [...]}` whenever a param changes, where
+/// each param is `{"name", "type": "f32", "value", "min", "max"}` or
+/// `{"name", "type": "bool", "value"}`. Clients set a value by sending
+/// `{"name": "speed", "value": 2.5}`.
+pub struct RemoteControl {
+    address: SocketAddr,
+    changes: Receiver<(String, Value)>,
+    shared: Arc<Shared>,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+// Public API
+// ----------
+
+impl RemoteControl {
+    /// Serve the control page and WebSocket on a TCP port on every network
+    /// interface.
+    pub fn bind(port: u16) -> Result<Self> {
+        let listener =
+            TcpListener::bind(("0.0.0.0", port)).with_context(|| {
+                format!("Unable to bind remote control port {}", port)
+            })?;
+        listener
+            .set_nonblocking(true)
+            .context("Unable to make the remote listener non-blocking!")?;
+        let address = listener
+            .local_addr()
+            .context("Unable to get the remote control address!")?;
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new((0, String::new())),
+            running: AtomicBool::new(true),
+        });
+        let (sender, changes) = mpsc::channel();
+        let accept_thread = {
+            let shared = shared.clone();
+            std::thread::Builder::new()
+                .name("sim2d-remote-control".to_owned())
+                .spawn(move || accept_clients(listener, shared, sender))
+                .context("Unable to start the remote control thread!")?
+        };
+        log::info!("Remote control listening on {}", address);
+
+        Ok(Self {
+            address,
+            changes,
+            shared,
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    /// The address the server is bound to. Useful after binding port 0 to
+    /// let the system pick a free port.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Apply the changes clients made since the last update, then send the
+    /// params to clients if they've changed. Returns true when any param
+    /// was changed remotely.
+    pub fn update(&mut self, params: &mut Params) -> bool {
+        let mut changed = false;
+        for (name, value) in self.changes.try_iter() {
+            changed |= match value {
+                Value::Bool(value) => params.set_bool(&name, value),
+                Value::Number(value) => value
+                    .as_f64()
+                    .map(|value| params.set_f32(&name, value as f32))
+                    .unwrap_or(false),
+                _ => false,
+            };
+        }
+
+        let json = params.to_json().to_string();
+        let mut state = self.shared.state.lock().unwrap();
+        if state.1 != json {
+            state.0 += 1;
+            state.1 = json;
+        }
+        changed
+    }
+}
+
+impl Drop for RemoteControl {
+    fn drop(&mut self) {
+        self.shared.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.accept_thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Private API
+// -----------
+
+/// State shared with the background threads.
+struct Shared {
+    /// The newest params JSON and a version which increases every time the
+    /// JSON changes.
+    state: Mutex<(u64, String)>,
+
+    /// Cleared when the RemoteControl is dropped.
+    running: AtomicBool,
+}
+
+impl Shared {
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+}
+
+fn accept_clients(
+    listener: TcpListener,
+    shared: Arc<Shared>,
+    changes: Sender<(String, Value)>,
+) {
+    while shared.is_running() {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                let shared = shared.clone();
+                let changes = changes.clone();
+                std::thread::spawn(move || {
+                    if let Err(error) = serve_client(stream, &shared, changes) {
+                        log::warn!("Remote client {}: {:?}", peer, error);
+                    }
+                });
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            Err(error) => {
+                log::warn!("Unable to accept remote client: {}", error);
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Upgrade WebSocket requests, and answer anything else with the control
+/// page.
+fn serve_client(
+    stream: TcpStream,
+    shared: &Shared,
+    changes: Sender<(String, Value)>,
+) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    let request = peek_request(&stream, shared)?;
+    if request.to_ascii_lowercase().contains("upgrade: websocket") {
+        let websocket = tungstenite::accept(stream)
+            .map_err(|error| anyhow!("{}", error))
+            .context("WebSocket handshake failed!")?;
+        serve_websocket(websocket, shared, changes)
+    } else {
+        serve_page(stream)
+    }
+}
+
+/// Wait for the request headers without consuming them, so the WebSocket
+/// handshake can read them again.
+fn peek_request(stream: &TcpStream, shared: &Shared) -> Result<String> {
+    let mut buffer = vec![0u8; MAX_REQUEST_SIZE];
+    while shared.is_running() {
+        match stream.peek(&mut buffer) {
+            Ok(0) => bail!("Closed before sending a request"),
+            Ok(size) => {
+                let request = String::from_utf8_lossy(&buffer[..size]);
+                if request.contains("\r\n\r\n") {
+                    return Ok(request.into_owned());
+                }
+                if size == buffer.len() {
+                    bail!("Request headers are too large");
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            Err(error) if is_timeout(&error) => (),
+            Err(error) => return Err(error.into()),
+        }
+    }
+    bail!("The remote control was stopped")
+}
+
+fn serve_page(mut stream: TcpStream) -> Result<()> {
+    // Consume the request so closing the socket doesn't reset it.
+    let mut request = [0u8; MAX_REQUEST_SIZE];
+    let _ = stream.read(&mut request);
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: text/html; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        CONTROL_PAGE.len(),
+        CONTROL_PAGE
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn serve_websocket(
+    mut websocket: WebSocket<TcpStream>,
+    shared: &Shared,
+    changes: Sender<(String, Value)>,
+) -> Result<()> {
+    let mut sent_version = 0;
+    while shared.is_running() {
+        let state = {
+            let state = shared.state.lock().unwrap();
+            (state.0 != sent_version).then(|| state.clone())
+        };
+        if let Some((version, json)) = state {
+            websocket.send(Message::text(json))?;
+            sent_version = version;
+        }
+
+        match websocket.read() {
+            Ok(message) if message.is_text() => {
+                match parse_change(message.to_text()?) {
+                    Some(change) => {
+                        if changes.send(change).is_err() {
+                            break;
+                        }
+                    }
+                    None => log::warn!("Invalid remote message {}", message),
+                }
+            }
+            Ok(message) if message.is_close() => break,
+            Ok(_) => (),
+            Err(tungstenite::Error::Io(error)) if is_timeout(&error) => (),
+            Err(tungstenite::Error::ConnectionClosed) => break,
+            Err(error) => return Err(error.into()),
+        }
+    }
+    Ok(())
+}
+
+/// Parse `{"name": ..., "value": ...}`.
+fn parse_change(text: &str) -> Option<(String, Value)> {
+    let mut message: Value = serde_json::from_str(text).ok()?;
+    let name = message.get("name")?.as_str()?.to_owned();
+    let value = message.get_mut("value")?.take();
+    Some((name, value))
+}
+
+fn is_timeout(error: &std::io::Error) -> bool {
+    matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_remote_control() -> Result<()> {
+        let mut params = Params::new();
+        params.f32("speed", 1.0, 0.0..10.0);
+        params.bool("trails", false);
+
+        let mut remote = RemoteControl::bind(0)?;
+        let url = format!("ws://127.0.0.1:{}/", remote.local_addr().port());
+        remote.update(&mut params);
+        let (mut client, _) = tungstenite::connect(url)?;
+        let state: Value = serde_json::from_str(client.read()?.to_text()?)?;
+        assert_eq!(state["params"][0]["max"], 10.0);
+
+        client.send(Message::text(r#"{"name":"speed","value":50}"#))?;
+        client.send(Message::text(r#"{"name":"trails","value":true}"#))?;
+        for _ in 0..100 {
+            if remote.update(&mut params) && params.bool("trails", false) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(params.f32("speed", 1.0, 0.0..10.0), 10.0);
+        assert!(params.bool("trails", false));
+        Ok(())
+    }
+}
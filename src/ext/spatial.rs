@@ -0,0 +1,333 @@
+//! Spatial indexes for finding the items near a point without checking every
+//! item.
+//!
+//! `SpatialHash` buckets items into a uniform grid of cells. It is the best
+//! choice when items are spread evenly and queries are around the size of a
+//! cell, like neighbor searches in particle and boids sketches. `QuadTree`
+//! subdivides space where items are dense, so it handles clustered items and
+//! queries of very different sizes.
+//!
+//! Both are cheap to rebuild, so sketches with moving items usually clear
+//! and refill the index every frame:
+//!
+//! ```ignore
+//! self.index.clear();
+//! for (i, particle) in self.particles.iter().enumerate() {
+//!     self.index.insert(particle.pos, i);
+//! }
+//! for (pos, &i) in self.index.query_radius(mouse, 50.0) {
+//!     // ...
+//! }
+//! ```
+
+use {
+    crate::math::{Rect, Vec2},
+    std::collections::HashMap,
+};
+
+/// Items bucketed into square cells on an unbounded grid.
+#[derive(Debug, Clone)]
+pub struct SpatialHash<T> {
+    cell_size: f32,
+    items: Vec<(Vec2, T)>,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+/// Items stored in a tree of rectangles which split into four when they hold
+/// too many items.
+#[derive(Debug, Clone)]
+pub struct QuadTree<T> {
+    items: Vec<(Vec2, T)>,
+    nodes: Vec<Node>,
+    node_capacity: usize,
+    max_depth: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    bounds: Rect,
+    depth: u32,
+    items: Vec<usize>,
+    children: Option<[usize; 4]>,
+}
+
+// Public API
+// ----------
+
+impl<T> SpatialHash<T> {
+    /// Create an empty hash. Queries are fastest when `cell_size` is close
+    /// to the usual query radius.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "The cell size must be positive");
+        Self {
+            cell_size,
+            items: vec![],
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn insert(&mut self, pos: Vec2, item: T) {
+        let index = self.items.len();
+        self.items.push((pos, item));
+        self.cells.entry(self.cell_of(pos)).or_default().push(index);
+    }
+
+    /// Remove every item while keeping allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.items.clear();
+        for bucket in self.cells.values_mut() {
+            bucket.clear();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Every item as (position, item), in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2, &T)> {
+        self.items.iter().map(|(pos, item)| (*pos, item))
+    }
+
+    /// Every item within `radius` of `center`, including items exactly
+    /// `radius` away.
+    pub fn query_radius(
+        &self,
+        center: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = (Vec2, &T)> {
+        let bounds = Rect::from_center(center, Vec2::new(radius, radius) * 2.0);
+        self.query_rect(bounds).filter(move |(pos, _)| {
+            (pos - center).norm_squared() <= radius * radius
+        })
+    }
+
+    /// Every item inside `rect`, including items on its edges.
+    pub fn query_rect(&self, rect: Rect) -> impl Iterator<Item = (Vec2, &T)> {
+        let (min_x, min_y) = self.cell_of(rect.min);
+        let (max_x, max_y) = self.cell_of(rect.max);
+        (min_y..=max_y)
+            .flat_map(move |y| (min_x..=max_x).map(move |x| (x, y)))
+            .filter_map(move |cell| self.cells.get(&cell))
+            .flatten()
+            .map(move |&index| &self.items[index])
+            .filter(move |(pos, _)| rect.contains(*pos))
+            .map(|(pos, item)| (*pos, item))
+    }
+}
+
+impl<T> QuadTree<T> {
+    /// Create an empty tree which covers `bounds`.
+    pub fn new(bounds: Rect) -> Self {
+        Self {
+            items: vec![],
+            nodes: vec![Node::new(bounds, 0)],
+            node_capacity: 8,
+            max_depth: 12,
+        }
+    }
+
+    /// How many items a node holds before it splits. Defaults to 8.
+    pub fn with_node_capacity(mut self, node_capacity: usize) -> Self {
+        self.node_capacity = node_capacity.max(1);
+        self
+    }
+
+    /// The rectangle covered by the tree.
+    pub fn bounds(&self) -> Rect {
+        self.nodes[0].bounds
+    }
+
+    /// Add an item. Returns false, and drops the item, if `pos` is outside
+    /// of the tree's bounds.
+    pub fn insert(&mut self, pos: Vec2, item: T) -> bool {
+        if !self.bounds().contains(pos) {
+            return false;
+        }
+        let index = self.items.len();
+        self.items.push((pos, item));
+
+        let mut node = 0;
+        while let Some(children) = self.nodes[node].children {
+            node = self.child_containing(children, pos);
+        }
+        self.nodes[node].items.push(index);
+        if self.nodes[node].items.len() > self.node_capacity
+            && self.nodes[node].depth < self.max_depth
+        {
+            self.split(node);
+        }
+        true
+    }
+
+    /// Remove every item, keeping the tree's bounds.
+    pub fn clear(&mut self) {
+        let bounds = self.bounds();
+        self.items.clear();
+        self.nodes.clear();
+        self.nodes.push(Node::new(bounds, 0));
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Every item as (position, item), in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2, &T)> {
+        self.items.iter().map(|(pos, item)| (*pos, item))
+    }
+
+    /// Every item within `radius` of `center`, including items exactly
+    /// `radius` away.
+    pub fn query_radius(
+        &self,
+        center: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = (Vec2, &T)> {
+        let bounds = Rect::from_center(center, Vec2::new(radius, radius) * 2.0);
+        self.query_rect(bounds).filter(move |(pos, _)| {
+            (pos - center).norm_squared() <= radius * radius
+        })
+    }
+
+    /// Every item inside `rect`, including items on its edges.
+    pub fn query_rect(&self, rect: Rect) -> impl Iterator<Item = (Vec2, &T)> {
+        let mut stack = vec![0];
+        let mut node_items = [].iter();
+        std::iter::from_fn(move || loop {
+            if let Some(&index) = node_items.next() {
+                let (pos, item) = &self.items[index];
+                if rect.contains(*pos) {
+                    return Some((*pos, item));
+                }
+                continue;
+            }
+
+            let node = &self.nodes[stack.pop()?];
+            if node.bounds.intersects(&rect) {
+                node_items = node.items.iter();
+                stack.extend(node.children.iter().flatten());
+            }
+        })
+    }
+}
+
+// Private API
+// -----------
+
+impl<T> SpatialHash<T> {
+    fn cell_of(&self, pos: Vec2) -> (i32, i32) {
+        (
+            (pos.x / self.cell_size).floor() as i32,
+            (pos.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+impl<T> QuadTree<T> {
+    /// Move a leaf node's items into four new children.
+    fn split(&mut self, node: usize) {
+        let Node { bounds, depth, .. } = self.nodes[node];
+        let center = bounds.center();
+        let first_child = self.nodes.len();
+        for corner in [
+            bounds.min,
+            Vec2::new(bounds.max.x, bounds.min.y),
+            Vec2::new(bounds.min.x, bounds.max.y),
+            bounds.max,
+        ] {
+            self.nodes
+                .push(Node::new(Rect::new(center, corner), depth + 1));
+        }
+        let children = [
+            first_child,
+            first_child + 1,
+            first_child + 2,
+            first_child + 3,
+        ];
+        self.nodes[node].children = Some(children);
+
+        for index in std::mem::take(&mut self.nodes[node].items) {
+            let child = self.child_containing(children, self.items[index].0);
+            self.nodes[child].items.push(index);
+        }
+    }
+
+    /// Pick the child for a point. Points on a shared edge go to the first
+    /// child which contains them.
+    fn child_containing(&self, children: [usize; 4], pos: Vec2) -> usize {
+        children
+            .iter()
+            .copied()
+            .find(|&child| self.nodes[child].bounds.contains(pos))
+            .unwrap_or(children[0])
+    }
+}
+
+impl Node {
+    fn new(bounds: Rect, depth: u32) -> Self {
+        Self {
+            bounds,
+            depth,
+            items: vec![],
+            children: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_queries_match_brute_force() {
+        let points: Vec<Vec2> = (0..500)
+            .map(|i| {
+                let t = i as f32 * 0.618;
+                Vec2::new(
+                    (t * 37.0) % 200.0 - 100.0,
+                    (t * 91.0) % 200.0 - 100.0,
+                )
+            })
+            .collect();
+
+        let mut hash = SpatialHash::new(15.0);
+        let mut tree = QuadTree::new(Rect::from_center(
+            Vec2::zeros(),
+            Vec2::new(200.0, 200.0),
+        ))
+        .with_node_capacity(4);
+        for (i, &point) in points.iter().enumerate() {
+            hash.insert(point, i);
+            assert!(tree.insert(point, i));
+        }
+        assert!(!tree.insert(Vec2::new(500.0, 0.0), 0));
+
+        let center = Vec2::new(12.0, -30.0);
+        let mut expected: Vec<usize> = (0..points.len())
+            .filter(|&i| (points[i] - center).norm() <= 40.0)
+            .collect();
+        expected.sort_unstable();
+
+        for found in [
+            hash.query_radius(center, 40.0).map(|(_, &i)| i).collect(),
+            tree.query_radius(center, 40.0).map(|(_, &i)| i).collect(),
+        ] {
+            let mut found: Vec<usize> = found;
+            found.sort_unstable();
+            assert_eq!(found, expected);
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Craig Reynolds' steering behaviors for autonomous agents.
+//!
+//! Each behavior returns a steering force. Forces can be scaled and summed,
+//! then applied to the agent:
+//!
+//! ```ignore
+//! let forces = self.flocking.forces(&self.agents);
+//! for (agent, flock_force) in self.agents.iter_mut().zip(forces) {
+//!     let force = flock_force + agent.seek(mouse) * 0.5;
+//!     agent.apply_force(force, sim.dt());
+//! }
+//! ```
+
+use {
+    super::spatial::SpatialHash,
+    crate::math::{random::Random, Vec2, Vec2Ext},
+};
+
+/// A point which moves with a limited speed and turning force.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Agent {
+    pub pos: Vec2,
+    pub vel: Vec2,
+
+    /// The fastest the agent can move, in units per second.
+    pub max_speed: f32,
+
+    /// The largest steering force any single behavior returns.
+    pub max_force: f32,
+
+    /// The current angle on the wander circle, updated by `wander`.
+    pub wander_angle: f32,
+}
+
+/// Settings for `Agent::wander`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Wander {
+    /// How far ahead of the agent the wander circle is.
+    pub distance: f32,
+
+    /// The radius of the wander circle.
+    pub radius: f32,
+
+    /// The most the wander angle changes each call, in radians.
+    pub jitter: f32,
+}
+
+/// Separation, alignment, and cohesion for a group of agents.
+///
+/// Neighbors are found with a `SpatialHash`, so each agent only checks the
+/// agents near it.
+#[derive(Debug, Clone)]
+pub struct Flocking {
+    pub separation: f32,
+    pub alignment: f32,
+    pub cohesion: f32,
+
+    /// Agents closer than this are neighbors for alignment and cohesion.
+    pub neighbor_radius: f32,
+
+    /// Agents closer than this push each other apart.
+    pub separation_radius: f32,
+
+    index: SpatialHash<usize>,
+}
+
+impl Default for Wander {
+    fn default() -> Self {
+        Self {
+            distance: 60.0,
+            radius: 30.0,
+            jitter: 0.3,
+        }
+    }
+}
+
+impl Default for Flocking {
+    fn default() -> Self {
+        Self {
+            separation: 1.5,
+            alignment: 1.0,
+            cohesion: 1.0,
+            neighbor_radius: 50.0,
+            separation_radius: 25.0,
+            index: SpatialHash::new(50.0),
+        }
+    }
+}
+
+// Public API
+// ----------
+
+impl Agent {
+    /// Create a stationary agent.
+    pub fn new(pos: Vec2) -> Self {
+        Self {
+            pos,
+            vel: Vec2::zeros(),
+            max_speed: 150.0,
+            max_force: 300.0,
+            wander_angle: 0.0,
+        }
+    }
+
+    /// Steer toward `target` at full speed.
+    pub fn seek(&self, target: Vec2) -> Vec2 {
+        self.steer_toward(direction(self.pos, target) * self.max_speed)
+    }
+
+    /// Steer away from `target` at full speed.
+    pub fn flee(&self, target: Vec2) -> Vec2 {
+        self.steer_toward(direction(target, self.pos) * self.max_speed)
+    }
+
+    /// Steer toward `target`, slowing down to stop on it once the agent is
+    /// within `slowing_radius`.
+    pub fn arrive(&self, target: Vec2, slowing_radius: f32) -> Vec2 {
+        let distance = (target - self.pos).magnitude();
+        let speed = if distance < slowing_radius {
+            self.max_speed * distance / slowing_radius
+        } else {
+            self.max_speed
+        };
+        self.steer_toward(direction(self.pos, target) * speed)
+    }
+
+    /// Steer toward a point which drifts randomly around a circle in front
+    /// of the agent, giving smooth aimless motion.
+    pub fn wander(&mut self, rng: &mut Random, wander: &Wander) -> Vec2 {
+        if wander.jitter > 0.0 {
+            self.wander_angle +=
+                rng.random_range(-wander.jitter..wander.jitter);
+        }
+        let heading = if self.vel.magnitude_squared() > 0.0 {
+            self.vel.normalize()
+        } else {
+            Vec2::new(1.0, 0.0)
+        };
+        let center = self.pos + heading * wander.distance;
+        self.seek(center + Vec2::from_angle(self.wander_angle) * wander.radius)
+    }
+
+    /// Accelerate by `force` for `dt` seconds, then move.
+    pub fn apply_force(&mut self, force: Vec2, dt: f32) {
+        self.vel = limit(self.vel + force * dt, self.max_speed);
+        self.pos += self.vel * dt;
+    }
+}
+
+impl Flocking {
+    /// Compute the combined, weighted flocking force for every agent.
+    ///
+    /// The returned forces are in the same order as `agents`.
+    pub fn forces(&mut self, agents: &[Agent]) -> Vec<Vec2> {
+        let radius = self.neighbor_radius.max(self.separation_radius);
+        if radius <= 0.0 {
+            return vec![Vec2::zeros(); agents.len()];
+        }
+        if self.index.cell_size() != radius {
+            self.index = SpatialHash::new(radius);
+        }
+        self.index.clear();
+        for (i, agent) in agents.iter().enumerate() {
+            self.index.insert(agent.pos, i);
+        }
+
+        agents
+            .iter()
+            .enumerate()
+            .map(|(i, agent)| self.force_for(i, agent, agents, radius))
+            .collect()
+    }
+}
+
+// Private API
+// -----------
+
+impl Agent {
+    /// The force which turns the current velocity toward `desired`, limited
+    /// to `max_force`.
+    fn steer_toward(&self, desired: Vec2) -> Vec2 {
+        limit(desired - self.vel, self.max_force)
+    }
+}
+
+impl Flocking {
+    fn force_for(
+        &self,
+        index: usize,
+        agent: &Agent,
+        agents: &[Agent],
+        radius: f32,
+    ) -> Vec2 {
+        let mut push = Vec2::zeros();
+        let mut velocity_sum = Vec2::zeros();
+        let mut position_sum = Vec2::zeros();
+        let mut neighbors = 0;
+
+        for (pos, &other) in self.index.query_radius(agent.pos, radius) {
+            if other == index {
+                continue;
+            }
+            let offset = agent.pos - pos;
+            let distance = offset.magnitude();
+            if distance < self.separation_radius && distance > 0.0 {
+                // Closer agents push harder.
+                push += offset / (distance * distance);
+            }
+            if distance < self.neighbor_radius {
+                velocity_sum += agents[other].vel;
+                position_sum += pos;
+                neighbors += 1;
+            }
+        }
+
+        let mut force = Vec2::zeros();
+        if push != Vec2::zeros() {
+            force += agent.steer_toward(push.normalize() * agent.max_speed)
+                * self.separation;
+        }
+        if neighbors > 0 {
+            let count = neighbors as f32;
+            let average_velocity = velocity_sum / count;
+            if average_velocity != Vec2::zeros() {
+                force += agent.steer_toward(
+                    average_velocity.normalize() * agent.max_speed,
+                ) * self.alignment;
+            }
+            force += agent.seek(position_sum / count) * self.cohesion;
+        }
+        force
+    }
+}
+
+/// The unit vector from `from` to `to`, or zero if they are the same point.
+fn direction(from: Vec2, to: Vec2) -> Vec2 {
+    let offset = to - from;
+    let length = offset.magnitude();
+    if length > 0.0 {
+        offset / length
+    } else {
+        Vec2::zeros()
+    }
+}
+
+fn limit(v: Vec2, max: f32) -> Vec2 {
+    let length = v.magnitude();
+    if length > max && length > 0.0 {
+        v * (max / length)
+    } else {
+        v
+    }
+}
@@ -0,0 +1,250 @@
+//! Turtle graphics and L-systems.
+//!
+//! A `Turtle` walks around the plane leaving lines behind it. An `LSystem`
+//! grows a string of commands by repeatedly replacing symbols, and the
+//! turtle can follow those commands to draw plants and fractals:
+//!
+//! ```ignore
+//! let commands = LSystem::new("X")
+//!     .with_rule('X', "F+[[X]-X]-F[-FX]+X")
+//!     .with_rule('F', "FF")
+//!     .expand(5);
+//! let mut turtle = Turtle::new(Vec2::new(0.0, -300.0), FRAC_PI_2);
+//! turtle.run(&commands, 4.0, 25f32.to_radians());
+//! turtle.draw(&mut sim.g);
+//! ```
+
+use {
+    crate::{
+        graphics::{Image, G2D},
+        math::{Vec2, Vec2Ext},
+    },
+    std::collections::HashMap,
+};
+
+/// A pen which moves and turns, recording the lines it draws as polylines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turtle {
+    pos: Vec2,
+    heading: f32,
+    pen_down: bool,
+
+    /// True when the last polyline is still being extended.
+    drawing: bool,
+    stack: Vec<(Vec2, f32)>,
+    paths: Vec<Vec<Vec2>>,
+}
+
+/// A set of rewriting rules which expands an axiom into turtle commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LSystem {
+    axiom: String,
+    rules: HashMap<char, String>,
+}
+
+impl Default for Turtle {
+    /// A turtle at the origin, facing up.
+    fn default() -> Self {
+        Self::new(Vec2::zeros(), std::f32::consts::FRAC_PI_2)
+    }
+}
+
+// Public API
+// ----------
+
+impl Turtle {
+    /// Create a turtle with its pen down.
+    ///
+    /// # Params
+    ///
+    /// * `pos` - the starting position
+    /// * `heading` - the starting direction in radians, counter-clockwise from
+    ///   the positive x axis
+    pub fn new(pos: Vec2, heading: f32) -> Self {
+        Self {
+            pos,
+            heading,
+            pen_down: true,
+            drawing: false,
+            stack: vec![],
+            paths: vec![],
+        }
+    }
+
+    pub fn position(&self) -> Vec2 {
+        self.pos
+    }
+
+    pub fn heading(&self) -> f32 {
+        self.heading
+    }
+
+    /// Move forward, drawing a line if the pen is down.
+    pub fn forward(&mut self, distance: f32) {
+        let next = self.pos + Vec2::from_angle(self.heading) * distance;
+        if self.pen_down {
+            if !self.drawing {
+                self.paths.push(vec![self.pos]);
+                self.drawing = true;
+            }
+            self.paths.last_mut().unwrap().push(next);
+        }
+        self.pos = next;
+    }
+
+    pub fn backward(&mut self, distance: f32) {
+        self.forward(-distance);
+    }
+
+    /// Turn counter-clockwise by `angle` radians.
+    pub fn turn_left(&mut self, angle: f32) {
+        self.heading += angle;
+    }
+
+    /// Turn clockwise by `angle` radians.
+    pub fn turn_right(&mut self, angle: f32) {
+        self.heading -= angle;
+    }
+
+    pub fn pen_up(&mut self) {
+        self.pen_down = false;
+        self.drawing = false;
+    }
+
+    pub fn pen_down(&mut self) {
+        self.pen_down = true;
+    }
+
+    /// Move to a position without drawing.
+    pub fn jump_to(&mut self, pos: Vec2) {
+        self.pos = pos;
+        self.drawing = false;
+    }
+
+    /// Save the turtle's position and heading.
+    pub fn push(&mut self) {
+        self.stack.push((self.pos, self.heading));
+    }
+
+    /// Return to the most recently pushed position and heading without
+    /// drawing. Does nothing if nothing was pushed.
+    pub fn pop(&mut self) {
+        if let Some((pos, heading)) = self.stack.pop() {
+            self.jump_to(pos);
+            self.heading = heading;
+        }
+    }
+
+    /// Follow a string of commands, such as the output of an `LSystem`.
+    ///
+    /// * `F` and `G` move forward by `step` and draw
+    /// * `f` moves forward by `step` without drawing
+    /// * `+` turns left by `angle` and `-` turns right by `angle`
+    /// * `|` turns around
+    /// * `[` pushes and `]` pops
+    ///
+    /// Any other character is ignored.
+    pub fn run(&mut self, commands: &str, step: f32, angle: f32) {
+        for command in commands.chars() {
+            match command {
+                'F' | 'G' => self.forward(step),
+                'f' => {
+                    let pen_down = self.pen_down;
+                    self.pen_up();
+                    self.forward(step);
+                    self.pen_down = pen_down;
+                }
+                '+' => self.turn_left(angle),
+                '-' => self.turn_right(angle),
+                '|' => self.turn_left(std::f32::consts::PI),
+                '[' => self.push(),
+                ']' => self.pop(),
+                _ => (),
+            }
+        }
+    }
+
+    /// The polylines drawn so far. Each polyline has at least two points.
+    pub fn paths(&self) -> &[Vec<Vec2>] {
+        &self.paths
+    }
+
+    /// Remove every polyline, keeping the turtle's position and heading.
+    pub fn clear(&mut self) {
+        self.paths.clear();
+        self.drawing = false;
+    }
+
+    /// Draw every polyline with the current fill color and line width.
+    pub fn draw(&self, g: &mut G2D) {
+        let original_image = g.image;
+        g.image = Image::none();
+        for path in &self.paths {
+            for segment in path.windows(2) {
+                g.line(segment[0], segment[1]);
+            }
+        }
+        g.image = original_image;
+    }
+}
+
+impl LSystem {
+    pub fn new(axiom: impl Into<String>) -> Self {
+        Self {
+            axiom: axiom.into(),
+            rules: HashMap::new(),
+        }
+    }
+
+    /// Replace every `symbol` with `replacement` on each iteration.
+    pub fn with_rule(
+        mut self,
+        symbol: char,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.rules.insert(symbol, replacement.into());
+        self
+    }
+
+    /// Apply the rules to the axiom `iterations` times. Symbols without a
+    /// rule are kept as they are.
+    ///
+    /// The output grows exponentially with most rules, so keep the number
+    /// of iterations small.
+    pub fn expand(&self, iterations: u32) -> String {
+        let mut current = self.axiom.clone();
+        for _ in 0..iterations {
+            let mut next = String::with_capacity(current.len() * 2);
+            for symbol in current.chars() {
+                match self.rules.get(&symbol) {
+                    Some(replacement) => next.push_str(replacement),
+                    None => next.push(symbol),
+                }
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, approx::assert_relative_eq, std::f32::consts::FRAC_PI_2};
+
+    #[test]
+    fn test_expand_and_run() {
+        let system = LSystem::new("F").with_rule('F', "F+F");
+        assert_eq!(system.expand(2), "F+F+F+F");
+
+        let mut turtle = Turtle::new(Vec2::zeros(), 0.0);
+        turtle.run(&system.expand(2), 1.0, FRAC_PI_2);
+        assert_eq!(turtle.paths().len(), 1);
+        assert_eq!(turtle.paths()[0].len(), 5);
+        assert_relative_eq!(turtle.position(), Vec2::zeros(), epsilon = 1e-5);
+
+        // The branch extends the square, then the jump starts a new path.
+        turtle.run("[F]fF", 1.0, 0.0);
+        assert_eq!(turtle.paths().len(), 2);
+        assert_eq!(turtle.paths()[0].len(), 6);
+    }
+}
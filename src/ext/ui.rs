@@ -0,0 +1,340 @@
+//! A tiny immediate-mode widget set for tuning sketch parameters live.
+//!
+//! Keep a `Ui` in the sketch, call `begin` once per update, then call a
+//! widget method for each parameter. Widgets are laid out in a column, one
+//! per row, and draw immediately with `G2D`.
+//!
+//! ```ignore
+//! fn update(&mut self, sim: &mut Sim2D) {
+//!     self.ui.begin(&sim.w, Vec2::new(-sim.w.width() * 0.5 + 10.0, 0.0));
+//!     self.ui.slider(&mut sim.g, "speed", &mut self.speed, 0.0..=10.0);
+//!     self.ui.toggle(&mut sim.g, "trails", &mut self.trails);
+//!     if self.ui.button(&mut sim.g, "reset") {
+//!         self.reset();
+//!     }
+//!     self.ui.color(&mut sim.g, "tint", &mut self.tint);
+//! }
+//! ```
+//!
+//! Widgets are identified by the order they are called in, so the same
+//! widgets should be created in the same order every frame.
+
+use {
+    crate::{
+        graphics::{Image, G2D},
+        math::{Rect, Vec2},
+        window::WindowState,
+    },
+    std::ops::RangeInclusive,
+};
+
+/// Colors used to draw widgets.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UiStyle {
+    pub text_color: [f32; 4],
+
+    /// The color behind each row.
+    pub background: [f32; 4],
+
+    /// The color of slider tracks, checkboxes, and buttons.
+    pub control_color: [f32; 4],
+
+    /// The color of filled regions and hovered controls.
+    pub accent_color: [f32; 4],
+}
+
+impl Default for UiStyle {
+    fn default() -> Self {
+        Self {
+            text_color: [1.0, 1.0, 1.0, 1.0],
+            background: [0.1, 0.1, 0.1, 0.75],
+            control_color: [0.3, 0.3, 0.3, 1.0],
+            accent_color: [0.3, 0.6, 0.9, 1.0],
+        }
+    }
+}
+
+/// The state which persists between frames for immediate-mode widgets.
+#[derive(Debug, Clone)]
+pub struct Ui {
+    pub style: UiStyle,
+
+    /// The width of each row in screen units.
+    pub width: f32,
+
+    /// The height of each row in screen units.
+    pub row_height: f32,
+
+    top_left: Vec2,
+    next_row: usize,
+    next_id: usize,
+
+    /// The widget which was pressed and has not been released yet.
+    active: Option<usize>,
+    mouse: Vec2,
+    mouse_down: bool,
+    pressed: bool,
+    released: bool,
+    hovered_any: bool,
+}
+
+impl Default for Ui {
+    fn default() -> Self {
+        Self {
+            style: UiStyle::default(),
+            width: 300.0,
+            row_height: 28.0,
+            top_left: Vec2::zeros(),
+            next_row: 0,
+            next_id: 0,
+            active: None,
+            mouse: Vec2::zeros(),
+            mouse_down: false,
+            pressed: false,
+            released: false,
+            hovered_any: false,
+        }
+    }
+}
+
+// Public API
+// ----------
+
+impl Ui {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start laying out widgets for this frame.
+    ///
+    /// # Params
+    ///
+    /// * `w` - the window state, used to read the mouse
+    /// * `top_left` - the top left corner of the first row
+    pub fn begin(&mut self, w: &WindowState, top_left: Vec2) {
+        if self.released {
+            self.active = None;
+        }
+
+        let mouse_down = w.is_left_mouse_button_pressed();
+        self.pressed = mouse_down && !self.mouse_down;
+        self.released = !mouse_down && self.mouse_down;
+        self.mouse_down = mouse_down;
+        self.mouse = w.mouse_pos();
+
+        self.top_left = top_left;
+        self.next_row = 0;
+        self.next_id = 0;
+        self.hovered_any = false;
+    }
+
+    /// Returns true when the mouse is over a widget or dragging one.
+    ///
+    /// Sketches can check this to ignore clicks meant for the UI.
+    pub fn is_using_mouse(&self) -> bool {
+        self.hovered_any || self.active.is_some()
+    }
+
+    /// A horizontal slider. Returns true when the value changed.
+    pub fn slider(
+        &mut self,
+        g: &mut G2D,
+        label: &str,
+        value: &mut f32,
+        range: RangeInclusive<f32>,
+    ) -> bool {
+        let (label_area, control) = split(&self.next_row(g));
+        let (hovered, active) = self.interact(&control);
+
+        let (min, max) = (*range.start(), *range.end());
+        let original = *value;
+        if active && self.mouse_down {
+            let t = ((self.mouse.x - control.min.x) / control.width())
+                .clamp(0.0, 1.0);
+            *value = min + (max - min) * t;
+        }
+        let t = if max > min {
+            ((*value - min) / (max - min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        self.draw(g, |ui, g| {
+            ui.fill(g, &control, ui.style.control_color);
+            let filled = Rect::new(
+                control.min,
+                Vec2::new(control.min.x + control.width() * t, control.max.y),
+            );
+            ui.fill(g, &filled, ui.highlight(hovered || active));
+            ui.label(g, &label_area, &format!("{}: {:.3}", label, value));
+        });
+        *value != original
+    }
+
+    /// A checkbox. Returns true when the value changed.
+    pub fn toggle(
+        &mut self,
+        g: &mut G2D,
+        label: &str,
+        value: &mut bool,
+    ) -> bool {
+        let (label_area, control) = split(&self.next_row(g));
+        let checkbox = Rect::from_center(
+            Vec2::new(
+                control.min.x + control.height() * 0.5,
+                control.center().y,
+            ),
+            Vec2::new(control.height(), control.height()),
+        );
+        let (hovered, clicked) = self.click(&checkbox);
+        if clicked {
+            *value = !*value;
+        }
+
+        self.draw(g, |ui, g| {
+            ui.fill(g, &checkbox, ui.style.control_color);
+            if *value {
+                ui.fill(g, &checkbox.inflate(-4.0), ui.highlight(hovered));
+            }
+            ui.label(g, &label_area, label);
+        });
+        clicked
+    }
+
+    /// A push button. Returns true on the frame the button is clicked.
+    pub fn button(&mut self, g: &mut G2D, label: &str) -> bool {
+        let row = self.next_row(g);
+        let (hovered, clicked) = self.click(&row);
+
+        self.draw(g, |ui, g| {
+            let color = if hovered {
+                ui.style.accent_color
+            } else {
+                ui.style.control_color
+            };
+            ui.fill(g, &row, color);
+            ui.label(g, &row, label);
+        });
+        clicked
+    }
+
+    /// A color swatch with a slider for each of the red, green, and blue
+    /// channels. Returns true when the color changed.
+    pub fn color(
+        &mut self,
+        g: &mut G2D,
+        label: &str,
+        color: &mut [f32; 4],
+    ) -> bool {
+        let (label_area, control) = split(&self.next_row(g));
+        self.draw(g, |ui, g| {
+            ui.fill(g, &control, *color);
+            ui.label(g, &label_area, label);
+        });
+
+        let mut changed = false;
+        for (channel, name) in color.iter_mut().zip(["r", "g", "b"]) {
+            changed |= self.slider(g, name, channel, 0.0..=1.0);
+        }
+        changed
+    }
+}
+
+// Private API
+// -----------
+
+/// Space between the edge of a row and its contents.
+const PADDING: f32 = 3.0;
+
+impl Ui {
+    fn row_rect(&self, row: usize) -> Rect {
+        let top = self.top_left.y - row as f32 * self.row_height;
+        Rect::new(
+            Vec2::new(self.top_left.x, top - self.row_height),
+            Vec2::new(self.top_left.x + self.width, top),
+        )
+    }
+
+    /// Claim the next row and draw its background. Returns the area inside
+    /// the row's padding.
+    fn next_row(&mut self, g: &mut G2D) -> Rect {
+        let row = self.row_rect(self.next_row);
+        self.next_row += 1;
+        if row.contains(self.mouse) {
+            self.hovered_any = true;
+        }
+        self.draw(g, |ui, g| ui.fill(g, &row, ui.style.background));
+        row.inflate(-PADDING)
+    }
+
+    /// Update the active widget and return (hovered, active) for a widget
+    /// which covers `rect`.
+    fn interact(&mut self, rect: &Rect) -> (bool, bool) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let hovered = rect.contains(self.mouse);
+        if hovered && self.pressed && self.active.is_none() {
+            self.active = Some(id);
+        }
+        (hovered, self.active == Some(id))
+    }
+
+    /// Returns (hovered, clicked) for a widget which is clicked by pressing
+    /// and releasing the mouse over it.
+    fn click(&mut self, rect: &Rect) -> (bool, bool) {
+        let (hovered, active) = self.interact(rect);
+        (hovered, active && hovered && self.released)
+    }
+
+    fn highlight(&self, hovered: bool) -> [f32; 4] {
+        let [r, g, b, a] = self.style.accent_color;
+        if hovered {
+            [
+                (r + 0.15).min(1.0),
+                (g + 0.15).min(1.0),
+                (b + 0.15).min(1.0),
+                a,
+            ]
+        } else {
+            [r, g, b, a]
+        }
+    }
+
+    /// Call `f` to draw, then restore the sketch's fill color and image.
+    fn draw<F>(&self, g: &mut G2D, f: F)
+    where
+        F: FnOnce(&Self, &mut G2D),
+    {
+        let original_color = g.fill_color;
+        let original_image = g.image;
+        g.image = Image::none();
+        f(self, g);
+        g.fill_color = original_color;
+        g.image = original_image;
+    }
+
+    fn fill(&self, g: &mut G2D, rect: &Rect, color: [f32; 4]) {
+        g.fill_color = color;
+        g.rect(Vec2::new(rect.min.x, rect.max.y), rect.size(), 0.0);
+    }
+
+    /// Draw text vertically centered in `area`.
+    fn label(&self, g: &mut G2D, area: &Rect, text: &str) {
+        let size = g.text_size(text);
+        g.fill_color = self.style.text_color;
+        g.text(
+            Vec2::new(area.min.x + PADDING, area.center().y + size.y * 0.5),
+            text,
+        );
+    }
+}
+
+/// Split a row into a label area on the left and a control area on the right.
+fn split(row: &Rect) -> (Rect, Rect) {
+    let middle = row.min.x + row.width() * 0.5;
+    (
+        Rect::new(row.min, Vec2::new(middle, row.max.y)),
+        Rect::new(Vec2::new(middle, row.min.y), row.max),
+    )
+}
@@ -0,0 +1,371 @@
+use {
+    crate::{
+        graphics::{
+            vulkan_api::{ShaderInputs, CHANNEL_COUNT},
+            AssetLoader, BackgroundShader, Image,
+        },
+        live_reload::{draw_error, WatchedFile},
+        math::Vec2,
+        sim2d::Sim2D,
+        Sketch,
+    },
+    anyhow::{bail, Context, Result},
+    image::RgbaImage,
+    std::{
+        io::Write,
+        path::{Path, PathBuf},
+        process::{Command, Stdio},
+        sync::Arc,
+    },
+};
+
+/// The first word of every SPIR-V module.
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+/// Declares Shadertoy's inputs before the user's code.
+const GLSL_PRELUDE: &str = "#version 450
+layout(push_constant) uniform ShadertoyInputs {
+    vec4 iMouse;
+    vec3 iResolution;
+    float iTime;
+    float iTimeDelta;
+    int iFrame;
+};
+layout(set = 0, binding = 0) uniform sampler2D iChannel0;
+layout(set = 0, binding = 1) uniform sampler2D iChannel1;
+layout(set = 0, binding = 2) uniform sampler2D iChannel2;
+layout(set = 0, binding = 3) uniform sampler2D iChannel3;
+layout(location = 0) out vec4 sim2d_FragColor;
+#line 1
+";
+
+/// Calls `mainImage` with Shadertoy's bottom-left pixel origin.
+const GLSL_MAIN: &str = "
+void main() {
+    vec2 fragCoord = vec2(gl_FragCoord.x, iResolution.y - gl_FragCoord.y);
+    mainImage(sim2d_FragColor, fragCoord);
+    sim2d_FragColor.a = 1.0;
+}
+";
+
+/// A sketch which draws a single Shadertoy-style fragment shader over the
+/// whole window.
+///
+/// GLSL shaders are written like they are on Shadertoy, with a `mainImage`
+/// function and the `iTime`, `iTimeDelta`, `iFrame`, `iResolution`, `iMouse`,
+/// and `iChannel0` through `iChannel3` inputs:
+///
+/// ```ignore
+/// let sketch = FragmentSketch::from_glsl_file("clouds.glsl")?
+///     .with_channel(0, "noise.png");
+/// Application::run(sketch)
+/// ```
+///
+/// GLSL is compiled at runtime by running `glslc`, so `glslc` must be on
+/// the `PATH` of every machine which runs the sketch, not just the one which
+/// builds it. It comes with the Vulkan SDK and with shaderc. To ship a sketch
+/// without that requirement, compile the shader ahead of time and load it
+/// with `from_spirv` or `from_spirv_file`. SPIR-V shaders need their own
+/// `main` and must declare the inputs the same way, see `ShaderInputs` in
+/// the source.
+///
+/// Shaders loaded from files are reloaded when the file changes. If the new
+/// version doesn't compile, the previous shader keeps running and the error is
+/// drawn in the corner of the window.
+///
+/// Sprites drawn by other sketches, like layers in a `SketchStack`, are drawn
+/// on top of the shader.
+pub struct FragmentSketch {
+    spirv: Arc<[u8]>,
+    file: Option<ShaderFile>,
+
+    /// Why the shader file couldn't be reloaded.
+    error: Option<String>,
+
+    channel_paths: [Option<PathBuf>; CHANNEL_COUNT],
+    channels: [Image; CHANNEL_COUNT],
+    time: f32,
+    frame: i32,
+
+    /// Where the left mouse button was last pressed, in pixels.
+    click: Vec2,
+    was_mouse_down: bool,
+}
+
+// Public API
+// ----------
+
+impl FragmentSketch {
+    /// Compile a Shadertoy GLSL shader. Fails if `glslc` isn't on the
+    /// `PATH`.
+    pub fn from_glsl(source: &str) -> Result<Self> {
+        Self::from_spirv(&compile_glsl(source)?)
+    }
+
+    pub fn from_glsl_file(path: impl AsRef<Path>) -> Result<Self> {
+        let source =
+            std::fs::read_to_string(path.as_ref()).with_context(|| {
+                format!("Unable to read shader {:?}", path.as_ref())
+            })?;
+        let mut sketch = Self::from_glsl(&source).with_context(|| {
+            format!("Unable to compile {:?}", path.as_ref())
+        })?;
+        sketch.file = Some(ShaderFile::new(path.as_ref(), true));
+        Ok(sketch)
+    }
+
+    /// Use a compiled fragment shader.
+    pub fn from_spirv(bytes: &[u8]) -> Result<Self> {
+        check_spirv(bytes)?;
+        Ok(Self {
+            spirv: bytes.into(),
+            file: None,
+            error: None,
+            channel_paths: Default::default(),
+            channels: [Image::none(); CHANNEL_COUNT],
+            time: 0.0,
+            frame: 0,
+            click: Vec2::zeros(),
+            was_mouse_down: false,
+        })
+    }
+
+    pub fn from_spirv_file(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = std::fs::read(path.as_ref()).with_context(|| {
+            format!("Unable to read shader {:?}", path.as_ref())
+        })?;
+        let mut sketch = Self::from_spirv(&bytes)?;
+        sketch.file = Some(ShaderFile::new(path.as_ref(), false));
+        Ok(sketch)
+    }
+
+    /// Bind an image file to `iChannel{index}`. The image is loaded during
+    /// preload, relative to the asset root.
+    ///
+    /// Channels without an image sample black.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is 4 or more.
+    pub fn with_channel(
+        mut self,
+        index: usize,
+        image_path: impl Into<PathBuf>,
+    ) -> Self {
+        assert!(
+            index < CHANNEL_COUNT,
+            "Shaders only have {} channels",
+            CHANNEL_COUNT
+        );
+        self.channel_paths[index] = Some(image_path.into());
+        self
+    }
+
+    /// The shader's `iTime` in seconds.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Restart `iTime` and `iFrame` from zero.
+    pub fn restart(&mut self) {
+        self.time = 0.0;
+        self.frame = 0;
+    }
+
+    /// Why the shader file couldn't be reloaded, if the last attempt failed.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+impl Sketch for FragmentSketch {
+    fn preload(&mut self, asset_loader: &mut AssetLoader) -> Result<()> {
+        let black = asset_loader.load_image(
+            RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 255])),
+            false,
+            "sim2d-fragment-sketch-black",
+        );
+        for (channel, path) in self.channels.iter_mut().zip(&self.channel_paths)
+        {
+            *channel = match path {
+                Some(path) => asset_loader.load_image_file(path, true)?,
+                None => black,
+            };
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, sim: &mut Sim2D) {
+        self.reload_if_changed();
+
+        let dt = sim.dt();
+        self.time += dt;
+
+        // The mouse position is relative to the center of the window, but
+        // Shadertoy measures from the bottom left.
+        let half_size = Vec2::new(sim.w.width(), sim.w.height()) * 0.5;
+        let mouse = sim.w.mouse_pos() + half_size;
+        let is_mouse_down = sim.w.is_left_mouse_button_pressed();
+        let just_pressed = is_mouse_down && !self.was_mouse_down;
+        if just_pressed {
+            self.click = mouse;
+        }
+        self.was_mouse_down = is_mouse_down;
+
+        // Like Shadertoy, xy follows the mouse while the button is held,
+        // z is negative once the button is released, and w is only positive
+        // on the frame the button was pressed.
+        let drag = if is_mouse_down { mouse } else { self.click };
+        let sign = |positive: bool| if positive { 1.0 } else { -1.0 };
+        let inputs = ShaderInputs {
+            mouse: [
+                drag.x,
+                drag.y,
+                self.click.x * sign(is_mouse_down),
+                self.click.y * sign(just_pressed),
+            ],
+            resolution: [sim.w.width(), sim.w.height(), 1.0],
+            time: self.time,
+            time_delta: dt,
+            frame: self.frame,
+        };
+        self.frame += 1;
+
+        sim.background_shader = Some(BackgroundShader {
+            spirv: self.spirv.clone(),
+            inputs,
+            channels: self.channels,
+        });
+
+        if let Some(error) = &self.error {
+            draw_error(sim, error);
+        }
+    }
+}
+
+// Private API
+// -----------
+
+/// A shader file to watch for changes.
+struct ShaderFile {
+    file: WatchedFile,
+    is_glsl: bool,
+}
+
+impl ShaderFile {
+    fn new(path: &Path, is_glsl: bool) -> Self {
+        Self {
+            file: WatchedFile::new(path),
+            is_glsl,
+        }
+    }
+
+    fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    fn load(&self) -> Result<Vec<u8>> {
+        if self.is_glsl {
+            let source =
+                std::fs::read_to_string(self.path()).with_context(|| {
+                    format!("Unable to read shader {:?}", self.path())
+                })?;
+            compile_glsl(&source)
+        } else {
+            let bytes = std::fs::read(self.path()).with_context(|| {
+                format!("Unable to read shader {:?}", self.path())
+            })?;
+            check_spirv(&bytes)?;
+            Ok(bytes)
+        }
+    }
+}
+
+impl FragmentSketch {
+    /// Swap in the shader file's new code when it changes. The renderer
+    /// rebuilds its pipeline between frames when it sees the new code.
+    fn reload_if_changed(&mut self) {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => return,
+        };
+        if !file.file.is_changed() {
+            return;
+        }
+        match file.load() {
+            Ok(spirv) => {
+                log::info!("Reloaded shader {:?}", file.path());
+                self.spirv = spirv.into();
+                self.error = None;
+            }
+            Err(error) => {
+                let error = format!("{:?}: {:#}", file.path(), error);
+                log::error!("{}", error);
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+fn check_spirv(bytes: &[u8]) -> Result<()> {
+    if bytes.len() < 4 || !bytes.chunks_exact(4).remainder().is_empty() {
+        bail!("SPIR-V must be a whole number of 32 bit words");
+    }
+    let magic = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    if magic != SPIRV_MAGIC {
+        bail!("The shader is not SPIR-V, it doesn't start with 0x07230203");
+    }
+    Ok(())
+}
+
+/// Wrap Shadertoy code with its inputs and a `main`, then compile it to
+/// SPIR-V with `glslc`.
+fn compile_glsl(source: &str) -> Result<Vec<u8>> {
+    let mut child = Command::new("glslc")
+        .args(["-fshader-stage=frag", "-o", "-", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(
+            "Unable to run glslc. Compiling GLSL at runtime needs glslc from \
+             the Vulkan SDK or shaderc on the PATH, or load precompiled \
+             SPIR-V with FragmentSketch::from_spirv instead.",
+        )?;
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        stdin
+            .write_all(wrap_glsl(source).as_bytes())
+            .context("Unable to send the shader to glslc!")?;
+    }
+    let output = child
+        .wait_with_output()
+        .context("Unable to wait for glslc!")?;
+    if !output.status.success() {
+        bail!(
+            "Shader compilation failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(output.stdout)
+}
+
+fn wrap_glsl(source: &str) -> String {
+    format!("{}{}{}", GLSL_PRELUDE, source, GLSL_MAIN)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_spirv() {
+        assert!(FragmentSketch::from_spirv(&[0x03, 0x02, 0x23]).is_err());
+        assert!(FragmentSketch::from_spirv(&[0; 8]).is_err());
+        assert!(FragmentSketch::from_spirv(&[0x03, 0x02, 0x23, 0x07]).is_ok());
+
+        let wrapped = wrap_glsl("void mainImage(out vec4 c, in vec2 p) {}");
+        assert!(wrapped.starts_with("#version 450"));
+        assert!(wrapped.contains("#line 1\nvoid mainImage"));
+    }
+}
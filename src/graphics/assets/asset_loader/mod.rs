@@ -3,22 +3,33 @@ mod new_assets;
 use {
     super::FontId,
     crate::graphics::{
-        assets::{CachedFont, Image, TextureId},
-        vulkan_api::RenderDevice,
+        assets::{CachedFont, Image, ImagePixels, SamplerSettings, TextureId},
+        vulkan_api::{RenderDevice, StagingPool},
         GraphicsError,
     },
     ::image::RgbaImage,
     ab_glyph::{Font, FontVec, PxScaleFont},
     anyhow::Context,
-    std::{collections::HashMap, path::Path, sync::Arc},
+    std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+        sync::Arc,
+    },
 };
 
 pub use self::new_assets::NewAssets;
+#[cfg(feature = "camera")]
+use crate::graphics::assets::camera::Camera;
+#[cfg(feature = "capture")]
+use crate::graphics::assets::capture::{CaptureSource, ScreenCapture};
+#[cfg(any(feature = "camera", feature = "capture"))]
+use crate::graphics::assets::VideoTexture;
 
 #[derive(Debug, Clone)]
 pub struct TextureSource {
     img: RgbaImage,
     generate_mipmaps: bool,
+    sampler: SamplerSettings,
 }
 
 /// The public API for loading new images and textures for use in sketches.
@@ -26,15 +37,29 @@ pub struct AssetLoader {
     texture_base_index: usize,
     texture_sources: Vec<TextureSource>,
     cached_textures: HashMap<String, Image>,
+    sampler: SamplerSettings,
 
     font_base_index: usize,
     fonts: Vec<Arc<CachedFont>>,
     cached_fonts: HashMap<String, FontId>,
 
+    asset_root: PathBuf,
+    staging_pool: StagingPool,
     render_device: Arc<RenderDevice>,
 }
 
 impl AssetLoader {
+    /// Set how images loaded after this call are filtered. Images which
+    /// were already loaded under the same name keep their settings.
+    pub fn set_sampler(&mut self, sampler: SamplerSettings) {
+        self.sampler = sampler;
+    }
+
+    /// The settings used for newly loaded images.
+    pub fn sampler(&self) -> SamplerSettings {
+        self.sampler
+    }
+
     pub fn load_font(
         &mut self,
         font: PxScaleFont<FontVec>,
@@ -70,8 +95,9 @@ impl AssetLoader {
         file_path: impl AsRef<Path>,
         size: f32,
     ) -> Result<FontId, GraphicsError> {
+        let file_path = self.asset_root.join(file_path);
         let cache_id: String =
-            format!("{}-{}", file_path.as_ref().to_str().unwrap(), size);
+            format!("{}-{}", file_path.to_str().unwrap(), size);
         if let Some(font_id) = self.cached_fonts.get(&cache_id) {
             return Ok(*font_id);
         }
@@ -90,7 +116,8 @@ impl AssetLoader {
         file_path: impl AsRef<Path>,
         generate_mipmaps: bool,
     ) -> Result<Image, GraphicsError> {
-        let cache_id: String = file_path.as_ref().to_str().unwrap().to_owned();
+        let file_path = self.asset_root.join(file_path);
+        let cache_id: String = file_path.to_str().unwrap().to_owned();
 
         if let Some(image) = self.cached_textures.get(&cache_id) {
             return Ok(*image);
@@ -100,6 +127,25 @@ impl AssetLoader {
         Ok(self.load_image(img, generate_mipmaps, cache_id))
     }
 
+    /// Load an image file like `load_image_file`, and also keep a CPU copy
+    /// of its pixels so the sketch can read colors from it.
+    ///
+    /// Fails like `load_image_file` does, including for images with no
+    /// pixels.
+    pub fn load_image_file_with_pixels(
+        &mut self,
+        file_path: impl AsRef<Path>,
+        generate_mipmaps: bool,
+    ) -> Result<(Image, ImagePixels), GraphicsError> {
+        let file_path = self.asset_root.join(file_path);
+        let cache_id: String = file_path.to_str().unwrap().to_owned();
+
+        let img = Self::load_image_from_file(file_path)?;
+        let pixels = ImagePixels::new(img.clone());
+        let image = self.load_image(img, generate_mipmaps, cache_id);
+        Ok((image, pixels))
+    }
+
     pub fn load_image(
         &mut self,
         img: RgbaImage,
@@ -116,6 +162,7 @@ impl AssetLoader {
         let source = TextureSource {
             img,
             generate_mipmaps,
+            sampler: self.sampler,
         };
         self.texture_sources.push(source);
 
@@ -123,6 +170,67 @@ impl AssetLoader {
         self.cached_textures.insert(name.as_ref().to_owned(), image);
         image
     }
+
+    /// Start capturing from a webcam. The returned video texture is usable
+    /// anywhere an image is and is updated with `VideoTexture::update`.
+    ///
+    /// Cameras are numbered in the order the platform lists them, so 0 is
+    /// usually the built in webcam. Requires the `camera` feature.
+    #[cfg(feature = "camera")]
+    pub fn open_camera(
+        &mut self,
+        index: usize,
+    ) -> Result<VideoTexture, GraphicsError> {
+        let camera = Camera::open(index)
+            .with_context(|| format!("Unable to open camera {}", index))?;
+
+        // Each video texture needs its own texture, so the name can't be
+        // shared with anything already loaded.
+        let name = format!(
+            "camera-{}-{}",
+            index,
+            self.texture_base_index + self.texture_sources.len()
+        );
+        let placeholder = RgbaImage::from_pixel(
+            camera.width(),
+            camera.height(),
+            ::image::Rgba([0, 0, 0, 255]),
+        );
+        let image = self.load_image(placeholder, false, name);
+        Ok(VideoTexture::new(image, camera))
+    }
+
+    /// Start capturing the screen, a region of it, or another window. The
+    /// returned video texture is usable anywhere an image is and is updated
+    /// with `VideoTexture::update`.
+    ///
+    /// Only available on Linux with X11 and the `capture` feature. Wayland
+    /// sessions only allow capture through XWayland, which can't see native
+    /// Wayland windows.
+    #[cfg(feature = "capture")]
+    pub fn open_screen_capture(
+        &mut self,
+        source: CaptureSource,
+    ) -> Result<VideoTexture, GraphicsError> {
+        let capture =
+            ScreenCapture::open(source.clone()).with_context(|| {
+                format!("Unable to start screen capture of {:?}", source)
+            })?;
+
+        // Each video texture needs its own texture, so the name can't be
+        // shared with anything already loaded.
+        let name = format!(
+            "capture-{}",
+            self.texture_base_index + self.texture_sources.len()
+        );
+        let placeholder = RgbaImage::from_pixel(
+            capture.width(),
+            capture.height(),
+            ::image::Rgba([0, 0, 0, 255]),
+        );
+        let image = self.load_image(placeholder, false, name);
+        Ok(VideoTexture::new(image, capture))
+    }
 }
 
 impl AssetLoader {
@@ -132,20 +240,29 @@ impl AssetLoader {
         cached_textures: HashMap<String, Image>,
         font_base_index: usize,
         cached_fonts: HashMap<String, FontId>,
+        asset_root: PathBuf,
+        staging_pool: StagingPool,
     ) -> Self {
         Self {
             texture_base_index,
             texture_sources: vec![],
             cached_textures,
+            sampler: SamplerSettings::default(),
 
             font_base_index,
             cached_fonts,
             fonts: vec![],
 
+            asset_root,
+            staging_pool,
             render_device,
         }
     }
 
+    pub(crate) fn set_asset_root(&mut self, asset_root: PathBuf) {
+        self.asset_root = asset_root;
+    }
+
     pub(crate) fn texture_base_index(&self) -> usize {
         self.texture_base_index
     }
@@ -166,6 +283,11 @@ impl AssetLoader {
         &self.fonts
     }
 
+    /// Take the staging buffers back so the next loader can reuse them.
+    pub(crate) fn into_staging_pool(self) -> StagingPool {
+        self.staging_pool
+    }
+
     fn load_image_from_file(
         texture_path: impl AsRef<Path>,
     ) -> Result<RgbaImage, GraphicsError> {
@@ -184,6 +306,13 @@ impl AssetLoader {
                 )
             })?
             .into_rgba8();
+        if img.width() == 0 || img.height() == 0 {
+            // Vulkan can't create an image without pixels.
+            return Err(GraphicsError::RuntimeError(anyhow::anyhow!(
+                "Texture image at {:?} has no pixels",
+                texture_path.as_ref()
+            )));
+        }
         Ok(img)
     }
 }
@@ -1,9 +1,10 @@
 use {
     crate::graphics::{
         vulkan_api::{
-            raii, OneTimeSubmitCommandBuffer, RenderDevice, Texture2D,
+            raii, OneTimeSubmitCommandBuffer, RenderDevice, StagingPool,
+            Texture2D,
         },
-        AssetLoader, GraphicsError,
+        AssetLoader, GraphicsError, SamplerSettings,
     },
     ash::vk,
     image::RgbaImage,
@@ -33,6 +34,11 @@ impl NewAssets {
     ) -> Result<Self, GraphicsError> {
         let start_time = Instant::now();
 
+        let samplers = asset_loader
+            .texture_sources
+            .iter()
+            .map(|source| source.sampler)
+            .collect::<Vec<SamplerSettings>>();
         let images = asset_loader
             .texture_sources
             .par_drain(0..)
@@ -46,8 +52,14 @@ impl NewAssets {
             .collect::<Vec<Vec<image::RgbaImage>>>();
 
         let render_device = asset_loader.render_device.clone();
-        let (textures, image_acquire_barriers) =
-            unsafe { Self::build_and_upload_textures(render_device, &images)? };
+        let (textures, image_acquire_barriers) = unsafe {
+            Self::build_and_upload_textures(
+                render_device,
+                &mut asset_loader.staging_pool,
+                &images,
+                &samplers,
+            )?
+        };
 
         let time_to_build_textures = Instant::now() - start_time;
         log::trace!(
@@ -94,7 +106,9 @@ impl NewAssets {
 
     unsafe fn build_and_upload_textures(
         render_device: Arc<RenderDevice>,
+        staging_pool: &mut StagingPool,
         images: &[Vec<image::RgbaImage>],
+        samplers: &[SamplerSettings],
     ) -> Result<
         (Vec<Arc<Texture2D>>, Vec<vk::ImageMemoryBarrier2>),
         GraphicsError,
@@ -108,10 +122,11 @@ impl NewAssets {
         let mut transfer_acquire_barriers = vec![];
         let mut transfer_release_barriers = vec![];
         let mut grahpics_acquire_barriers = vec![];
-        for mips in images {
+        for (mips, &sampler) in images.iter().zip(samplers) {
             let texture = Arc::new(Self::allocate_new_texture(
                 render_device.clone(),
                 mips,
+                sampler,
             )?);
             textures.push(texture.clone());
 
@@ -172,11 +187,8 @@ impl NewAssets {
             })
             .sum();
 
-        let staging_buffer =
-            Self::allocate_staging_buffer(render_device.clone(), total_size)?;
-
-        let staging_buffer_ptr: *mut c_void =
-            staging_buffer.allocation().map(render_device.device())?;
+        let staging_buffer = staging_pool.take(total_size)?;
+        let staging_buffer_ptr: *mut c_void = staging_buffer.ptr();
 
         let mut buffer_offset = 0;
         for (texture_index, mips) in images.iter().enumerate() {
@@ -187,7 +199,7 @@ impl NewAssets {
                 // Should always be true given the total_size calculation
                 debug_assert!(
                     buffer_offset + mip.as_raw().len()
-                        <= staging_buffer.allocation().size_in_bytes() as usize
+                        <= staging_buffer.size() as usize
                 );
 
                 let staging_buffer_with_offset = (staging_buffer_ptr as usize
@@ -249,38 +261,16 @@ impl NewAssets {
         }
 
         one_time_submit.sync_submit_and_reset()?;
+        staging_pool.recycle(staging_buffer);
 
         Ok((textures, grahpics_acquire_barriers))
     }
 
-    /// Allocate a new host visible buffer to stage image data.
-    unsafe fn allocate_staging_buffer(
-        render_device: Arc<RenderDevice>,
-        size: u64,
-    ) -> Result<raii::Buffer, GraphicsError> {
-        unsafe {
-            let index = render_device.transfer_queue().family_index();
-            let create_info = vk::BufferCreateInfo {
-                size,
-                sharing_mode: vk::SharingMode::EXCLUSIVE,
-                queue_family_index_count: 1,
-                p_queue_family_indices: &index,
-                usage: vk::BufferUsageFlags::TRANSFER_SRC,
-                ..Default::default()
-            };
-            raii::Buffer::new(
-                render_device,
-                &create_info,
-                vk::MemoryPropertyFlags::HOST_VISIBLE
-                    | vk::MemoryPropertyFlags::HOST_COHERENT,
-            )
-        }
-    }
-
     /// Allocate a new 2d texture for the given RGBA image mipmaps.
     unsafe fn allocate_new_texture(
         render_device: Arc<RenderDevice>,
         mips: &[image::RgbaImage],
+        sampler: SamplerSettings,
     ) -> Result<Texture2D, GraphicsError> {
         let image = unsafe {
             let queue_family_index =
@@ -328,7 +318,11 @@ impl NewAssets {
             };
             raii::ImageView::new(render_device, &create_info)?
         };
-        Ok(Texture2D { image, image_view })
+        Ok(Texture2D {
+            image,
+            image_view,
+            sampler,
+        })
     }
 
     /// Create an image memory barrier which acquires the image as a transfer
@@ -0,0 +1,146 @@
+use {
+    super::video_texture::FrameSource,
+    anyhow::{Context, Result},
+    image::RgbaImage,
+    nokhwa::{
+        pixel_format::RgbAFormat,
+        utils::{CameraIndex, RequestedFormat, RequestedFormatType},
+    },
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc, Arc, Mutex,
+        },
+        thread::JoinHandle,
+    },
+};
+
+/// Captures frames from a webcam on a background thread.
+pub(super) struct Camera {
+    width: u32,
+    height: u32,
+
+    /// The newest frame which hasn't been taken yet. Older frames are
+    /// dropped.
+    latest: Arc<Mutex<Option<RgbaImage>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Camera {
+    /// Start capturing from the camera with the given index, in the order the
+    /// platform lists cameras.
+    pub fn open(index: usize) -> Result<Self> {
+        let latest = Arc::new(Mutex::new(None));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (opened_sender, opened) = mpsc::channel();
+
+        // Cameras aren't Send on every platform, so the camera is opened on
+        // the thread which reads it and only its size is sent back.
+        let thread = {
+            let latest = latest.clone();
+            let stop = stop.clone();
+            std::thread::Builder::new()
+                .name(format!("camera-{}", index))
+                .spawn(move || {
+                    let mut camera = match open_stream(index) {
+                        Ok(camera) => camera,
+                        Err(error) => {
+                            let _ = opened_sender.send(Err(error));
+                            return;
+                        }
+                    };
+                    let resolution = camera.resolution();
+                    let _ = opened_sender
+                        .send(Ok((resolution.width(), resolution.height())));
+
+                    while !stop.load(Ordering::Relaxed) {
+                        match read_frame(&mut camera) {
+                            Ok(frame) => *latest.lock().unwrap() = Some(frame),
+                            Err(error) => {
+                                log::error!(
+                                    "Camera {} stopped: {:?}",
+                                    index,
+                                    error
+                                );
+                                break;
+                            }
+                        }
+                    }
+                    if let Err(error) = camera.stop_stream() {
+                        log::warn!(
+                            "Unable to stop camera {}: {:?}",
+                            index,
+                            error
+                        );
+                    }
+                })
+                .context("Unable to start the camera thread!")?
+        };
+
+        let (width, height) = opened
+            .recv()
+            .context("The camera thread stopped before opening the camera")??;
+        Ok(Self {
+            width,
+            height,
+            latest,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl FrameSource for Camera {
+    fn take_frame(&self) -> Option<RgbaImage> {
+        self.latest.lock().unwrap().take()
+    }
+}
+
+impl Drop for Camera {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Open the camera and start streaming with its highest frame rate.
+fn open_stream(index: usize) -> Result<nokhwa::Camera> {
+    // macOS asks the user for camera access the first time. Opening the
+    // camera fails until access is granted.
+    #[cfg(target_os = "macos")]
+    nokhwa::nokhwa_initialize(|granted| {
+        if !granted {
+            log::error!("Camera access was denied");
+        }
+    });
+
+    let format = RequestedFormat::new::<RgbAFormat>(
+        RequestedFormatType::AbsoluteHighestFrameRate,
+    );
+    let mut camera =
+        nokhwa::Camera::new(CameraIndex::Index(index as u32), format)?;
+    camera.open_stream()?;
+    Ok(camera)
+}
+
+/// Wait for the camera's next frame and convert it to RGBA.
+fn read_frame(camera: &mut nokhwa::Camera) -> Result<RgbaImage> {
+    let frame = camera.frame()?;
+    let resolution = frame.resolution();
+    // nokhwa may use a different version of the image crate, so only the raw
+    // pixels are passed along.
+    let pixels = frame.decode_image::<RgbAFormat>()?.into_raw();
+    RgbaImage::from_raw(resolution.width(), resolution.height(), pixels)
+        .context("The camera's frame has an unexpected size")
+}
@@ -0,0 +1,132 @@
+#[cfg(target_os = "linux")]
+mod x11;
+
+use {
+    super::video_texture::FrameSource,
+    anyhow::Result,
+    image::RgbaImage,
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::JoinHandle,
+        time::Duration,
+    },
+};
+
+/// The shortest time between captured frames. Grabbing the screen is slow,
+/// so there's no point in capturing faster than the display refreshes.
+#[cfg(target_os = "linux")]
+const FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+
+/// What to capture with `AssetLoader::open_screen_capture`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureSource {
+    /// The whole screen.
+    Screen,
+
+    /// A rectangle of the screen in pixels, with x and y measured from the
+    /// top left of the screen.
+    Region {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+
+    /// The first visible window with a title containing the text. The
+    /// capture follows the window as it moves, and includes anything which
+    /// covers it.
+    Window(String),
+}
+
+/// Captures the screen on a background thread.
+pub(super) struct ScreenCapture {
+    width: u32,
+    height: u32,
+
+    /// The newest frame which hasn't been taken yet. Older frames are
+    /// dropped.
+    latest: Arc<Mutex<Option<RgbaImage>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ScreenCapture {
+    /// Start capturing from the X server.
+    #[cfg(target_os = "linux")]
+    pub fn open(source: CaptureSource) -> Result<Self> {
+        use {anyhow::Context, std::time::Instant};
+
+        let mut grabber = x11::Grabber::open(&source)?;
+        let (width, height) = (grabber.width(), grabber.height());
+        let latest = Arc::new(Mutex::new(None));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let latest = latest.clone();
+            let stop = stop.clone();
+            std::thread::Builder::new()
+                .name("screen-capture".to_owned())
+                .spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        let start = Instant::now();
+                        match grabber.grab() {
+                            Ok(frame) => *latest.lock().unwrap() = Some(frame),
+                            Err(error) => {
+                                log::error!(
+                                    "Screen capture of {:?} stopped: {:?}",
+                                    source,
+                                    error
+                                );
+                                break;
+                            }
+                        }
+                        if let Some(rest) =
+                            FRAME_INTERVAL.checked_sub(start.elapsed())
+                        {
+                            std::thread::sleep(rest);
+                        }
+                    }
+                })
+                .context("Unable to start the screen capture thread!")?
+        };
+
+        Ok(Self {
+            width,
+            height,
+            latest,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn open(_source: CaptureSource) -> Result<Self> {
+        anyhow::bail!("Screen capture is only supported on Linux with X11")
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl FrameSource for ScreenCapture {
+    fn take_frame(&self) -> Option<RgbaImage> {
+        self.latest.lock().unwrap().take()
+    }
+}
+
+impl Drop for ScreenCapture {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Grabs screen pixels from the X server with GetImage.
+
+use {
+    super::CaptureSource,
+    anyhow::{bail, Context, Result},
+    image::RgbaImage,
+    x11rb::{
+        connection::Connection,
+        protocol::xproto::{
+            AtomEnum, ConnectionExt, ImageFormat, ImageOrder, MapState, Window,
+        },
+        rust_connection::RustConnection,
+    },
+};
+
+/// Copies a fixed size region of the root window. When capturing a window,
+/// the region follows the window as it moves.
+pub(super) struct Grabber {
+    connection: RustConnection,
+    root: Window,
+    screen_width: u16,
+    screen_height: u16,
+    msb_first: bool,
+
+    window: Option<Window>,
+    x: i16,
+    y: i16,
+    width: u16,
+    height: u16,
+}
+
+// Public API
+// ----------
+
+impl Grabber {
+    pub fn open(source: &CaptureSource) -> Result<Self> {
+        let (connection, screen_index) = x11rb::connect(None)
+            .context("Unable to connect to the X server!")?;
+        let setup = connection.setup();
+        let screen = &setup.roots[screen_index];
+        let (root, screen_width, screen_height) =
+            (screen.root, screen.width_in_pixels, screen.height_in_pixels);
+        let bits_per_pixel = setup
+            .pixmap_formats
+            .iter()
+            .find(|format| format.depth == screen.root_depth)
+            .map(|format| format.bits_per_pixel);
+        if bits_per_pixel != Some(32) {
+            bail!(
+                "Unsupported screen depth {} with {:?} bits per pixel",
+                screen.root_depth,
+                bits_per_pixel
+            );
+        }
+        let msb_first = setup.image_byte_order == ImageOrder::MSB_FIRST;
+
+        let mut grabber = Self {
+            connection,
+            root,
+            screen_width,
+            screen_height,
+            msb_first,
+            window: None,
+            x: 0,
+            y: 0,
+            width: screen_width,
+            height: screen_height,
+        };
+        match source {
+            CaptureSource::Screen => (),
+            CaptureSource::Region {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                grabber.x = *x as i16;
+                grabber.y = *y as i16;
+                grabber.width = (*width as u16).min(screen_width);
+                grabber.height = (*height as u16).min(screen_height);
+            }
+            CaptureSource::Window(title) => {
+                let window =
+                    grabber.find_window(root, title)?.with_context(|| {
+                        format!("No visible window is titled {:?}", title)
+                    })?;
+                let geometry =
+                    grabber.connection.get_geometry(window)?.reply()?;
+                grabber.window = Some(window);
+                grabber.width = geometry.width.min(screen_width);
+                grabber.height = geometry.height.min(screen_height);
+            }
+        }
+        if grabber.width == 0 || grabber.height == 0 {
+            bail!("Unable to capture an empty region");
+        }
+        Ok(grabber)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height as u32
+    }
+
+    /// Copy the current contents of the region. Fails once a captured
+    /// window is closed.
+    pub fn grab(&mut self) -> Result<RgbaImage> {
+        if let Some(window) = self.window {
+            let position = self
+                .connection
+                .translate_coordinates(window, self.root, 0, 0)?
+                .reply()
+                .context("The captured window was closed")?;
+            self.x = position.dst_x;
+            self.y = position.dst_y;
+        }
+
+        // GetImage fails for regions which leave the screen, so keep the
+        // region on screen instead.
+        let x = self.x.clamp(0, (self.screen_width - self.width) as i16);
+        let y = self.y.clamp(0, (self.screen_height - self.height) as i16);
+        let image = self
+            .connection
+            .get_image(
+                ImageFormat::Z_PIXMAP,
+                self.root,
+                x,
+                y,
+                self.width,
+                self.height,
+                !0,
+            )?
+            .reply()
+            .context("Unable to read the screen!")?;
+
+        let mut rgba = image.data;
+        for pixel in rgba.chunks_exact_mut(4) {
+            let [r, g, b] = if self.msb_first {
+                [pixel[1], pixel[2], pixel[3]]
+            } else {
+                [pixel[2], pixel[1], pixel[0]]
+            };
+            pixel.copy_from_slice(&[r, g, b, 255]);
+        }
+        RgbaImage::from_raw(self.width(), self.height(), rgba)
+            .context("The screen image has an unexpected size")
+    }
+}
+
+// Private API
+// -----------
+
+impl Grabber {
+    /// Search the window tree under `parent` for a visible window with a
+    /// title containing `title`.
+    fn find_window(
+        &self,
+        parent: Window,
+        title: &str,
+    ) -> Result<Option<Window>> {
+        let tree = self.connection.query_tree(parent)?.reply()?;
+        for &window in &tree.children {
+            let attributes =
+                self.connection.get_window_attributes(window)?.reply()?;
+            if attributes.map_state != MapState::VIEWABLE {
+                continue;
+            }
+            if self.title(window)?.contains(title) {
+                return Ok(Some(window));
+            }
+            if let Some(found) = self.find_window(window, title)? {
+                return Ok(Some(found));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The window's title, preferring the UTF-8 `_NET_WM_NAME`.
+    fn title(&self, window: Window) -> Result<String> {
+        let net_wm_name = self
+            .connection
+            .intern_atom(false, b"_NET_WM_NAME")?
+            .reply()?
+            .atom;
+        for property in [net_wm_name, AtomEnum::WM_NAME.into()] {
+            let reply = self
+                .connection
+                .get_property(false, window, property, AtomEnum::ANY, 0, 1024)?
+                .reply()?;
+            if !reply.value.is_empty() {
+                return Ok(String::from_utf8_lossy(&reply.value).into_owned());
+            }
+        }
+        Ok(String::new())
+    }
+}
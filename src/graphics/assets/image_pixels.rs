@@ -0,0 +1,120 @@
+use {
+    crate::math::Vec2,
+    ::image::{Rgba, RgbaImage},
+    std::sync::Arc,
+};
+
+/// A CPU copy of an image's pixels, for sketches which read colors from an
+/// image instead of drawing it.
+///
+/// Texture coordinates match the GPU: (0, 0) is the top left corner of the
+/// image and (1, 1) is the bottom right. Colors are RGBA in the range 0-1.
+/// Cloning is cheap because the pixels are shared.
+#[derive(Debug, Clone)]
+pub struct ImagePixels {
+    img: Arc<RgbaImage>,
+}
+
+// Public API
+// ----------
+
+impl ImagePixels {
+    /// Panics if the image has no pixels.
+    pub fn new(img: RgbaImage) -> Self {
+        assert!(
+            img.width() > 0 && img.height() > 0,
+            "ImagePixels needs at least one pixel"
+        );
+        Self { img: Arc::new(img) }
+    }
+
+    /// The width in pixels.
+    pub fn width(&self) -> u32 {
+        self.img.width()
+    }
+
+    /// The height in pixels.
+    pub fn height(&self) -> u32 {
+        self.img.height()
+    }
+
+    /// The underlying image.
+    pub fn raw(&self) -> &RgbaImage {
+        &self.img
+    }
+
+    /// The color of the pixel which contains texture coordinates (u, v).
+    /// Coordinates outside of the image are clamped to its edge.
+    pub fn get_pixel(&self, u: f32, v: f32) -> [f32; 4] {
+        let x = (u * self.width() as f32).floor() as i64;
+        let y = (v * self.height() as f32).floor() as i64;
+        self.pixel_clamped(x, y)
+    }
+
+    /// The color at texture coordinates `uv`, blended from the four nearest
+    /// pixels. Coordinates outside of the image are clamped to its edge.
+    pub fn sample(&self, uv: Vec2) -> [f32; 4] {
+        let x = uv.x * self.width() as f32 - 0.5;
+        let y = uv.y * self.height() as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let mut color = [0.0; 4];
+        for (dx, dy, weight) in [
+            (0, 0, (1.0 - tx) * (1.0 - ty)),
+            (1, 0, tx * (1.0 - ty)),
+            (0, 1, (1.0 - tx) * ty),
+            (1, 1, tx * ty),
+        ] {
+            let pixel = self.pixel_clamped(x0 + dx, y0 + dy);
+            for (channel, value) in color.iter_mut().zip(pixel) {
+                *channel += value * weight;
+            }
+        }
+        color
+    }
+
+    /// Sample the image where it covers a world position, assuming it was
+    /// drawn with `G2D::rect(top_left, size, 0.0)`.
+    ///
+    /// Returns None when `pos` is outside of the drawn rectangle.
+    pub fn sample_drawn(
+        &self,
+        pos: Vec2,
+        top_left: Vec2,
+        size: Vec2,
+    ) -> Option<[f32; 4]> {
+        let u = (pos.x - top_left.x) / size.x;
+        let v = (top_left.y - pos.y) / size.y;
+        if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+            return None;
+        }
+        Some(self.sample(Vec2::new(u, v)))
+    }
+
+    /// The perceived brightness at texture coordinates `uv`, in the range
+    /// 0-1. Alpha is ignored.
+    pub fn brightness(&self, uv: Vec2) -> f32 {
+        let [r, g, b, _] = self.sample(uv);
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+}
+
+impl From<RgbaImage> for ImagePixels {
+    fn from(img: RgbaImage) -> Self {
+        Self::new(img)
+    }
+}
+
+// Private API
+// -----------
+
+impl ImagePixels {
+    fn pixel_clamped(&self, x: i64, y: i64) -> [f32; 4] {
+        let x = x.clamp(0, self.width() as i64 - 1) as u32;
+        let y = y.clamp(0, self.height() as i64 - 1) as u32;
+        let Rgba(rgba) = *self.img.get_pixel(x, y);
+        rgba.map(|channel| channel as f32 / 255.0)
+    }
+}
@@ -1,16 +1,30 @@
 mod asset_loader;
+#[cfg(feature = "camera")]
+mod camera;
+#[cfg(feature = "capture")]
+mod capture;
 mod image;
+mod image_pixels;
+mod sampler_settings;
 mod text;
+#[cfg(any(feature = "camera", feature = "capture"))]
+mod video_texture;
 
 use {
-    crate::graphics::vulkan_api::{RenderDevice, Texture2D},
+    crate::graphics::vulkan_api::{RenderDevice, StagingPool, Texture2D},
     ash::vk,
-    std::{collections::HashMap, sync::Arc},
+    std::{collections::HashMap, path::PathBuf, sync::Arc},
 };
 
+#[cfg(feature = "capture")]
+pub use self::capture::CaptureSource;
+#[cfg(any(feature = "camera", feature = "capture"))]
+pub use self::video_texture::VideoTexture;
 pub use self::{
     asset_loader::{AssetLoader, NewAssets, TextureSource},
     image::Image,
+    image_pixels::ImagePixels,
+    sampler_settings::SamplerSettings,
     text::CachedFont,
 };
 
@@ -67,6 +81,7 @@ pub struct Assets {
     cached_fonts: HashMap<String, FontId>,
 
     loader: Option<AssetLoader>,
+    asset_root: PathBuf,
     render_device: Arc<RenderDevice>,
 }
 
@@ -85,11 +100,25 @@ impl Assets {
                 HashMap::default(),
                 0,
                 HashMap::default(),
+                PathBuf::new(),
+                StagingPool::new(render_device.clone()),
             )),
+            asset_root: PathBuf::new(),
             render_device,
         }
     }
 
+    /// Set the directory used to resolve relative asset paths.
+    ///
+    /// Applies to assets loaded after this call. Absolute paths are not
+    /// affected.
+    pub fn set_asset_root(&mut self, asset_root: impl Into<PathBuf>) {
+        self.asset_root = asset_root.into();
+        if let Some(loader) = self.loader.as_mut() {
+            loader.set_asset_root(self.asset_root.clone());
+        }
+    }
+
     pub fn take_asset_loader(&mut self) -> AssetLoader {
         self.loader.take().unwrap()
     }
@@ -128,6 +157,8 @@ impl Assets {
             self.cached_textures.clone(),
             self.fonts.len(),
             self.cached_fonts.clone(),
+            self.asset_root.clone(),
+            new_assets.asset_loader.into_staging_pool(),
         ));
 
         log::trace!("Loaded assets: {:#?}", self.cached_textures);
@@ -0,0 +1,64 @@
+use ash::vk;
+
+/// How an image is filtered when it is drawn smaller or larger than its size.
+///
+/// The mip level options only matter for images loaded with mipmaps.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SamplerSettings {
+    /// Added to the mip level the GPU picks. Negative values keep scaled
+    /// sprites sharper at the cost of shimmering, positive values blur them.
+    pub lod_bias: f32,
+
+    /// The most detailed mip level to sample.
+    pub min_lod: f32,
+
+    /// The least detailed mip level to sample.
+    pub max_lod: f32,
+
+    /// The highest anisotropic filtering ratio, or None to disable it.
+    /// Anisotropy keeps images sharp when they are squashed along one axis.
+    /// Clamped to what the device supports, usually 16, and ignored when
+    /// the device doesn't support anisotropic filtering.
+    pub anisotropy: Option<f32>,
+}
+
+impl Default for SamplerSettings {
+    fn default() -> Self {
+        Self {
+            lod_bias: 0.0,
+            min_lod: 0.0,
+            max_lod: vk::LOD_CLAMP_NONE,
+            anisotropy: None,
+        }
+    }
+}
+
+// Private API
+// -----------
+
+impl SamplerSettings {
+    /// The create info for a sampler with these settings. Anisotropy is
+    /// disabled when `max_anisotropy` is 1.0 or less.
+    pub(crate) fn create_info(
+        &self,
+        max_anisotropy: f32,
+    ) -> vk::SamplerCreateInfo {
+        let anisotropy = self
+            .anisotropy
+            .filter(|_| max_anisotropy > 1.0)
+            .map(|anisotropy| anisotropy.clamp(1.0, max_anisotropy));
+        vk::SamplerCreateInfo {
+            mipmap_mode: vk::SamplerMipmapMode::LINEAR,
+            mag_filter: vk::Filter::LINEAR,
+            min_filter: vk::Filter::LINEAR,
+            address_mode_u: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            address_mode_v: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            mip_lod_bias: self.lod_bias,
+            min_lod: self.min_lod,
+            max_lod: self.max_lod.max(self.min_lod),
+            anisotropy_enable: anisotropy.is_some().into(),
+            max_anisotropy: anisotropy.unwrap_or(1.0),
+            ..Default::default()
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use {
-    crate::{graphics::Image, math::Vec2},
+    crate::{
+        graphics::{Image, PathSegment},
+        math::Vec2,
+    },
     ab_glyph::{
-        Font, FontVec, Glyph, GlyphId, OutlinedGlyph, PxScaleFont, ScaleFont,
+        Font, FontVec, Glyph, GlyphId, OutlineCurve, OutlinedGlyph,
+        PxScaleFont, ScaleFont,
     },
     image::RgbaImage,
     std::collections::HashMap,
@@ -120,6 +124,61 @@ impl CachedFont {
         (glyph_geometry, width, height)
     }
 
+    /// The outlines of `text`'s glyphs, positioned the same way as the
+    /// sprites from `layout_paragraph_geometry`.
+    pub(crate) fn layout_paragraph_outlines(
+        &self,
+        text: impl AsRef<str>,
+    ) -> Vec<PathSegment> {
+        let scale = self.font.scale_factor();
+        let mut segments = vec![];
+        for glyph in Self::layout_paragraph_glyphs(&self.font, text) {
+            let outline = match self.font.font().outline(glyph.id) {
+                Some(outline) => outline,
+                None => continue,
+            };
+            // Font units point up and layout positions point down.
+            let to_world = |point: ab_glyph::Point| {
+                Vec2::new(
+                    glyph.position.x + point.x * scale.horizontal,
+                    point.y * scale.vertical - glyph.position.y,
+                )
+            };
+            let mut current: Option<ab_glyph::Point> = None;
+            for curve in &outline.curves {
+                let (start, end, segment) = match *curve {
+                    OutlineCurve::Line(a, b) => {
+                        (a, b, PathSegment::LineTo(to_world(b)))
+                    }
+                    OutlineCurve::Quad(a, b, c) => {
+                        (a, c, PathSegment::QuadTo(to_world(b), to_world(c)))
+                    }
+                    OutlineCurve::Cubic(a, b, c, d) => (
+                        a,
+                        d,
+                        PathSegment::CubicTo(
+                            to_world(b),
+                            to_world(c),
+                            to_world(d),
+                        ),
+                    ),
+                };
+                if current != Some(start) {
+                    if current.is_some() {
+                        segments.push(PathSegment::Close);
+                    }
+                    segments.push(PathSegment::MoveTo(to_world(start)));
+                }
+                segments.push(segment);
+                current = Some(end);
+            }
+            if current.is_some() {
+                segments.push(PathSegment::Close);
+            }
+        }
+        segments
+    }
+
     fn layout_paragraph_glyphs<F, SF>(
         font: &SF,
         text: impl AsRef<str>,
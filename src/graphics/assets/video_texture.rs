@@ -0,0 +1,81 @@
+use {super::Image, crate::Sim2D, image::RgbaImage};
+
+/// An image which is replaced by live video, such as a webcam opened with
+/// `AssetLoader::open_camera` or the screen captured with
+/// `AssetLoader::open_screen_capture`.
+///
+/// Call `update` once per frame, then draw `image()` like any other image:
+///
+/// ```ignore
+/// fn update(&mut self, sim: &mut Sim2D) {
+///     self.camera.update(sim);
+///     sim.g.image = self.camera.image();
+///     sim.g.rect(top_left, size, 0.0);
+///     // ...
+/// }
+/// ```
+///
+/// Frames are captured on a background thread and only the newest frame is
+/// kept, so a slow sketch skips frames instead of falling behind. Capture
+/// stops when the video texture is dropped.
+pub struct VideoTexture {
+    image: Image,
+    source: Box<dyn FrameSource>,
+}
+
+// Public API
+// ----------
+
+impl VideoTexture {
+    /// The image to draw. It is black until the first frame arrives.
+    pub fn image(&self) -> Image {
+        self.image
+    }
+
+    pub fn width(&self) -> f32 {
+        self.image.width()
+    }
+
+    pub fn height(&self) -> f32 {
+        self.image.height()
+    }
+
+    /// Upload the newest frame, if one arrived since the last update.
+    /// Returns true when the image changed.
+    pub fn update(&mut self, sim: &mut Sim2D) -> bool {
+        match self.source.take_frame() {
+            Some(frame) => {
+                sim.update_image(self.image, frame);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl From<&VideoTexture> for Image {
+    fn from(video: &VideoTexture) -> Self {
+        video.image
+    }
+}
+
+// Private API
+// -----------
+
+/// Produces frames on a background thread.
+pub(super) trait FrameSource: Send {
+    /// Take the newest frame if one arrived since the last call.
+    fn take_frame(&self) -> Option<RgbaImage>;
+}
+
+impl VideoTexture {
+    pub(super) fn new(
+        image: Image,
+        source: impl FrameSource + 'static,
+    ) -> Self {
+        Self {
+            image,
+            source: Box::new(source),
+        }
+    }
+}
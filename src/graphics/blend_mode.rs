@@ -0,0 +1,84 @@
+use ash::vk;
+
+/// How sprites are combined with everything drawn before them.
+///
+/// Set `G2D::blend_mode` before drawing. Sprites which share a blend mode
+/// are still drawn together, so switching modes costs a draw call each
+/// time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    /// Blend by the sprite's alpha.
+    #[default]
+    Alpha,
+
+    /// Add the sprite's color, scaled by its alpha. Good for glows, light,
+    /// and particles.
+    Additive,
+
+    /// Multiply by the sprite's color, for shadows and tinting. Alpha is
+    /// ignored, so the transparent parts of a texture should be white.
+    Multiply,
+
+    /// Replace everything underneath, ignoring alpha.
+    Opaque,
+}
+
+// Private API
+// -----------
+
+impl BlendMode {
+    /// Every blend mode, in the order of `index`.
+    pub(crate) const ALL: [BlendMode; 4] = [
+        BlendMode::Alpha,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+        BlendMode::Opaque,
+    ];
+
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The pipeline blend state which implements this mode. The
+    /// framebuffer's alpha is only replaced by Alpha and Opaque sprites.
+    pub(crate) fn attachment_state(
+        self,
+    ) -> vk::PipelineColorBlendAttachmentState {
+        let (src_color, dst_color, src_alpha, dst_alpha) = match self {
+            BlendMode::Alpha => (
+                vk::BlendFactor::SRC_ALPHA,
+                vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ZERO,
+            ),
+            BlendMode::Additive => (
+                vk::BlendFactor::SRC_ALPHA,
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ZERO,
+                vk::BlendFactor::ONE,
+            ),
+            BlendMode::Multiply => (
+                vk::BlendFactor::ZERO,
+                vk::BlendFactor::SRC_COLOR,
+                vk::BlendFactor::ZERO,
+                vk::BlendFactor::ONE,
+            ),
+            BlendMode::Opaque => (
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ZERO,
+                vk::BlendFactor::ONE,
+                vk::BlendFactor::ZERO,
+            ),
+        };
+        vk::PipelineColorBlendAttachmentState {
+            color_write_mask: vk::ColorComponentFlags::RGBA,
+            blend_enable: vk::TRUE,
+            src_color_blend_factor: src_color,
+            dst_color_blend_factor: dst_color,
+            color_blend_op: vk::BlendOp::ADD,
+            src_alpha_blend_factor: src_alpha,
+            dst_alpha_blend_factor: dst_alpha,
+            alpha_blend_op: vk::BlendOp::ADD,
+        }
+    }
+}
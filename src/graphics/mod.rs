@@ -1,26 +1,61 @@
 mod assets;
+mod blend_mode;
 mod error;
 mod renderer;
+mod vector;
 pub(crate) mod vulkan_api;
 
-use {crate::math::Vec2, std::sync::Arc, vulkan_api::SpriteData};
+use {
+    crate::math::{Rect, Rot2, Vec2},
+    std::{ops::Range, sync::Arc},
+    vulkan_api::SpriteData,
+};
 
-pub(crate) use self::assets::NewAssets;
+#[cfg(feature = "capture")]
+pub use self::assets::CaptureSource;
+#[cfg(any(feature = "camera", feature = "capture"))]
+pub use self::assets::VideoTexture;
+pub(crate) use self::{assets::NewAssets, renderer::BackgroundShader};
 pub use self::{
-    assets::{AssetLoader, Assets, CachedFont, FontId, Image, TextureId},
+    assets::{
+        AssetLoader, Assets, CachedFont, FontId, Image, ImagePixels,
+        SamplerSettings, TextureId,
+    },
+    blend_mode::BlendMode,
     error::GraphicsError,
-    renderer::Renderer,
+    renderer::{RenderTimings, Renderer},
+    vector::{PathSegment, Shape, VectorDrawing},
 };
 
 pub struct G2D {
     cached_fonts: Vec<Arc<CachedFont>>,
     sprites: Vec<SpriteData>,
 
+    /// Consecutive sprites which share a blend mode and clip rect.
+    batches: Vec<SpriteBatch>,
+
+    /// Shapes drawn since `start_recording`, with the index of the first
+    /// sprite drawn for each.
+    recording: Option<Vec<(usize, Shape)>>,
+
     pub font: FontId,
     pub clear_color: [f32; 4],
     pub fill_color: [f32; 4],
     pub image: Image,
     pub line_width: f32,
+    pub blend_mode: BlendMode,
+
+    /// Sprites are only drawn inside this world-space rect. None draws
+    /// everywhere.
+    pub clip: Option<Rect>,
+}
+
+/// A range of sprites drawn with the same blend mode and clip rect.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SpriteBatch {
+    pub blend_mode: BlendMode,
+    pub clip: Option<Rect>,
+    pub sprites: Range<usize>,
 }
 
 impl G2D {
@@ -29,15 +64,19 @@ impl G2D {
             font: FontId::default_font(),
             cached_fonts: assets.fonts().to_vec(),
             sprites: Vec::with_capacity(10_000),
+            batches: vec![],
+            recording: None,
             clear_color: [1.0, 1.0, 1.0, 1.0],
             fill_color: [1.0, 1.0, 1.0, 1.0],
             image: Image::none(),
             line_width: 1.0,
+            blend_mode: BlendMode::Alpha,
+            clip: None,
         }
     }
 
     pub fn rect_centered(&mut self, pos: Vec2, size: Vec2, angle: f32) {
-        self.sprites.push(SpriteData {
+        self.push_rect(SpriteData {
             pos: [pos.x, pos.y],
             size: [size.x, size.y],
             rgba: self.fill_color,
@@ -50,7 +89,7 @@ impl G2D {
     }
 
     pub fn rect(&mut self, top_left: Vec2, size: Vec2, angle: f32) {
-        self.sprites.push(SpriteData {
+        self.push_rect(SpriteData {
             pos: [top_left.x, top_left.y],
             size: [size.x, size.y],
             rgba: self.fill_color,
@@ -68,7 +107,7 @@ impl G2D {
         uv_top_left: Vec2,
         uv_scale: Vec2,
     ) {
-        self.sprites.push(SpriteData {
+        self.push_rect(SpriteData {
             pos: [top_left.x, top_left.y],
             size: [size.x, size.y],
             rgba: self.fill_color,
@@ -83,30 +122,85 @@ impl G2D {
     pub fn line(&mut self, start: Vec2, end: Vec2) {
         let d = end - start;
         let len = d.magnitude();
+        if len <= f32::EPSILON {
+            // There is no direction to orient a zero length line.
+            return;
+        }
         let midpoint = start + 0.5 * d;
         let angle =
             ((d.y / len) / (d.x / len)).atan() + std::f32::consts::FRAC_PI_2;
-        self.rect_centered(midpoint, Vec2::new(self.line_width, len), angle);
+        self.record(Shape::Line {
+            start,
+            end,
+            width: self.line_width,
+            color: self.fill_color,
+        });
+        self.push_sprite(SpriteData {
+            pos: [midpoint.x, midpoint.y],
+            size: [self.line_width, len],
+            rgba: self.fill_color,
+            tex: self.image.texture_id().raw() as f32,
+            angle,
+            uv_scale: [1.0, 1.0],
+            uv_offset: [0.0, 0.0],
+            ..Default::default()
+        });
     }
 
     pub fn text(&mut self, pos: Vec2, text: impl AsRef<str>) {
-        let font = &self.cached_fonts[self.font.raw()];
+        let font = self.cached_fonts[self.font.raw()].clone();
 
+        let text = text.as_ref();
         let (glyph_sprites, _, _) = font.layout_paragraph_geometry(text);
 
-        let original_image = self.image;
-        self.image = font.atlas;
+        if self.recording.is_some() {
+            let segments = font.layout_paragraph_outlines(text);
+            let color = self.fill_color;
+            let mut shape = Shape::Path { segments, color };
+            shape.translate(pos);
+            self.record(shape);
+        }
 
         for sprite in &glyph_sprites {
-            self.rect_uvs(
-                sprite.top_left + pos,
-                sprite.size,
-                sprite.uv_top_left,
-                sprite.uv_size,
-            )
+            let top_left = sprite.top_left + pos;
+            self.push_sprite(SpriteData {
+                pos: [top_left.x, top_left.y],
+                size: [sprite.size.x, sprite.size.y],
+                rgba: self.fill_color,
+                tex: font.atlas.texture_id().raw() as f32,
+                angle: 0.0,
+                center_offset: [0.5, -0.5],
+                uv_offset: sprite.uv_top_left.into(),
+                uv_scale: sprite.uv_size.into(),
+            });
         }
+    }
+
+    /// The width and height of `text` when drawn with the current font.
+    ///
+    /// Text is drawn down and to the right of the position passed to
+    /// `text()`.
+    pub fn text_size(&self, text: impl AsRef<str>) -> Vec2 {
+        let font = &self.cached_fonts[self.font.raw()];
+        let (_, width, height) = font.layout_paragraph_geometry(text);
+        Vec2::new(width, height)
+    }
+
+    /// Keep the shapes drawn from now on so they can be exported as vector
+    /// graphics. Restarts the recording if one is already running.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(vec![]);
+    }
 
-        self.image = original_image;
+    /// Stop recording and take the shapes drawn since `start_recording`.
+    ///
+    /// Returns None when nothing was being recorded.
+    pub fn finish_recording(&mut self) -> Option<VectorDrawing> {
+        let recording = self.recording.take()?;
+        Some(VectorDrawing {
+            clear_color: self.clear_color,
+            shapes: recording.into_iter().map(|(_, shape)| shape).collect(),
+        })
     }
 }
 
@@ -118,7 +212,79 @@ impl G2D {
         &self.sprites
     }
 
-    fn reset(&mut self) {
+    /// The ranges of sprites which are drawn together, in drawing order.
+    pub(crate) fn sprite_batches(&self) -> &[SpriteBatch] {
+        &self.batches
+    }
+
+    pub(crate) fn sprite_count(&self) -> usize {
+        self.sprites.len()
+    }
+
+    /// Move every sprite added since `first` by `offset`.
+    pub(crate) fn offset_sprites_since(&mut self, first: usize, offset: Vec2) {
+        for sprite in &mut self.sprites[first..] {
+            sprite.pos[0] += offset.x;
+            sprite.pos[1] += offset.y;
+        }
+        if let Some(recording) = &mut self.recording {
+            let shapes = recording.iter_mut().rev();
+            for (_, shape) in shapes.take_while(|(index, _)| *index >= first) {
+                shape.translate(offset);
+            }
+        }
+    }
+
+    /// Add a rect's sprite, recording it as a polygon.
+    fn push_rect(&mut self, sprite: SpriteData) {
+        if self.recording.is_some() {
+            let rotation = Rot2::new(sprite.angle);
+            let size = Vec2::from(sprite.size);
+            let offset = Vec2::from(sprite.center_offset);
+            let points = [(-0.5, 0.5), (0.5, 0.5), (0.5, -0.5), (-0.5, -0.5)]
+                .iter()
+                .map(|&(x, y)| {
+                    let corner =
+                        (Vec2::new(x, y) + offset).component_mul(&size);
+                    Vec2::from(sprite.pos) + rotation * corner
+                })
+                .collect();
+            let color = sprite.rgba;
+            self.record(Shape::Polygon { points, color });
+        }
+        self.push_sprite(sprite);
+    }
+
+    /// Add a sprite, starting a new batch when the blend mode or clip rect
+    /// changed.
+    fn push_sprite(&mut self, sprite: SpriteData) {
+        let index = self.sprites.len();
+        match self.batches.last_mut() {
+            Some(batch)
+                if batch.blend_mode == self.blend_mode
+                    && batch.clip == self.clip =>
+            {
+                batch.sprites.end = index + 1;
+            }
+            _ => self.batches.push(SpriteBatch {
+                blend_mode: self.blend_mode,
+                clip: self.clip,
+                sprites: index..index + 1,
+            }),
+        }
+        self.sprites.push(sprite);
+    }
+
+    /// Record a shape drawn with the sprites added next.
+    fn record(&mut self, shape: Shape) {
+        let first_sprite = self.sprites.len();
+        if let Some(recording) = &mut self.recording {
+            recording.push((first_sprite, shape));
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
         self.sprites.clear();
+        self.batches.clear();
     }
 }
@@ -0,0 +1,22 @@
+use {
+    crate::graphics::{
+        vulkan_api::{ShaderInputs, CHANNEL_COUNT},
+        Image,
+    },
+    std::sync::Arc,
+};
+
+/// A fragment shader to draw over the whole window, behind the sprites,
+/// until the sketch's next update.
+#[derive(Debug, Clone)]
+pub(crate) struct BackgroundShader {
+    /// The compiled fragment shader. The pipeline is only rebuilt when this
+    /// points to different code.
+    pub spirv: Arc<[u8]>,
+
+    /// Shadertoy's inputs. The renderer fills in the resolution.
+    pub inputs: ShaderInputs,
+
+    /// The images bound to iChannel0 through iChannel3.
+    pub channels: [Image; CHANNEL_COUNT],
+}
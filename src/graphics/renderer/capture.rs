@@ -0,0 +1,186 @@
+use {
+    crate::graphics::{
+        vulkan_api::{Frame, MappedBuffer, RenderDevice, Swapchain},
+        GraphicsError,
+    },
+    anyhow::Context,
+    ash::vk,
+    image::RgbaImage,
+    std::sync::Arc,
+};
+
+/// A host-visible buffer which swapchain images are copied into so a
+/// rendered frame can be read on the CPU.
+pub(super) struct FrameCapture {
+    buffer: MappedBuffer<u8>,
+    extent: vk::Extent2D,
+    format: vk::Format,
+
+    /// The id of the capture copied into the buffer which hasn't been read
+    /// yet.
+    pending: Option<u64>,
+}
+
+impl FrameCapture {
+    /// Create a capture buffer sized for the swapchain's images.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - the capture must be dropped before the render device
+    pub unsafe fn new(
+        render_device: Arc<RenderDevice>,
+        swapchain: &Swapchain,
+    ) -> Result<Self, GraphicsError> {
+        let extent = swapchain.extent();
+        let size = extent.width as usize * extent.height as usize * 4;
+        let mut buffer = MappedBuffer::new(
+            render_device,
+            size,
+            vk::BufferUsageFlags::TRANSFER_DST,
+        )?;
+        buffer.write(&vec![0; size])?;
+        Ok(Self {
+            buffer,
+            extent,
+            format: swapchain.image_format(),
+            pending: None,
+        })
+    }
+
+    /// True when the capture buffer fits the swapchain's images.
+    pub fn matches(&self, swapchain: &Swapchain) -> bool {
+        self.extent == swapchain.extent()
+            && self.format == swapchain.image_format()
+    }
+
+    /// Record commands which copy the frame's swapchain image into the
+    /// capture buffer. The copy is read with `read` and returned with `id`.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - the frame's render pass must have ended, leaving the swapchain image
+    ///     in the PRESENT_SRC_KHR layout
+    ///   - the buffer must not be read until the frame's commands complete
+    ///   - any earlier copy must already have been read
+    pub unsafe fn record_copy(
+        &mut self,
+        render_device: &RenderDevice,
+        frame: &Frame,
+        swapchain: &Swapchain,
+        id: u64,
+    ) {
+        debug_assert!(self.pending.is_none());
+        self.pending = Some(id);
+
+        let image = swapchain.images()[frame.swapchain_image_index()];
+        let subresource_range = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        let device = render_device.device();
+        let command_buffer = frame.command_buffer();
+
+        let to_transfer = vk::ImageMemoryBarrier2 {
+            src_stage_mask: vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            src_access_mask: vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+            dst_stage_mask: vk::PipelineStageFlags2::TRANSFER,
+            dst_access_mask: vk::AccessFlags2::TRANSFER_READ,
+            old_layout: vk::ImageLayout::PRESENT_SRC_KHR,
+            new_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            image,
+            subresource_range,
+            ..Default::default()
+        };
+        device.cmd_pipeline_barrier2(
+            command_buffer,
+            &vk::DependencyInfo {
+                image_memory_barrier_count: 1,
+                p_image_memory_barriers: &to_transfer,
+                ..Default::default()
+            },
+        );
+
+        let region = vk::BufferImageCopy {
+            buffer_offset: 0,
+            buffer_row_length: 0,
+            buffer_image_height: 0,
+            image_subresource: vk::ImageSubresourceLayers {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count: 1,
+            },
+            image_offset: vk::Offset3D::default(),
+            image_extent: vk::Extent3D {
+                width: self.extent.width,
+                height: self.extent.height,
+                depth: 1,
+            },
+        };
+        device.cmd_copy_image_to_buffer(
+            command_buffer,
+            image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            self.buffer.raw(),
+            &[region],
+        );
+
+        // Presentation is synchronized with a semaphore, so the dst stage
+        // and access don't matter here.
+        let to_present = vk::ImageMemoryBarrier2 {
+            src_stage_mask: vk::PipelineStageFlags2::TRANSFER,
+            src_access_mask: vk::AccessFlags2::TRANSFER_READ,
+            dst_stage_mask: vk::PipelineStageFlags2::BOTTOM_OF_PIPE,
+            dst_access_mask: vk::AccessFlags2::NONE,
+            old_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            new_layout: vk::ImageLayout::PRESENT_SRC_KHR,
+            image,
+            subresource_range,
+            ..Default::default()
+        };
+        device.cmd_pipeline_barrier2(
+            command_buffer,
+            &vk::DependencyInfo {
+                image_memory_barrier_count: 1,
+                p_image_memory_barriers: &to_present,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Read the pixels copied by `record_copy` as an RGBA image, along with
+    /// the capture's id. Returns None when nothing was copied since the last
+    /// read.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - the commands recorded by `record_copy` must have completed
+    pub unsafe fn read(
+        &mut self,
+    ) -> Result<Option<(u64, RgbaImage)>, GraphicsError> {
+        let id = match self.pending.take() {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        let mut pixels = self.buffer.read().to_vec();
+        let is_bgra = matches!(
+            self.format,
+            vk::Format::B8G8R8A8_SRGB | vk::Format::B8G8R8A8_UNORM
+        );
+        if is_bgra {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        let image =
+            RgbaImage::from_raw(self.extent.width, self.extent.height, pixels)
+                .context("Captured frame has the wrong number of pixels")?;
+        Ok(Some((id, image)))
+    }
+}
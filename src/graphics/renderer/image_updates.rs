@@ -0,0 +1,219 @@
+use {
+    crate::graphics::{
+        vulkan_api::{Frame, MappedBuffer, RenderDevice, Texture2D},
+        GraphicsError, Image,
+    },
+    ash::vk,
+    image::RgbaImage,
+    std::sync::Arc,
+};
+
+/// Copies new pixels into textures which have already been loaded, for
+/// images which change every frame.
+pub(super) struct ImageUpdates {
+    /// Updates waiting for the next frame.
+    pending: Vec<(Image, RgbaImage)>,
+
+    /// One staging buffer per frame in flight because a previous frame's
+    /// copy may still be running when the next frame is recorded.
+    staging: Vec<Option<MappedBuffer<u8>>>,
+    render_device: Arc<RenderDevice>,
+}
+
+impl ImageUpdates {
+    pub fn new(render_device: Arc<RenderDevice>, frame_count: usize) -> Self {
+        Self {
+            pending: vec![],
+            staging: (0..frame_count).map(|_| None).collect(),
+            render_device,
+        }
+    }
+
+    /// Queue updates for the next frame. A newer update for the same image
+    /// replaces an older one.
+    pub fn push(&mut self, updates: Vec<(Image, RgbaImage)>) {
+        for (image, pixels) in updates {
+            self.pending.retain(|(pending, _)| {
+                pending.texture_id() != image.texture_id()
+            });
+            self.pending.push((image, pixels));
+        }
+    }
+
+    /// Record commands which copy every pending update into its texture.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - the frame's fence must have been waited on, so its staging buffer is
+    ///     not in use
+    ///   - the commands must be recorded outside of a render pass and after the
+    ///     textures have been acquired by the graphics queue
+    pub unsafe fn record(
+        &mut self,
+        frame: &Frame,
+        textures: &[Arc<Texture2D>],
+    ) -> Result<(), GraphicsError> {
+        let updates: Vec<(Image, RgbaImage)> = self
+            .pending
+            .drain(..)
+            .filter(|(image, pixels)| is_valid(image, pixels, textures))
+            .collect();
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        let mut data = Vec::with_capacity(
+            updates
+                .iter()
+                .map(|(_, pixels)| pixels.as_raw().len())
+                .sum(),
+        );
+        let mut regions = vec![];
+        for (_, pixels) in &updates {
+            regions.push(vk::BufferImageCopy {
+                buffer_offset: data.len() as u64,
+                buffer_row_length: 0,
+                buffer_image_height: 0,
+                image_subresource: vk::ImageSubresourceLayers {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    mip_level: 0,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                },
+                image_offset: vk::Offset3D::default(),
+                image_extent: vk::Extent3D {
+                    width: pixels.width(),
+                    height: pixels.height(),
+                    depth: 1,
+                },
+            });
+            data.extend_from_slice(pixels.as_raw());
+        }
+
+        let staging = &mut self.staging[frame.frame_index()];
+        if staging.is_none() {
+            *staging = Some(MappedBuffer::new(
+                self.render_device.clone(),
+                data.len(),
+                vk::BufferUsageFlags::TRANSFER_SRC,
+            )?);
+        }
+        let staging = staging.as_mut().unwrap();
+        staging.write(&data)?;
+        let staging = staging.raw();
+
+        let images: Vec<vk::Image> = updates
+            .iter()
+            .map(|(image, _)| {
+                textures[image.texture_id().raw() as usize].image.raw()
+            })
+            .collect();
+
+        // Earlier frames only read the textures, so waiting for their
+        // fragment shaders is enough.
+        self.barrier(
+            frame,
+            &images,
+            (
+                vk::PipelineStageFlags2::FRAGMENT_SHADER,
+                vk::AccessFlags2::NONE,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            ),
+            (
+                vk::PipelineStageFlags2::TRANSFER,
+                vk::AccessFlags2::TRANSFER_WRITE,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            ),
+        );
+        for (image, region) in images.iter().zip(&regions) {
+            self.render_device.device().cmd_copy_buffer_to_image(
+                frame.command_buffer(),
+                staging,
+                *image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                std::slice::from_ref(region),
+            );
+        }
+        self.barrier(
+            frame,
+            &images,
+            (
+                vk::PipelineStageFlags2::TRANSFER,
+                vk::AccessFlags2::TRANSFER_WRITE,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            ),
+            (
+                vk::PipelineStageFlags2::FRAGMENT_SHADER,
+                vk::AccessFlags2::SHADER_SAMPLED_READ,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            ),
+        );
+        Ok(())
+    }
+}
+
+impl ImageUpdates {
+    unsafe fn barrier(
+        &self,
+        frame: &Frame,
+        images: &[vk::Image],
+        src: (vk::PipelineStageFlags2, vk::AccessFlags2, vk::ImageLayout),
+        dst: (vk::PipelineStageFlags2, vk::AccessFlags2, vk::ImageLayout),
+    ) {
+        let barriers: Vec<vk::ImageMemoryBarrier2> = images
+            .iter()
+            .map(|&image| vk::ImageMemoryBarrier2 {
+                src_stage_mask: src.0,
+                src_access_mask: src.1,
+                dst_stage_mask: dst.0,
+                dst_access_mask: dst.1,
+                old_layout: src.2,
+                new_layout: dst.2,
+                image,
+                subresource_range: vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                },
+                ..Default::default()
+            })
+            .collect();
+        self.render_device.device().cmd_pipeline_barrier2(
+            frame.command_buffer(),
+            &vk::DependencyInfo {
+                image_memory_barrier_count: barriers.len() as u32,
+                p_image_memory_barriers: barriers.as_ptr(),
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/// True when the image refers to a loaded texture and the pixels are the
+/// same size.
+fn is_valid(
+    image: &Image,
+    pixels: &RgbaImage,
+    textures: &[Arc<Texture2D>],
+) -> bool {
+    let index = image.texture_id().raw();
+    if index < 0 || index as usize >= textures.len() {
+        log::warn!("Unable to update {:?}, it isn't a loaded image", image);
+        return false;
+    }
+    if pixels.width() as f32 != image.width()
+        || pixels.height() as f32 != image.height()
+    {
+        log::warn!(
+            "Unable to update {:?} with {}x{} pixels, the size must match",
+            image,
+            pixels.width(),
+            pixels.height()
+        );
+        return false;
+    }
+    true
+}
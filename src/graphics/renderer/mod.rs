@@ -1,18 +1,45 @@
+mod background_shader;
+mod capture;
+mod image_updates;
+
 use {
+    self::{capture::FrameCapture, image_updates::ImageUpdates},
     crate::{
         graphics::{
             vulkan_api::{
-                BindlessSprites, ColorPass, FrameStatus, FramesInFlight,
-                RenderDevice, Texture2D,
+                BindlessSprites, ColorPass, Frame, FrameStatus, FramesInFlight,
+                FullscreenShader, RenderDevice, Texture2D,
             },
-            GraphicsError, G2D,
+            GraphicsError, Image, G2D,
         },
         math::Mat4,
     },
     ash::vk,
-    std::sync::Arc,
+    image::RgbaImage,
+    std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    },
 };
 
+pub(crate) use self::background_shader::BackgroundShader;
+
+/// How long each stage of the most recent frame took on the CPU.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct RenderTimings {
+    /// Waiting for a swapchain image and the frame's fence.
+    pub acquire: Duration,
+
+    /// Recording barriers and copying sprite data to the GPU.
+    pub upload: Duration,
+
+    /// Recording draw commands.
+    pub draw: Duration,
+
+    /// Submitting the frame and queueing it for presentation.
+    pub present: Duration,
+}
+
 /// The Sim2D Rendering backend.
 pub struct Renderer {
     projection: Mat4,
@@ -21,6 +48,29 @@ pub struct Renderer {
     bindless_sprites: BindlessSprites,
     image_acquire_barriers: Vec<vk::ImageMemoryBarrier2>,
     textures: Vec<Arc<Texture2D>>,
+    timings: RenderTimings,
+
+    /// Time spent in `wait_for_next_frame` since the last frame.
+    waited: Duration,
+
+    /// A readback buffer for each frame in flight. Each capture is read once
+    /// its frame's fence has signaled, so capturing doesn't stall the GPU.
+    captures: Vec<Option<FrameCapture>>,
+
+    /// The id for a capture of the next rendered frame, if one was
+    /// requested.
+    capture_requested: Option<u64>,
+    next_capture_id: u64,
+    captured: Option<(u64, RgbaImage)>,
+    image_updates: ImageUpdates,
+    background_request: Option<BackgroundShader>,
+
+    /// The pipeline for the most recent background shader and the code it
+    /// was built from.
+    background: Option<(Arc<[u8]>, FullscreenShader)>,
+
+    /// Shader code which failed to build, so it isn't retried every frame.
+    rejected_background: Option<Arc<[u8]>>,
     render_device: Arc<RenderDevice>,
 }
 
@@ -28,11 +78,19 @@ impl Renderer {
     pub fn new(
         render_device: Arc<RenderDevice>,
         framebuffer_size: (i32, i32),
+        transparent: bool,
+        vsync: bool,
         textures: &[Arc<Texture2D>],
         image_acquire_barriers: &[vk::ImageMemoryBarrier2],
     ) -> Result<Self, GraphicsError> {
         let frames_in_flight = unsafe {
-            FramesInFlight::new(render_device.clone(), framebuffer_size, 3)?
+            FramesInFlight::new(
+                render_device.clone(),
+                framebuffer_size,
+                transparent,
+                vsync,
+                3,
+            )?
         };
 
         let color_pass = unsafe {
@@ -40,6 +98,10 @@ impl Renderer {
         };
 
         let projection = Self::fullscreen_ortho_projection(framebuffer_size);
+        let image_updates = ImageUpdates::new(
+            render_device.clone(),
+            frames_in_flight.frame_count(),
+        );
 
         let mut bindless_sprites = unsafe {
             BindlessSprites::new(
@@ -51,6 +113,9 @@ impl Renderer {
         };
         bindless_sprites.set_projection(&projection);
 
+        let captures =
+            (0..frames_in_flight.frame_count()).map(|_| None).collect();
+
         Ok(Self {
             projection,
             frames_in_flight,
@@ -60,6 +125,16 @@ impl Renderer {
 
             image_acquire_barriers: image_acquire_barriers.to_owned(),
             textures: textures.to_owned(),
+            timings: RenderTimings::default(),
+            waited: Duration::ZERO,
+            captures,
+            capture_requested: None,
+            next_capture_id: 0,
+            captured: None,
+            image_updates,
+            background_request: None,
+            background: None,
+            rejected_background: None,
 
             render_device,
         })
@@ -70,37 +145,76 @@ impl Renderer {
         textures: &[Arc<Texture2D>],
         image_acquire_barriers: &[vk::ImageMemoryBarrier2],
     ) -> Result<(), GraphicsError> {
+        // New textures are usually appended to the old ones, in which case
+        // only their descriptors need to be written.
+        let extends_bound_textures = textures.len() >= self.textures.len()
+            && textures.len() <= self.bindless_sprites.texture_capacity()
+            && self
+                .textures
+                .iter()
+                .zip(textures)
+                .all(|(old, new)| Arc::ptr_eq(old, new));
         self.textures = textures.to_owned();
 
-        self.bindless_sprites = unsafe {
+        unsafe {
             self.frames_in_flight.wait_for_all_frames_to_complete()?;
-            BindlessSprites::new(
-                self.render_device.clone(),
-                self.color_pass.render_pass(),
-                &self.frames_in_flight,
-                &self.textures,
-            )?
-        };
-        self.bindless_sprites.set_projection(&self.projection);
+            if extends_bound_textures {
+                self.bindless_sprites.bind_new_textures(&self.textures)?;
+            } else {
+                self.bindless_sprites = BindlessSprites::new(
+                    self.render_device.clone(),
+                    self.color_pass.render_pass(),
+                    &self.frames_in_flight,
+                    &self.textures,
+                )?;
+                self.bindless_sprites.set_projection(&self.projection);
+            }
+        }
 
         self.image_acquire_barriers
             .extend_from_slice(image_acquire_barriers);
         Ok(())
     }
 
+    /// Block until the GPU has room for another frame.
+    ///
+    /// Call this before polling input. Otherwise the wait happens in
+    /// `render`, after the input was read, which adds up to a frame of
+    /// latency when presentation waits for vsync.
+    pub fn wait_for_next_frame(&mut self) -> Result<(), GraphicsError> {
+        let start = Instant::now();
+        self.frames_in_flight.wait_for_next_frame()?;
+        self.waited += start.elapsed();
+        Ok(())
+    }
+
     pub fn render(
         &mut self,
         framebuffer_size: (i32, i32),
-        g2d: &mut G2D,
+        g2d: &G2D,
     ) -> Result<(), GraphicsError> {
+        self.timings = RenderTimings::default();
+        if framebuffer_size.0 <= 0 || framebuffer_size.1 <= 0 {
+            // There is nothing to draw and a zero-sized swapchain can't be
+            // created.
+            return Ok(());
+        }
+
+        self.prepare_background()?;
+
+        let start = Instant::now();
         let frame = match self.frames_in_flight.acquire_frame()? {
             FrameStatus::FrameAcquired(frame) => frame,
             FrameStatus::SwapchainNeedsRebuild => {
                 return self.rebuild_swapchain(framebuffer_size);
             }
         };
+        let acquired = Instant::now();
+        self.timings.acquire =
+            acquired - start + std::mem::take(&mut self.waited);
 
-        unsafe {
+        let capture = unsafe { self.begin_capture(&frame) };
+        let uploaded = unsafe {
             if !self.image_acquire_barriers.is_empty() {
                 let dependency_info = vk::DependencyInfo {
                     dependency_flags: vk::DependencyFlags::empty(),
@@ -119,25 +233,97 @@ impl Renderer {
                 );
                 self.image_acquire_barriers.clear();
             }
-
-            self.color_pass
-                .begin_render_pass_inline(&frame, g2d.clear_color);
+            self.image_updates.record(&frame, &self.textures)?;
 
             self.bindless_sprites
                 .write_sprites_for_frame(&frame, g2d.get_sprites())?;
-            g2d.reset();
+            let uploaded = Instant::now();
+
+            self.color_pass
+                .begin_render_pass_inline(&frame, g2d.clear_color);
+            self.draw_background(&frame);
 
             self.bindless_sprites.draw_vertices(
                 &frame,
                 self.frames_in_flight.swapchain().extent(),
+                g2d.sprite_batches(),
             )?;
 
             self.render_device
                 .device()
                 .cmd_end_render_pass(frame.command_buffer());
+
+            if let Some(id) = capture {
+                self.captures[frame.frame_index()]
+                    .as_mut()
+                    .unwrap()
+                    .record_copy(
+                        &self.render_device,
+                        &frame,
+                        self.frames_in_flight.swapchain(),
+                        id,
+                    );
+            }
+            uploaded
+        };
+        let drawn = Instant::now();
+        self.timings.upload = uploaded - acquired;
+        self.timings.draw = drawn - uploaded;
+
+        self.frames_in_flight.present_frame(frame)?;
+        self.timings.present = drawn.elapsed();
+        Ok(())
+    }
+
+    /// Copy the next rendered frame so it can be read with `take_capture`
+    /// once the GPU has finished drawing it, usually a few frames later.
+    ///
+    /// Returns the id which `take_capture` returns with the image. Requests
+    /// made before the next frame is rendered share an id. Returns None if
+    /// the window's surface doesn't allow its images to be copied.
+    pub fn request_capture(&mut self) -> Option<u64> {
+        if !self.frames_in_flight.swapchain().supports_readback() {
+            log::warn!("The window's surface does not support frame capture");
+            return None;
+        }
+        if self.capture_requested.is_none() {
+            self.capture_requested = Some(self.next_capture_id);
+            self.next_capture_id += 1;
+        }
+        self.capture_requested
+    }
+
+    /// Replace the pixels of loaded images when the next frame is drawn.
+    pub fn update_images(&mut self, updates: Vec<(Image, RgbaImage)>) {
+        self.image_updates.push(updates);
+    }
+
+    /// Draw a fragment shader behind the sprites, or nothing when `None`.
+    /// The shader is drawn every frame until this is called again.
+    pub(crate) fn set_background_shader(
+        &mut self,
+        background: Option<BackgroundShader>,
+    ) {
+        self.background_request = background;
+    }
+
+    /// Take the most recently captured frame and its id, if any. Captures
+    /// finish in the order they were requested.
+    pub fn take_capture(&mut self) -> Option<(u64, RgbaImage)> {
+        self.captured.take()
+    }
+
+    /// Block until the GPU has finished all submitted work.
+    pub fn wait_idle(&self) -> Result<(), GraphicsError> {
+        unsafe {
+            self.render_device.device().device_wait_idle()?;
         }
+        Ok(())
+    }
 
-        self.frames_in_flight.present_frame(frame)
+    /// Get the CPU timings for each stage of the most recent frame.
+    pub fn last_frame_timings(&self) -> RenderTimings {
+        self.timings
     }
 
     pub fn rebuild_swapchain(
@@ -160,15 +346,138 @@ impl Renderer {
                 &self.textures,
             )?;
             self.bindless_sprites.set_projection(&self.projection);
+
+            // The pipeline was built for the old render pass.
+            self.background = None;
+            self.rejected_background = None;
+        };
+        Ok(())
+    }
+
+    /// Build the pipeline for the requested background shader if it isn't
+    /// built already.
+    fn prepare_background(&mut self) -> Result<(), GraphicsError> {
+        let request = match &self.background_request {
+            Some(request) => request,
+            None => return Ok(()),
+        };
+        let is_built = |spirv: Option<&Arc<[u8]>>| {
+            spirv.is_some_and(|spirv| Arc::ptr_eq(spirv, &request.spirv))
         };
+        if is_built(self.background.as_ref().map(|(spirv, _)| spirv))
+            || is_built(self.rejected_background.as_ref())
+        {
+            return Ok(());
+        }
+
+        // Keep drawing the previous pipeline if the new one can't be built,
+        // so a bad shader edit doesn't stop the sketch.
+        unsafe {
+            self.frames_in_flight.wait_for_all_frames_to_complete()?;
+            let result = FullscreenShader::new(
+                self.render_device.clone(),
+                self.color_pass.render_pass(),
+                &self.frames_in_flight,
+                &request.spirv,
+            );
+            match result {
+                Ok(shader) => {
+                    self.background = Some((request.spirv.clone(), shader));
+                    self.rejected_background = None;
+                }
+                Err(error) => {
+                    log::error!(
+                        "Unable to build the fragment shader pipeline!\n{}",
+                        error
+                    );
+                    self.rejected_background = Some(request.spirv.clone());
+                }
+            }
+        }
         Ok(())
     }
 
+    /// Record the background shader's draw, if one was requested.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - the render pass must already be started
+    unsafe fn draw_background(&self, frame: &Frame) {
+        let (request, shader) =
+            match (&self.background_request, &self.background) {
+                (Some(request), Some((_, shader))) => (request, shader),
+                _ => return,
+            };
+        if self.textures.is_empty() {
+            return;
+        }
+
+        let extent = self.frames_in_flight.swapchain().extent();
+        let mut inputs = request.inputs;
+        inputs.resolution = [extent.width as f32, extent.height as f32, 1.0];
+
+        // Images which aren't loaded fall back to the first texture rather
+        // than leaving the binding empty.
+        let channels = request.channels.map(|image| {
+            let index = image.texture_id().raw();
+            if index < 0 || index as usize >= self.textures.len() {
+                &*self.textures[0]
+            } else {
+                &*self.textures[index as usize]
+            }
+        });
+        shader.draw(frame, extent, &inputs, channels);
+    }
+
+    /// Read the capture from the last time this frame was drawn, then
+    /// prepare the frame's capture buffer if a capture was requested.
+    ///
+    /// Returns the id of the capture to record for this frame. Errors are
+    /// logged and skip the capture, so the frame is still submitted.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - the frame's previous commands must have completed, which
+    ///     `acquire_frame` waits for
+    unsafe fn begin_capture(&mut self, frame: &Frame) -> Option<u64> {
+        let slot = &mut self.captures[frame.frame_index()];
+        if let Some(capture) = slot {
+            match capture.read() {
+                Ok(Some(captured)) => self.captured = Some(captured),
+                Ok(None) => (),
+                Err(error) => {
+                    log::error!("Unable to read a captured frame!\n{}", error)
+                }
+            }
+        }
+
+        let id = self.capture_requested.take()?;
+        let swapchain = self.frames_in_flight.swapchain();
+        let is_stale = slot
+            .as_ref()
+            .map(|capture| !capture.matches(swapchain))
+            .unwrap_or(true);
+        if is_stale {
+            match FrameCapture::new(self.render_device.clone(), swapchain) {
+                Ok(capture) => *slot = Some(capture),
+                Err(error) => {
+                    *slot = None;
+                    log::error!("Unable to capture the frame!\n{}", error);
+                    return None;
+                }
+            }
+        }
+        Some(id)
+    }
+
     fn fullscreen_ortho_projection(framebuffer_size: (i32, i32)) -> Mat4 {
-        let half_w = framebuffer_size.0 as f32 / 2.0;
-        let half_h = framebuffer_size.1 as f32 / 2.0;
-        crate::math::ortho_projection(
-            -half_w, half_w, -half_h, half_h, 0.0, 1.0,
+        crate::math::projection::symmetric_ortho(
+            framebuffer_size.0 as f32,
+            framebuffer_size.1 as f32,
+            0.0,
+            1.0,
         )
     }
 }
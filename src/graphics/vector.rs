@@ -0,0 +1,477 @@
+use {
+    crate::math::{Rect, Vec2},
+    anyhow::{bail, Context, Result},
+    std::{fmt::Write, path::Path},
+};
+
+/// The shapes drawn with G2D during a recording.
+///
+/// Start a recording with `G2D::start_recording` and get the drawing with
+/// `G2D::finish_recording`. Rects, lines, and text are kept as vector
+/// shapes, with text converted to glyph outlines, so the frame can be
+/// exported for plotters or print at any resolution:
+///
+/// ```ignore
+/// // in update
+/// sim.g.start_recording();
+/// self.draw(sim);
+/// if let Some(drawing) = sim.g.finish_recording() {
+///     let area = Rect::from_center(
+///         Vec2::zeros(),
+///         Vec2::new(sim.w.width(), sim.w.height()),
+///     );
+///     drawing.save("frame.svg", area)?;
+/// }
+/// ```
+///
+/// Textures aren't exported. Textured rects are exported as a quad filled
+/// with their color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorDrawing {
+    pub clear_color: [f32; 4],
+    pub shapes: Vec<Shape>,
+}
+
+/// A recorded shape in world coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// A filled polygon, such as a rect.
+    Polygon { points: Vec<Vec2>, color: [f32; 4] },
+
+    /// A stroked line with square ends.
+    Line {
+        start: Vec2,
+        end: Vec2,
+        width: f32,
+        color: [f32; 4],
+    },
+
+    /// A filled path with the nonzero fill rule, such as text outlines.
+    Path {
+        segments: Vec<PathSegment>,
+        color: [f32; 4],
+    },
+}
+
+/// One step of a `Shape::Path`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PathSegment {
+    /// Start a new contour.
+    MoveTo(Vec2),
+    LineTo(Vec2),
+
+    /// A quadratic curve with one control point.
+    QuadTo(Vec2, Vec2),
+
+    /// A cubic curve with two control points.
+    CubicTo(Vec2, Vec2, Vec2),
+
+    /// Close the current contour.
+    Close,
+}
+
+// Public API
+// ----------
+
+impl VectorDrawing {
+    /// Write the drawing as SVG or PDF, picked by the file extension.
+    ///
+    /// # Params
+    ///
+    /// * `area` - the region of world space to export. It becomes the page with
+    ///   one unit per pixel or point.
+    pub fn save(&self, path: impl AsRef<Path>, area: Rect) -> Result<()> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        let contents = match extension.as_deref() {
+            Some("svg") => self.to_svg(area).into_bytes(),
+            Some("pdf") => self.to_pdf(area),
+            _ => bail!("Unsupported vector format for {:?}", path),
+        };
+        std::fs::write(path, contents)
+            .with_context(|| format!("Unable to write {:?}", path))
+    }
+
+    /// The drawing as an SVG document.
+    pub fn to_svg(&self, area: Rect) -> String {
+        // SVG's y axis points down.
+        let point = |p: Vec2| {
+            format!("{},{}", num(p.x - area.min.x), num(area.max.y - p.y))
+        };
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" \
+             height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = num(area.width()),
+            h = num(area.height()),
+        );
+        let _ = writeln!(
+            svg,
+            "<rect width=\"100%\" height=\"100%\" {}/>",
+            svg_paint("fill", self.clear_color),
+        );
+        for shape in &self.shapes {
+            let _ = match shape {
+                Shape::Polygon { points, color } => writeln!(
+                    svg,
+                    "<polygon points=\"{}\" {}/>",
+                    points
+                        .iter()
+                        .map(|&p| point(p))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    svg_paint("fill", *color),
+                ),
+                Shape::Line {
+                    start,
+                    end,
+                    width,
+                    color,
+                } => {
+                    let [x1, y1] = split(point(*start));
+                    let [x2, y2] = split(point(*end));
+                    writeln!(
+                        svg,
+                        "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" \
+                         stroke-width=\"{}\" stroke-linecap=\"square\" {}/>",
+                        x1,
+                        y1,
+                        x2,
+                        y2,
+                        num(*width),
+                        svg_paint("stroke", *color),
+                    )
+                }
+                Shape::Path { segments, color } => {
+                    let mut d = vec![];
+                    for segment in segments {
+                        d.push(match *segment {
+                            PathSegment::MoveTo(p) => format!("M{}", point(p)),
+                            PathSegment::LineTo(p) => format!("L{}", point(p)),
+                            PathSegment::QuadTo(c, p) => {
+                                format!("Q{} {}", point(c), point(p))
+                            }
+                            PathSegment::CubicTo(c1, c2, p) => format!(
+                                "C{} {} {}",
+                                point(c1),
+                                point(c2),
+                                point(p)
+                            ),
+                            PathSegment::Close => "Z".to_owned(),
+                        });
+                    }
+                    writeln!(
+                        svg,
+                        "<path d=\"{}\" {}/>",
+                        d.join(" "),
+                        svg_paint("fill", *color),
+                    )
+                }
+            };
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// The drawing as a single page PDF document.
+    pub fn to_pdf(&self, area: Rect) -> Vec<u8> {
+        // PDF's y axis points up, like world space.
+        let point = |p: Vec2| {
+            format!("{} {}", num(p.x - area.min.x), num(p.y - area.min.y))
+        };
+
+        // Transparency needs a graphics state for each distinct alpha.
+        let mut alphas: Vec<String> = vec![];
+        let mut graphics_state = |alpha: f32| {
+            let alpha = num(alpha.clamp(0.0, 1.0));
+            let index = match alphas.iter().position(|a| *a == alpha) {
+                Some(index) => index,
+                None => {
+                    alphas.push(alpha);
+                    alphas.len() - 1
+                }
+            };
+            format!("/GS{} gs", index)
+        };
+
+        let mut content = String::new();
+        let _ = writeln!(
+            content,
+            "q {} {} rg 0 0 {} {} re f Q",
+            graphics_state(self.clear_color[3]),
+            pdf_rgb(self.clear_color),
+            num(area.width()),
+            num(area.height()),
+        );
+        for shape in &self.shapes {
+            let _ = match shape {
+                Shape::Polygon { points, color } => {
+                    let mut ops = vec![];
+                    for (i, &p) in points.iter().enumerate() {
+                        let op = if i == 0 { "m" } else { "l" };
+                        ops.push(format!("{} {}", point(p), op));
+                    }
+                    writeln!(
+                        content,
+                        "q {} {} rg {} h f Q",
+                        graphics_state(color[3]),
+                        pdf_rgb(*color),
+                        ops.join(" "),
+                    )
+                }
+                Shape::Line {
+                    start,
+                    end,
+                    width,
+                    color,
+                } => writeln!(
+                    content,
+                    "q {} {} RG {} w 2 J {} m {} l S Q",
+                    graphics_state(color[3]),
+                    pdf_rgb(*color),
+                    num(*width),
+                    point(*start),
+                    point(*end),
+                ),
+                Shape::Path { segments, color } => {
+                    let mut ops = vec![];
+                    let mut current = Vec2::zeros();
+                    let mut contour_start = Vec2::zeros();
+                    for segment in segments {
+                        match *segment {
+                            PathSegment::MoveTo(p) => {
+                                ops.push(format!("{} m", point(p)));
+                                contour_start = p;
+                                current = p;
+                            }
+                            PathSegment::LineTo(p) => {
+                                ops.push(format!("{} l", point(p)));
+                                current = p;
+                            }
+                            PathSegment::QuadTo(c, p) => {
+                                // PDF only has cubic curves, which can
+                                // represent any quadratic exactly.
+                                let c1 = current + (c - current) * 2.0 / 3.0;
+                                let c2 = p + (c - p) * 2.0 / 3.0;
+                                ops.push(format!(
+                                    "{} {} {} c",
+                                    point(c1),
+                                    point(c2),
+                                    point(p)
+                                ));
+                                current = p;
+                            }
+                            PathSegment::CubicTo(c1, c2, p) => {
+                                ops.push(format!(
+                                    "{} {} {} c",
+                                    point(c1),
+                                    point(c2),
+                                    point(p)
+                                ));
+                                current = p;
+                            }
+                            PathSegment::Close => {
+                                ops.push("h".to_owned());
+                                current = contour_start;
+                            }
+                        }
+                    }
+                    writeln!(
+                        content,
+                        "q {} {} rg {} f Q",
+                        graphics_state(color[3]),
+                        pdf_rgb(*color),
+                        ops.join(" "),
+                    )
+                }
+            };
+        }
+
+        let ext_g_states = alphas
+            .iter()
+            .enumerate()
+            .map(|(i, _)| format!("/GS{} {} 0 R", i, i + 5))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_owned(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_owned(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Contents 4 0 R /Resources << /ExtGState << {} >> >> >>",
+                num(area.width()),
+                num(area.height()),
+                ext_g_states,
+            ),
+            format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.len(),
+                content
+            ),
+        ];
+        for alpha in &alphas {
+            objects.push(format!(
+                "<< /Type /ExtGState /ca {} /CA {} >>",
+                alpha, alpha
+            ));
+        }
+
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = vec![];
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", i + 1, object);
+        }
+        let xref = pdf.len();
+        let _ =
+            write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            pdf,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        );
+        pdf.into_bytes()
+    }
+}
+
+// Private API
+// -----------
+
+impl Shape {
+    /// Move the shape by `offset`.
+    pub(crate) fn translate(&mut self, offset: Vec2) {
+        match self {
+            Shape::Polygon { points, .. } => {
+                for point in points {
+                    *point += offset;
+                }
+            }
+            Shape::Line { start, end, .. } => {
+                *start += offset;
+                *end += offset;
+            }
+            Shape::Path { segments, .. } => {
+                for segment in segments {
+                    match segment {
+                        PathSegment::MoveTo(p) | PathSegment::LineTo(p) => {
+                            *p += offset;
+                        }
+                        PathSegment::QuadTo(c, p) => {
+                            *c += offset;
+                            *p += offset;
+                        }
+                        PathSegment::CubicTo(c1, c2, p) => {
+                            *c1 += offset;
+                            *c2 += offset;
+                            *p += offset;
+                        }
+                        PathSegment::Close => (),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Format a coordinate without trailing zeros.
+fn num(value: f32) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_owned()
+    } else {
+        text.to_owned()
+    }
+}
+
+fn split(point: String) -> [String; 2] {
+    let (x, y) = point.split_once(',').unwrap();
+    [x.to_owned(), y.to_owned()]
+}
+
+/// An SVG fill or stroke attribute, with opacity for translucent colors.
+fn svg_paint(attribute: &str, color: [f32; 4]) -> String {
+    let [r, g, b, a] = color.map(|c| c.clamp(0.0, 1.0));
+    let byte = |c: f32| (c * 255.0).round() as u8;
+    let mut paint =
+        format!("{}=\"rgb({},{},{})\"", attribute, byte(r), byte(g), byte(b));
+    if a < 1.0 {
+        let _ = write!(paint, " {}-opacity=\"{}\"", attribute, num(a));
+    }
+    paint
+}
+
+fn pdf_rgb(color: [f32; 4]) -> String {
+    let [r, g, b, _] = color.map(|c| c.clamp(0.0, 1.0));
+    format!("{} {} {}", num(r), num(g), num(b))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_export() {
+        let drawing = VectorDrawing {
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            shapes: vec![
+                Shape::Polygon {
+                    points: vec![
+                        Vec2::new(-10.0, 10.0),
+                        Vec2::new(10.0, 10.0),
+                        Vec2::new(10.0, -10.0),
+                    ],
+                    color: [1.0, 0.0, 0.0, 1.0],
+                },
+                Shape::Line {
+                    start: Vec2::new(-50.0, 0.0),
+                    end: Vec2::new(50.0, 0.0),
+                    width: 2.5,
+                    color: [1.0, 1.0, 1.0, 0.5],
+                },
+                Shape::Path {
+                    segments: vec![
+                        PathSegment::MoveTo(Vec2::new(0.0, 0.0)),
+                        PathSegment::QuadTo(
+                            Vec2::new(30.0, 0.0),
+                            Vec2::new(30.0, 30.0),
+                        ),
+                        PathSegment::Close,
+                    ],
+                    color: [0.0, 0.0, 1.0, 1.0],
+                },
+            ],
+        };
+        let area = Rect::from_center(Vec2::zeros(), Vec2::new(200.0, 100.0));
+
+        let svg = drawing.to_svg(area);
+        assert!(svg.contains("viewBox=\"0 0 200 100\""));
+        assert!(svg.contains("points=\"90,40 110,40 110,60\""));
+        assert!(svg.contains("stroke-opacity=\"0.5\""));
+        assert!(svg.contains("d=\"M100,50 Q130,50 130,20 Z\""));
+
+        let pdf = String::from_utf8(drawing.to_pdf(area)).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.contains("90 60 m 110 60 l 110 40 l h f"));
+        assert!(pdf.contains("100 50 m 120 50 130 60 130 80 c h f"));
+        assert!(pdf.contains("/ca 0.5 /CA 0.5"));
+
+        // Every xref entry points at the start of its object.
+        let xref = pdf.rfind("startxref\n").unwrap() + "startxref\n".len();
+        let xref: usize = pdf[xref..].lines().next().unwrap().parse().unwrap();
+        let entries = pdf[xref..].lines().skip(3);
+        for (i, entry) in entries.take_while(|l| l.ends_with("n ")).enumerate()
+        {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+}
@@ -6,15 +6,15 @@ use {
     crate::{
         graphics::{
             vulkan_api::{raii, FramesInFlight, RenderDevice, Texture2D},
-            GraphicsError,
+            BlendMode, GraphicsError, SamplerSettings, SpriteBatch,
         },
-        math::Mat4,
+        math::{Mat4, Rect, Vec2, Vec4},
     },
     ash::vk,
     std::sync::Arc,
 };
 
-mod pipeline;
+pub(super) mod pipeline;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[repr(C)]
@@ -59,16 +59,31 @@ impl Default for UniformData {
     }
 }
 
+/// The fewest texture slots reserved in each descriptor set.
+const MIN_TEXTURE_CAPACITY: usize = 64;
+
 /// A utility for rendering high-performance textured quads.
 pub struct BindlessSprites {
     frame_resources: Vec<PerFrame>,
     uniform_data: UniformData,
+    projection: Mat4,
     pipeline_layout: raii::PipelineLayout,
-    pipeline: raii::Pipeline,
 
-    _sampler: raii::Sampler,
+    /// A pipeline for each blend mode, in the order of `BlendMode::index`.
+    pipelines: Vec<raii::Pipeline>,
+
+    /// The number of texture slots in each descriptor set, and the number
+    /// of them which have been written.
+    texture_capacity: usize,
+    texture_count: usize,
+
+    /// Textures with the same settings share a sampler.
+    sampler_settings: Vec<SamplerSettings>,
+    samplers: Vec<raii::Sampler>,
+
     _descriptor_pool: raii::DescriptorPool,
     _descriptor_set_layout: raii::DescriptorSetLayout,
+    render_device: Arc<RenderDevice>,
 }
 
 impl BindlessSprites {
@@ -84,19 +99,40 @@ impl BindlessSprites {
         frames_in_flight: &FramesInFlight,
         textures: &[Arc<Texture2D>],
     ) -> Result<Self, GraphicsError> {
+        // Reserve room for more textures so loading a few more later only
+        // writes the new descriptors. Unused slots must be left unwritten,
+        // which needs partially bound descriptors.
+        let texture_capacity =
+            if render_device.supports_partially_bound_descriptors() {
+                let limit = render_device.max_texture_descriptors() as usize;
+                textures
+                    .len()
+                    .max(MIN_TEXTURE_CAPACITY)
+                    .next_power_of_two()
+                    .min(limit)
+                    .max(textures.len())
+            } else {
+                textures.len()
+            };
         let (descriptor_set_layout, pipeline_layout) =
             pipeline::create_layouts(
                 render_device.clone(),
-                textures.len() as u32,
+                texture_capacity as u32,
             )?;
 
-        let pipeline = pipeline::create_pipeline(
-            render_device.clone(),
-            include_bytes!("./shaders/bindless.vert.spv"),
-            include_bytes!("./shaders/bindless.frag.spv"),
-            &pipeline_layout,
-            render_pass,
-        )?;
+        let pipelines = BlendMode::ALL
+            .iter()
+            .map(|&blend_mode| {
+                pipeline::create_pipeline(
+                    render_device.clone(),
+                    include_bytes!("./shaders/bindless.vert.spv"),
+                    include_bytes!("./shaders/bindless.frag.spv"),
+                    &pipeline_layout,
+                    render_pass,
+                    blend_mode,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         let descriptor_count = frames_in_flight.frame_count() as u32;
         let mut descriptor_pool = raii::DescriptorPool::new_with_sizes(
@@ -109,7 +145,8 @@ impl BindlessSprites {
                 },
                 vk::DescriptorPoolSize {
                     ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
-                    descriptor_count,
+                    descriptor_count: descriptor_count
+                        * texture_capacity as u32,
                 },
             ],
         )?;
@@ -118,18 +155,6 @@ impl BindlessSprites {
             .collect::<Vec<&raii::DescriptorSetLayout>>();
         let _ = descriptor_pool.allocate_descriptor_sets(&layouts)?;
 
-        let sampler = raii::Sampler::new(
-            render_device.clone(),
-            &vk::SamplerCreateInfo {
-                mipmap_mode: vk::SamplerMipmapMode::LINEAR,
-                mag_filter: vk::Filter::LINEAR,
-                min_filter: vk::Filter::LINEAR,
-                address_mode_u: vk::SamplerAddressMode::CLAMP_TO_EDGE,
-                address_mode_v: vk::SamplerAddressMode::CLAMP_TO_EDGE,
-                ..Default::default()
-            },
-        )?;
-
         let uniform_data = UniformData::default();
 
         let mut frame_resources = vec![];
@@ -137,22 +162,88 @@ impl BindlessSprites {
             let per_frame = PerFrame::new(
                 render_device.clone(),
                 descriptor_pool.descriptor_set(i),
-                textures,
-                &sampler,
             )?;
             frame_resources.push(per_frame);
         }
 
-        Ok(Self {
+        let mut bindless_sprites = Self {
             frame_resources,
             uniform_data,
+            projection: Mat4::identity(),
             pipeline_layout,
-            pipeline,
+            pipelines,
+
+            texture_capacity,
+            texture_count: 0,
+
+            sampler_settings: vec![],
+            samplers: vec![],
 
-            _sampler: sampler,
             _descriptor_pool: descriptor_pool,
             _descriptor_set_layout: descriptor_set_layout,
-        })
+            render_device,
+        };
+        bindless_sprites.bind_new_textures(textures)?;
+        Ok(bindless_sprites)
+    }
+
+    /// The most textures this instance can bind before it must be replaced.
+    pub fn texture_capacity(&self) -> usize {
+        self.texture_capacity
+    }
+
+    /// Write descriptors for every texture after the ones which are already
+    /// bound. Textures which were bound before are not written again.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - No frames can be in flight when the descriptor sets are written.
+    ///   - `textures` must start with every texture which is already bound, in
+    ///     the same order.
+    ///   - `textures` must fit in `texture_capacity`.
+    pub unsafe fn bind_new_textures(
+        &mut self,
+        textures: &[Arc<Texture2D>],
+    ) -> Result<(), GraphicsError> {
+        debug_assert!(textures.len() <= self.texture_capacity);
+        debug_assert!(textures.len() >= self.texture_count);
+        let new_textures = &textures[self.texture_count..];
+        if new_textures.is_empty() {
+            return Ok(());
+        }
+
+        let max_anisotropy = self.render_device.max_sampler_anisotropy();
+        for texture in new_textures {
+            if !self.sampler_settings.contains(&texture.sampler) {
+                self.samplers.push(raii::Sampler::new(
+                    self.render_device.clone(),
+                    &texture.sampler.create_info(max_anisotropy),
+                )?);
+                self.sampler_settings.push(texture.sampler);
+            }
+        }
+        let texture_samplers = new_textures
+            .iter()
+            .map(|texture| {
+                let index = self
+                    .sampler_settings
+                    .iter()
+                    .position(|settings| *settings == texture.sampler)
+                    .unwrap();
+                &self.samplers[index]
+            })
+            .collect::<Vec<&raii::Sampler>>();
+
+        for per_frame in &self.frame_resources {
+            per_frame.update_texture_bindings(
+                self.texture_count,
+                new_textures,
+                &texture_samplers,
+            );
+        }
+        self.texture_count = textures.len();
+        Ok(())
     }
 
     pub fn write_sprites_for_frame(
@@ -164,6 +255,7 @@ impl BindlessSprites {
     }
 
     pub fn set_projection(&mut self, projection: &Mat4) {
+        self.projection = *projection;
         self.uniform_data
             .projection
             .copy_from_slice(projection.as_slice());
@@ -175,20 +267,61 @@ impl BindlessSprites {
     ///
     /// Unsafe because:
     ///   - The render pass must already be started.
+    ///   - `batches` must only contain sprites written for this frame.
     pub unsafe fn draw_vertices(
         &mut self,
         frame: &Frame,
         viewport: vk::Extent2D,
+        batches: &[SpriteBatch],
     ) -> Result<(), GraphicsError> {
         let per_frame = &mut self.frame_resources[frame.frame_index()];
 
         per_frame.write_uniform_data(self.uniform_data)?;
 
+        let pipelines = &self.pipelines;
+        let projection = &self.projection;
         per_frame.cmd_draw(
             frame.command_buffer(),
             viewport,
-            &self.pipeline,
+            batches.iter().filter_map(|batch| {
+                let scissor = match batch.clip {
+                    Some(clip) => clip_to_scissor(&clip, projection, viewport)?,
+                    None => full_scissor(viewport),
+                };
+                let pipeline = &pipelines[batch.blend_mode.index()];
+                Some((pipeline, scissor, batch.sprites.clone()))
+            }),
             &self.pipeline_layout,
         )
     }
 }
+
+/// A scissor which covers the whole viewport.
+fn full_scissor(viewport: vk::Extent2D) -> vk::Rect2D {
+    vk::Rect2D {
+        offset: vk::Offset2D { x: 0, y: 0 },
+        extent: viewport,
+    }
+}
+
+/// The pixels covered by a world-space clip rect, rounded outward and
+/// clamped to the viewport.
+///
+/// Returns None when the clip rect is entirely off screen.
+fn clip_to_scissor(
+    clip: &Rect,
+    projection: &Mat4,
+    viewport: vk::Extent2D,
+) -> Option<vk::Rect2D> {
+    let size = Vec2::new(viewport.width as f32, viewport.height as f32);
+    let to_pixels = |point: Vec2| {
+        let ndc = projection * Vec4::new(point.x, point.y, 0.0, 1.0);
+        (Vec2::new(ndc.x, ndc.y) / ndc.w + Vec2::new(1.0, 1.0))
+            .component_mul(&size)
+            * 0.5
+    };
+    let pixels = Rect::new(to_pixels(clip.min), to_pixels(clip.max));
+    let visible = pixels.intersection(&Rect::new(Vec2::zeros(), size))?;
+    let scissor = vk::Rect2D::from(visible);
+    (scissor.extent.width > 0 && scissor.extent.height > 0).then_some(scissor)
+}
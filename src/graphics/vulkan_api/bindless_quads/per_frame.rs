@@ -7,7 +7,7 @@ use {
         GraphicsError,
     },
     ash::vk,
-    std::sync::Arc,
+    std::{ops::Range, sync::Arc},
 };
 
 /// All of the frame-specific resources used by BindlessTriangles.
@@ -28,8 +28,6 @@ impl PerFrame {
     pub unsafe fn new(
         render_device: Arc<RenderDevice>,
         descriptor_set: vk::DescriptorSet,
-        textures: &[Arc<Texture2D>],
-        sampler: &raii::Sampler,
     ) -> Result<Self, GraphicsError> {
         let sprite_data_buffer = MappedBuffer::<SpriteData>::new(
             render_device.clone(),
@@ -42,16 +40,13 @@ impl PerFrame {
             vk::BufferUsageFlags::UNIFORM_BUFFER,
         )?;
 
-        let per_frame = Self {
+        Ok(Self {
             sprite_data_buffer,
             uniform_buffer,
             descriptor_set_needs_update: true,
             descriptor_set,
             render_device,
-        };
-        per_frame.update_texture_bindings(textures, sampler);
-
-        Ok(per_frame)
+        })
     }
 
     pub fn write_uniform_data(
@@ -80,27 +75,25 @@ impl PerFrame {
         Ok(())
     }
 
-    /// Add commands to the frame's command buffer to draw the vertices.
+    /// Add commands to the frame's command buffer to draw each range of
+    /// sprites with its pipeline and scissor.
     ///
     /// # Safety
     ///
     /// Unsafe because:
     ///   - The render pass must already be started.
-    pub unsafe fn cmd_draw(
+    ///   - The sprite ranges must be within the sprites written for this frame.
+    pub unsafe fn cmd_draw<'a>(
         &mut self,
         command_buffer: vk::CommandBuffer,
         viewport: vk::Extent2D,
-        pipeline: &raii::Pipeline,
+        batches: impl Iterator<
+            Item = (&'a raii::Pipeline, vk::Rect2D, Range<usize>),
+        >,
         pipeline_layout: &raii::PipelineLayout,
     ) -> Result<(), GraphicsError> {
         self.update_buffer_bindings();
 
-        self.render_device.device().cmd_bind_pipeline(
-            command_buffer,
-            vk::PipelineBindPoint::GRAPHICS,
-            pipeline.raw(),
-        );
-
         let vk::Extent2D { width, height } = viewport;
         self.render_device.device().cmd_set_viewport(
             command_buffer,
@@ -114,14 +107,6 @@ impl PerFrame {
                 max_depth: 1.0,
             }],
         );
-        self.render_device.device().cmd_set_scissor(
-            command_buffer,
-            0,
-            &[vk::Rect2D {
-                offset: vk::Offset2D { x: 0, y: 0 },
-                extent: vk::Extent2D { width, height },
-            }],
-        );
         self.render_device.device().cmd_bind_descriptor_sets(
             command_buffer,
             vk::PipelineBindPoint::GRAPHICS,
@@ -130,15 +115,69 @@ impl PerFrame {
             &[self.descriptor_set],
             &[],
         );
-        self.render_device.device().cmd_draw(
-            command_buffer,
-            self.sprite_data_buffer.count() as u32 * 6,
-            1,
-            0,
-            0,
-        );
+        for (pipeline, scissor, sprites) in batches {
+            debug_assert!(sprites.end <= self.sprite_data_buffer.count());
+            self.render_device.device().cmd_set_scissor(
+                command_buffer,
+                0,
+                &[scissor],
+            );
+            self.render_device.device().cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                pipeline.raw(),
+            );
+            self.render_device.device().cmd_draw(
+                command_buffer,
+                sprites.len() as u32 * 6,
+                1,
+                sprites.start as u32 * 6,
+                0,
+            );
+        }
         Ok(())
     }
+
+    /// Bind textures to this frame's descriptor set, starting at array
+    /// element `first`. Each texture is paired with the sampler at the same
+    /// index.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - the descriptor set must not be in use by the GPU when it is written.
+    pub unsafe fn update_texture_bindings(
+        &self,
+        first: usize,
+        textures: &[Arc<Texture2D>],
+        samplers: &[&raii::Sampler],
+    ) {
+        if textures.is_empty() {
+            return;
+        }
+
+        let image_infos = textures
+            .iter()
+            .zip(samplers)
+            .map(|(texture, sampler)| vk::DescriptorImageInfo {
+                sampler: sampler.raw(),
+                image_view: texture.image_view.raw(),
+                image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            })
+            .collect::<Vec<vk::DescriptorImageInfo>>();
+        self.render_device.device().update_descriptor_sets(
+            &[vk::WriteDescriptorSet {
+                dst_set: self.descriptor_set,
+                dst_binding: 2,
+                dst_array_element: first as u32,
+                descriptor_type: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                descriptor_count: image_infos.len() as u32,
+                p_image_info: image_infos.as_ptr(),
+                ..vk::WriteDescriptorSet::default()
+            }],
+            &[],
+        );
+    }
 }
 
 // Private API
@@ -192,41 +231,4 @@ impl PerFrame {
             &[],
         );
     }
-
-    /// Update the sampled texture bindings for this frame.
-    ///
-    /// # Safety
-    ///
-    /// Unsafe because:
-    ///   - the descriptor set must not be in use by the GPU when it is written.
-    unsafe fn update_texture_bindings(
-        &self,
-        textures: &[Arc<Texture2D>],
-        sampler: &raii::Sampler,
-    ) {
-        if textures.is_empty() {
-            return;
-        }
-
-        let image_infos = textures
-            .iter()
-            .map(|texture| vk::DescriptorImageInfo {
-                sampler: sampler.raw(),
-                image_view: texture.image_view.raw(),
-                image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
-            })
-            .collect::<Vec<vk::DescriptorImageInfo>>();
-        self.render_device.device().update_descriptor_sets(
-            &[vk::WriteDescriptorSet {
-                dst_set: self.descriptor_set,
-                dst_binding: 2,
-                dst_array_element: 0,
-                descriptor_type: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
-                descriptor_count: image_infos.len() as u32,
-                p_image_info: image_infos.as_ptr(),
-                ..vk::WriteDescriptorSet::default()
-            }],
-            &[],
-        );
-    }
 }
@@ -1,41 +1,67 @@
 use {
     crate::graphics::{
         vulkan_api::{raii, RenderDevice},
-        GraphicsError,
+        BlendMode, GraphicsError,
     },
     ash::vk,
-    std::{ffi::CString, sync::Arc},
+    std::{ffi::CString, os::raw::c_void, sync::Arc},
 };
 
+/// Create the descriptor set and pipeline layouts. The texture binding has
+/// room for `texture_capacity` textures. When the device supports partially
+/// bound descriptors, only the first textures need to be written.
 pub unsafe fn create_layouts(
     render_device: Arc<RenderDevice>,
-    texture_count: u32,
+    texture_capacity: u32,
 ) -> Result<(raii::DescriptorSetLayout, raii::PipelineLayout), GraphicsError> {
-    let descriptor_set_layout = raii::DescriptorSetLayout::new_with_bindings(
+    let bindings = [
+        vk::DescriptorSetLayoutBinding {
+            binding: 0,
+            descriptor_type: vk::DescriptorType::STORAGE_BUFFER,
+            descriptor_count: 1,
+            stage_flags: vk::ShaderStageFlags::VERTEX,
+            ..vk::DescriptorSetLayoutBinding::default()
+        },
+        vk::DescriptorSetLayoutBinding {
+            binding: 1,
+            descriptor_type: vk::DescriptorType::UNIFORM_BUFFER,
+            descriptor_count: 1,
+            stage_flags: vk::ShaderStageFlags::VERTEX,
+            ..vk::DescriptorSetLayoutBinding::default()
+        },
+        vk::DescriptorSetLayoutBinding {
+            binding: 2,
+            descriptor_type: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+            descriptor_count: texture_capacity,
+            stage_flags: vk::ShaderStageFlags::FRAGMENT,
+            ..vk::DescriptorSetLayoutBinding::default()
+        },
+    ];
+    let texture_flags = if render_device.supports_partially_bound_descriptors()
+    {
+        vk::DescriptorBindingFlags::PARTIALLY_BOUND
+    } else {
+        vk::DescriptorBindingFlags::empty()
+    };
+    let binding_flags = [
+        vk::DescriptorBindingFlags::empty(),
+        vk::DescriptorBindingFlags::empty(),
+        texture_flags,
+    ];
+    let binding_flags_create_info =
+        vk::DescriptorSetLayoutBindingFlagsCreateInfo {
+            binding_count: binding_flags.len() as u32,
+            p_binding_flags: binding_flags.as_ptr(),
+            ..Default::default()
+        };
+    let descriptor_set_layout = raii::DescriptorSetLayout::new(
         render_device.clone(),
-        &[
-            vk::DescriptorSetLayoutBinding {
-                binding: 0,
-                descriptor_type: vk::DescriptorType::STORAGE_BUFFER,
-                descriptor_count: 1,
-                stage_flags: vk::ShaderStageFlags::VERTEX,
-                ..vk::DescriptorSetLayoutBinding::default()
-            },
-            vk::DescriptorSetLayoutBinding {
-                binding: 1,
-                descriptor_type: vk::DescriptorType::UNIFORM_BUFFER,
-                descriptor_count: 1,
-                stage_flags: vk::ShaderStageFlags::VERTEX,
-                ..vk::DescriptorSetLayoutBinding::default()
-            },
-            vk::DescriptorSetLayoutBinding {
-                binding: 2,
-                descriptor_type: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
-                descriptor_count: texture_count,
-                stage_flags: vk::ShaderStageFlags::FRAGMENT,
-                ..vk::DescriptorSetLayoutBinding::default()
-            },
-        ],
+        &vk::DescriptorSetLayoutCreateInfo {
+            p_next: &binding_flags_create_info as *const _ as *const c_void,
+            binding_count: bindings.len() as u32,
+            p_bindings: bindings.as_ptr(),
+            ..Default::default()
+        },
     )?;
     let pipeline_layout = raii::PipelineLayout::new_with_layouts_and_ranges(
         render_device,
@@ -45,13 +71,14 @@ pub unsafe fn create_layouts(
     Ok((descriptor_set_layout, pipeline_layout))
 }
 
-/// Create the graphics pipeline for this example.
+/// Create the graphics pipeline for sprites drawn with `blend_mode`.
 pub unsafe fn create_pipeline(
     render_device: Arc<RenderDevice>,
     vertex_source: &[u8],
     fragment_source: &[u8],
     layout: &raii::PipelineLayout,
     render_pass: &raii::RenderPass,
+    blend_mode: BlendMode,
 ) -> Result<raii::Pipeline, GraphicsError> {
     let vertex_shader_module = raii::ShaderModule::new_from_bytes(
         render_device.clone(),
@@ -96,17 +123,7 @@ pub unsafe fn create_pipeline(
         rasterization_samples: vk::SampleCountFlags::TYPE_1,
         ..Default::default()
     };
-    let color_blend_attachment_states =
-        [vk::PipelineColorBlendAttachmentState {
-            color_write_mask: vk::ColorComponentFlags::RGBA,
-            blend_enable: vk::TRUE,
-            src_color_blend_factor: vk::BlendFactor::SRC_ALPHA,
-            dst_color_blend_factor: vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
-            color_blend_op: vk::BlendOp::ADD,
-            src_alpha_blend_factor: vk::BlendFactor::ONE,
-            dst_alpha_blend_factor: vk::BlendFactor::ZERO,
-            alpha_blend_op: vk::BlendOp::ADD,
-        }];
+    let color_blend_attachment_states = [blend_mode.attachment_state()];
     let color_blend_state = vk::PipelineColorBlendStateCreateInfo {
         attachment_count: color_blend_attachment_states.len() as u32,
         p_attachments: color_blend_attachment_states.as_ptr(),
@@ -26,6 +26,8 @@ pub enum FrameStatus {
 /// in-flight frames.
 pub struct FramesInFlight {
     swapchain_needs_rebuild: bool,
+    transparent: bool,
+    vsync: bool,
     current_frame: usize,
     frames: Vec<Option<FrameSync>>,
     swapchain: Option<Swapchain>,
@@ -40,6 +42,9 @@ impl FramesInFlight {
     /// * `render_device` - used to create all Vulkan resources
     /// * `framebuffer_size` - the size of the framebuffer in pixels. This is
     ///   used to create the swapchain and acompanying images.
+    /// * `transparent` - true when the swapchain images should be composited
+    ///   with the desktop using their alpha channel.
+    /// * `vsync` - true when presentation should wait for the vertical blank.
     /// * `frame_count` - the number of in-flight frames to support. Typically
     ///   this is 2 for double-buffering or 3 for triple-buffering in-filght
     ///   frames.
@@ -53,6 +58,8 @@ impl FramesInFlight {
     pub unsafe fn new(
         render_device: Arc<RenderDevice>,
         framebuffer_size: (i32, i32),
+        transparent: bool,
+        vsync: bool,
         frame_count: usize,
     ) -> Result<Self, GraphicsError> {
         let mut frames = vec![];
@@ -67,11 +74,19 @@ impl FramesInFlight {
         let (w, h) = framebuffer_size;
         let swapchain = unsafe {
             // SAFE because the swapchain is kept and destroyed by this struct.
-            Swapchain::new(render_device.clone(), (w as u32, h as u32), None)?
+            Swapchain::new(
+                render_device.clone(),
+                (w as u32, h as u32),
+                transparent,
+                vsync,
+                None,
+            )?
         };
 
         Ok(Self {
             swapchain_needs_rebuild: false,
+            transparent,
+            vsync,
             current_frame: 0,
             frames,
             swapchain: Some(swapchain),
@@ -128,6 +143,8 @@ impl FramesInFlight {
         let new_swapchain = Swapchain::new(
             self.render_device.clone(),
             (w as u32, h as u32),
+            self.transparent,
+            self.vsync,
             old_swapchain,
         )?;
         self.swapchain = Some(new_swapchain);
@@ -157,6 +174,22 @@ impl FramesInFlight {
         self.frames.len()
     }
 
+    /// Wait for the GPU to finish the commands of the frame which
+    /// `acquire_frame` will return next.
+    ///
+    /// `acquire_frame` does this wait anyway. Doing it first lets the
+    /// application poll input after the wait, so the input is as fresh as
+    /// possible when the frame is recorded.
+    pub fn wait_for_next_frame(&self) -> Result<(), GraphicsError> {
+        let next_frame = (self.current_frame + 1) % self.frames.len();
+        match &self.frames[next_frame] {
+            Some(frame_sync) => {
+                frame_sync.wait_for_graphics_commands_to_complete()
+            }
+            None => Ok(()),
+        }
+    }
+
     /// Acquire the next frame for rendering.
     ///
     /// # Params
@@ -186,6 +219,9 @@ impl FramesInFlight {
         let swapchain_image_index = match result {
             SwapchainStatus::Index(index) => index,
             SwapchainStatus::NeedsRebuild => {
+                // Return the frame's resources so they can be used once the
+                // swapchain is rebuilt.
+                self.frames[self.current_frame] = Some(frame_sync);
                 self.swapchain_needs_rebuild = true;
                 return Ok(FrameStatus::SwapchainNeedsRebuild);
             }
@@ -289,8 +325,15 @@ impl Drop for FramesInFlight {
     ///     by any frames.
     fn drop(&mut self) {
         unsafe {
-            self.wait_for_all_frames_to_complete()
-                .expect("Error while waiting for frames to complete!");
+            // A frame which was acquired but never presented (e.g. because of
+            // an error or panic while recording) is missing from the list, so
+            // wait for the whole device instead of each frame's fence.
+            if let Err(error) = self.render_device.device().device_wait_idle() {
+                log::error!(
+                    "Error while waiting for frames to complete!\n{:?}",
+                    error
+                );
+            }
         }
     }
 }
@@ -0,0 +1,234 @@
+use {
+    super::{bindless_quads::pipeline, Frame},
+    crate::{
+        graphics::{
+            vulkan_api::{raii, FramesInFlight, RenderDevice, Texture2D},
+            BlendMode, GraphicsError,
+        },
+        math::{Rect, Vec2},
+    },
+    ash::vk,
+    std::sync::Arc,
+};
+
+/// The number of textures a fullscreen shader can sample.
+pub const CHANNEL_COUNT: usize = 4;
+
+/// Shadertoy's inputs, passed to the fragment shader as push constants.
+///
+/// The fragment shader must declare a matching block:
+///
+/// ```glsl
+/// layout(push_constant) uniform ShadertoyInputs {
+///     vec4 iMouse;
+///     vec3 iResolution;
+///     float iTime;
+///     float iTimeDelta;
+///     int iFrame;
+/// };
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[repr(C)]
+pub struct ShaderInputs {
+    pub mouse: [f32; 4],
+    pub resolution: [f32; 3],
+    pub time: f32,
+    pub time_delta: f32,
+    pub frame: i32,
+}
+
+/// Draws a user-provided fragment shader over the whole viewport.
+///
+/// The shader's textures are bound to set 0, bindings 0 through 3.
+pub struct FullscreenShader {
+    descriptor_sets: Vec<vk::DescriptorSet>,
+    pipeline_layout: raii::PipelineLayout,
+    pipeline: raii::Pipeline,
+    sampler: raii::Sampler,
+
+    _descriptor_pool: raii::DescriptorPool,
+    _descriptor_set_layout: raii::DescriptorSetLayout,
+    render_device: Arc<RenderDevice>,
+}
+
+impl FullscreenShader {
+    /// Create the pipeline for a fragment shader.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - This instance must be dropped before the RenderDevice is destroyed.
+    ///   - This instance must not be dropped while frames which use it are in
+    ///     flight.
+    pub unsafe fn new(
+        render_device: Arc<RenderDevice>,
+        render_pass: &raii::RenderPass,
+        frames_in_flight: &FramesInFlight,
+        fragment_source: &[u8],
+    ) -> Result<Self, GraphicsError> {
+        let bindings: Vec<vk::DescriptorSetLayoutBinding> = (0..CHANNEL_COUNT)
+            .map(|binding| vk::DescriptorSetLayoutBinding {
+                binding: binding as u32,
+                descriptor_type: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                descriptor_count: 1,
+                stage_flags: vk::ShaderStageFlags::FRAGMENT,
+                ..vk::DescriptorSetLayoutBinding::default()
+            })
+            .collect();
+        let descriptor_set_layout =
+            raii::DescriptorSetLayout::new_with_bindings(
+                render_device.clone(),
+                &bindings,
+            )?;
+        let pipeline_layout =
+            raii::PipelineLayout::new_with_layouts_and_ranges(
+                render_device.clone(),
+                &[descriptor_set_layout.raw()],
+                &[vk::PushConstantRange {
+                    stage_flags: vk::ShaderStageFlags::FRAGMENT,
+                    offset: 0,
+                    size: std::mem::size_of::<ShaderInputs>() as u32,
+                }],
+            )?;
+
+        let pipeline = pipeline::create_pipeline(
+            render_device.clone(),
+            include_bytes!("./shaders/fullscreen.vert.spv"),
+            fragment_source,
+            &pipeline_layout,
+            render_pass,
+            BlendMode::Alpha,
+        )?;
+
+        let frame_count = frames_in_flight.frame_count() as u32;
+        let mut descriptor_pool = raii::DescriptorPool::new_with_sizes(
+            render_device.clone(),
+            frame_count,
+            &[vk::DescriptorPoolSize {
+                ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                descriptor_count: frame_count * CHANNEL_COUNT as u32,
+            }],
+        )?;
+        let layouts = (0..frame_count)
+            .map(|_| &descriptor_set_layout)
+            .collect::<Vec<&raii::DescriptorSetLayout>>();
+        let _ = descriptor_pool.allocate_descriptor_sets(&layouts)?;
+        let descriptor_sets = (0..frame_count as usize)
+            .map(|i| descriptor_pool.descriptor_set(i))
+            .collect();
+
+        // Shadertoy repeats textures by default.
+        let sampler = raii::Sampler::new(
+            render_device.clone(),
+            &vk::SamplerCreateInfo {
+                mipmap_mode: vk::SamplerMipmapMode::LINEAR,
+                mag_filter: vk::Filter::LINEAR,
+                min_filter: vk::Filter::LINEAR,
+                address_mode_u: vk::SamplerAddressMode::REPEAT,
+                address_mode_v: vk::SamplerAddressMode::REPEAT,
+                max_lod: vk::LOD_CLAMP_NONE,
+                ..Default::default()
+            },
+        )?;
+
+        Ok(Self {
+            descriptor_sets,
+            pipeline_layout,
+            pipeline,
+            sampler,
+
+            _descriptor_pool: descriptor_pool,
+            _descriptor_set_layout: descriptor_set_layout,
+            render_device,
+        })
+    }
+
+    /// Add commands to the frame's command buffer to draw the shader.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - The render pass must already be started.
+    ///   - The frame's fence must have been waited on, so its descriptor set is
+    ///     not in use.
+    pub unsafe fn draw(
+        &self,
+        frame: &Frame,
+        viewport: vk::Extent2D,
+        inputs: &ShaderInputs,
+        channels: [&Texture2D; CHANNEL_COUNT],
+    ) {
+        let device = self.render_device.device();
+        let command_buffer = frame.command_buffer();
+        let descriptor_set = self.descriptor_sets[frame.frame_index()];
+
+        let image_infos = channels.map(|texture| vk::DescriptorImageInfo {
+            sampler: self.sampler.raw(),
+            image_view: texture.image_view.raw(),
+            image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        });
+        let writes: Vec<vk::WriteDescriptorSet> = image_infos
+            .iter()
+            .enumerate()
+            .map(|(binding, image_info)| vk::WriteDescriptorSet {
+                dst_set: descriptor_set,
+                dst_binding: binding as u32,
+                dst_array_element: 0,
+                descriptor_type: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                descriptor_count: 1,
+                p_image_info: image_info,
+                ..vk::WriteDescriptorSet::default()
+            })
+            .collect();
+        device.update_descriptor_sets(&writes, &[]);
+
+        device.cmd_bind_pipeline(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline.raw(),
+        );
+        let vk::Extent2D { width, height } = viewport;
+        device.cmd_set_viewport(
+            command_buffer,
+            0,
+            &[vk::Viewport {
+                x: 0.0,
+                y: 0.0,
+                width: width as f32,
+                height: height as f32,
+                min_depth: 0.0,
+                max_depth: 1.0,
+            }],
+        );
+        device.cmd_set_scissor(
+            command_buffer,
+            0,
+            &[
+                Rect::new(
+                    Vec2::zeros(),
+                    Vec2::new(width as f32, height as f32),
+                )
+                .into(),
+            ],
+        );
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline_layout.raw(),
+            0,
+            &[descriptor_set],
+            &[],
+        );
+        device.cmd_push_constants(
+            command_buffer,
+            self.pipeline_layout.raw(),
+            vk::ShaderStageFlags::FRAGMENT,
+            0,
+            std::slice::from_raw_parts(
+                inputs as *const ShaderInputs as *const u8,
+                std::mem::size_of::<ShaderInputs>(),
+            ),
+        );
+        device.cmd_draw(command_buffer, 3, 1, 0, 0);
+    }
+}
@@ -104,6 +104,21 @@ impl<T: Copy> MappedBuffer<T> {
         Ok(write_status)
     }
 
+    /// Read the elements saved by the last write, including any changes the
+    /// GPU has made to them since.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - The application must synchronize access to the buffer so the GPU is
+    ///     not writing while the data is read.
+    pub unsafe fn read(&self) -> &[T] {
+        std::slice::from_raw_parts(
+            self.host_ptr as *const T,
+            self.element_count,
+        )
+    }
+
     /// How many elements are currently saved in the buffer. The value is
     /// based on the last write.
     pub fn count(&self) -> usize {
@@ -1,9 +1,11 @@
 mod bindless_quads;
 mod command_buffer;
 mod frames_in_flight;
+mod fullscreen_shader;
 mod mapped_buffer;
 mod render_device;
 mod render_pass;
+mod staging_pool;
 mod swapchain;
 mod texture;
 
@@ -12,9 +14,11 @@ pub use self::{
     bindless_quads::{BindlessSprites, SpriteData},
     command_buffer::OneTimeSubmitCommandBuffer,
     frames_in_flight::{Frame, FrameStatus, FramesInFlight},
+    fullscreen_shader::{FullscreenShader, ShaderInputs, CHANNEL_COUNT},
     mapped_buffer::{MappedBuffer, WriteStatus},
     render_device::{Queue, RenderDevice},
     render_pass::ColorPass,
+    staging_pool::StagingPool,
     swapchain::{Swapchain, SwapchainStatus},
     texture::Texture2D,
 };
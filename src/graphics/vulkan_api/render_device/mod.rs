@@ -5,7 +5,7 @@ use {
         LogicalDevice, PhysicalDevice, PhysicalDeviceFeatures, VulkanInstance,
     },
     indoc::indoc,
-    std::sync::Mutex,
+    std::{os::raw::c_void, sync::Mutex},
 };
 
 mod queue;
@@ -30,6 +30,15 @@ pub struct RenderDevice {
     logical_device: LogicalDevice,
     instance: VulkanInstance,
     allocator: Mutex<MemoryAllocator>,
+    optional_features: OptionalFeatures,
+}
+
+/// Features which are enabled when the device supports them, but which
+/// aren't required to run.
+#[derive(Debug, Copy, Clone, Default)]
+struct OptionalFeatures {
+    sampler_anisotropy: bool,
+    descriptor_binding_partially_bound: bool,
 }
 
 // Public Api
@@ -44,7 +53,8 @@ impl RenderDevice {
     ///   resources. The RenderDevice takes ownership of the vulkan instance so
     ///   it can be destroyed in the correct order.
     /// * `features` - the physical device features required by this
-    ///   application.
+    ///   application. Optional features, like anisotropic filtering, are
+    ///   enabled separately when the picked device supports them.
     /// * `surface` - the surface this application will use for swapchain
     ///   presentation. Typically provided by the windowing system.
     ///
@@ -60,7 +70,13 @@ impl RenderDevice {
     ) -> Result<Self, GraphicsError> {
         let window_surface = WindowSurface::new(&instance, surface);
         let physical_device =
-            Self::pick_physical_device(&instance, features, &window_surface)?;
+            Self::pick_physical_device(&instance, &features, &window_surface)?;
+        let (physical_device, optional_features) =
+            Self::enable_optional_features(
+                &instance,
+                physical_device,
+                features,
+            )?;
         let queue_finder = QueueFinder::new(&physical_device, &window_surface);
         let logical_device = unsafe {
             // SAFE because the RenderDevice takes ownership of the instance
@@ -92,6 +108,7 @@ impl RenderDevice {
             logical_device,
             instance,
             allocator: Mutex::new(allocator),
+            optional_features,
         };
         render_device.set_debug_name(
             *render_device.presentation_queue().raw(),
@@ -195,6 +212,46 @@ impl RenderDevice {
         self.logical_device.raw()
     }
 
+    /// The largest anisotropic filtering ratio samplers can use. This is 1.0
+    /// when the device doesn't support anisotropic filtering.
+    pub fn max_sampler_anisotropy(&self) -> f32 {
+        if !self.optional_features.sampler_anisotropy {
+            return 1.0;
+        }
+        unsafe {
+            self.ash()
+                .get_physical_device_properties(
+                    *self.logical_device.physical_device().raw(),
+                )
+                .limits
+                .max_sampler_anisotropy
+        }
+    }
+
+    /// The most combined image samplers one descriptor set binding can
+    /// hold, limited by both the sampler and the sampled image limits.
+    pub fn max_texture_descriptors(&self) -> u32 {
+        let limits = unsafe {
+            self.ash()
+                .get_physical_device_properties(
+                    *self.logical_device.physical_device().raw(),
+                )
+                .limits
+        };
+        limits
+            .max_per_stage_descriptor_samplers
+            .min(limits.max_per_stage_descriptor_sampled_images)
+            .min(limits.max_descriptor_set_samplers)
+            .min(limits.max_descriptor_set_sampled_images)
+    }
+
+    /// True when descriptor set bindings can be created with
+    /// PARTIALLY_BOUND, so array elements the shader doesn't use can be
+    /// left unwritten.
+    pub fn supports_partially_bound_descriptors(&self) -> bool {
+        self.optional_features.descriptor_binding_partially_bound
+    }
+
     /// The KHR surface provided by the window system for rendering.
     ///
     /// # Safety
@@ -248,9 +305,14 @@ impl RenderDevice {
 impl Drop for RenderDevice {
     fn drop(&mut self) {
         unsafe {
-            self.device().device_wait_idle().expect(
-                "Error waiting for pending graphics operations to complete!",
-            );
+            // Don't panic here, a panic while already unwinding aborts the
+            // process before the remaining resources are cleaned up.
+            if let Err(error) = self.device().device_wait_idle() {
+                log::error!(
+                    "Error waiting for pending graphics operations to complete!\n{:?}",
+                    error
+                );
+            }
         }
     }
 }
@@ -294,13 +356,13 @@ impl RenderDevice {
     /// * `features` - all features required by this application.
     fn pick_physical_device(
         instance: &VulkanInstance,
-        features: PhysicalDeviceFeatures,
+        features: &PhysicalDeviceFeatures,
         window_surface: &WindowSurface,
     ) -> Result<PhysicalDevice, GraphicsError> {
         log::trace!("Enumerate supported devices!");
 
         let all_devices =
-            PhysicalDevice::enumerate_supported_devices(instance, &features)?;
+            PhysicalDevice::enumerate_supported_devices(instance, features)?;
 
         log::trace!("All available physical devices: {:?}", all_devices);
 
@@ -359,4 +421,66 @@ impl RenderDevice {
             .ok_or(GraphicsError::NoSuitablePhysicalDevice)?;
         Ok(device.clone())
     }
+    /// Enable each optional feature which the picked device supports.
+    ///
+    /// The device was picked using only the required features, so it is
+    /// enumerated again with the supported optional features added.
+    ///
+    /// # Params
+    ///
+    /// * `instance` - the Vulkan instance used to access devices on this
+    ///   platform.
+    /// * `physical_device` - the device picked with the required features.
+    /// * `features` - all features required by this application.
+    unsafe fn enable_optional_features(
+        instance: &VulkanInstance,
+        physical_device: PhysicalDevice,
+        mut features: PhysicalDeviceFeatures,
+    ) -> Result<(PhysicalDevice, OptionalFeatures), GraphicsError> {
+        let handle = *physical_device.raw();
+        let mut descriptor_indexing =
+            vk::PhysicalDeviceDescriptorIndexingFeatures::default();
+        let mut supported = vk::PhysicalDeviceFeatures2 {
+            p_next: &mut descriptor_indexing as *mut _ as *mut c_void,
+            ..Default::default()
+        };
+        instance
+            .ash()
+            .get_physical_device_features2(handle, &mut supported);
+        let optional_features = OptionalFeatures {
+            sampler_anisotropy: supported.features.sampler_anisotropy
+                == vk::TRUE,
+            descriptor_binding_partially_bound: descriptor_indexing
+                .descriptor_binding_partially_bound
+                == vk::TRUE,
+        };
+        if !optional_features.sampler_anisotropy
+            && !optional_features.descriptor_binding_partially_bound
+        {
+            return Ok((physical_device, OptionalFeatures::default()));
+        }
+        if optional_features.sampler_anisotropy {
+            features.features_mut().sampler_anisotropy = vk::TRUE;
+        }
+        if optional_features.descriptor_binding_partially_bound {
+            features
+                .descriptor_indexing_features_mut()
+                .descriptor_binding_partially_bound = vk::TRUE;
+        }
+
+        let device =
+            PhysicalDevice::enumerate_supported_devices(instance, &features)?
+                .into_iter()
+                .find(|device| *device.raw() == handle);
+        match device {
+            Some(device) => Ok((device, optional_features)),
+            None => {
+                log::warn!(
+                    "{} rejected its optional features, running without them",
+                    physical_device
+                );
+                Ok((physical_device, OptionalFeatures::default()))
+            }
+        }
+    }
 }
@@ -0,0 +1,133 @@
+use {
+    crate::graphics::{
+        vulkan_api::{raii, RenderDevice},
+        GraphicsError,
+    },
+    ash::vk,
+    std::{os::raw::c_void, sync::Arc},
+};
+
+/// The smallest staging buffer the pool allocates.
+const MIN_BUFFER_SIZE: u64 = 64 * 1024;
+
+/// The most memory the pool keeps between uploads. Larger buffers are freed
+/// first when the pool is over the limit, so a one-off large upload doesn't
+/// keep its buffer for the rest of the app's life.
+const MAX_RETAINED_BYTES: u64 = 16 * 1024 * 1024;
+
+/// A host visible buffer used as the source for an upload.
+pub struct StagingBuffer {
+    buffer: raii::Buffer,
+    ptr: *mut c_void,
+    size: u64,
+}
+
+// The mapped pointer is only accessed by the owner of the buffer.
+unsafe impl Send for StagingBuffer {}
+
+/// Keeps staging buffers between uploads so each upload doesn't allocate and
+/// free device memory.
+///
+/// Buffers are rounded up to a power of two size and reused for any upload
+/// which fits.
+pub struct StagingPool {
+    /// Buffers which aren't in use, sorted from smallest to largest.
+    free: Vec<StagingBuffer>,
+    render_device: Arc<RenderDevice>,
+}
+
+// Public API
+// ----------
+
+impl StagingBuffer {
+    /// The raw Vulkan buffer handle.
+    pub fn raw(&self) -> vk::Buffer {
+        self.buffer.raw()
+    }
+
+    /// The buffer's mapped memory.
+    pub fn ptr(&self) -> *mut c_void {
+        self.ptr
+    }
+
+    /// The buffer's size in bytes. This can be larger than the size that was
+    /// requested.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl StagingPool {
+    pub fn new(render_device: Arc<RenderDevice>) -> Self {
+        Self {
+            free: vec![],
+            render_device,
+        }
+    }
+
+    /// Take the smallest free buffer which holds at least `size` bytes,
+    /// allocating one if no free buffer is large enough.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - the buffer must be returned with `recycle` or dropped before the
+    ///     render device is destroyed.
+    pub unsafe fn take(
+        &mut self,
+        size: u64,
+    ) -> Result<StagingBuffer, GraphicsError> {
+        let bucket = size.max(MIN_BUFFER_SIZE).next_power_of_two();
+        match self.free.iter().position(|buffer| buffer.size >= bucket) {
+            Some(index) => Ok(self.free.remove(index)),
+            None => self.allocate(bucket),
+        }
+    }
+
+    /// Return a buffer to the pool so a later upload can use it.
+    ///
+    /// # Safety
+    ///
+    /// Unsafe because:
+    ///   - the GPU must be finished reading from the buffer, e.g. the fence for
+    ///     the upload's submission has signaled.
+    pub unsafe fn recycle(&mut self, buffer: StagingBuffer) {
+        self.free.push(buffer);
+        self.free.sort_by_key(|buffer| buffer.size);
+        let mut retained: u64 =
+            self.free.iter().map(|buffer| buffer.size).sum();
+        while retained > MAX_RETAINED_BYTES {
+            retained -= self.free.pop().unwrap().size;
+        }
+    }
+}
+
+// Private API
+// -----------
+
+impl StagingPool {
+    /// Allocate a new host visible buffer for transfers on the transfer
+    /// queue.
+    unsafe fn allocate(
+        &self,
+        size: u64,
+    ) -> Result<StagingBuffer, GraphicsError> {
+        let index = self.render_device.transfer_queue().family_index();
+        let create_info = vk::BufferCreateInfo {
+            size,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            queue_family_index_count: 1,
+            p_queue_family_indices: &index,
+            usage: vk::BufferUsageFlags::TRANSFER_SRC,
+            ..Default::default()
+        };
+        let buffer = raii::Buffer::new(
+            self.render_device.clone(),
+            &create_info,
+            vk::MemoryPropertyFlags::HOST_VISIBLE
+                | vk::MemoryPropertyFlags::HOST_COHERENT,
+        )?;
+        let ptr = buffer.allocation().map(self.render_device.device())?;
+        Ok(StagingBuffer { buffer, ptr, size })
+    }
+}
@@ -21,6 +21,7 @@ pub struct Swapchain {
     extent: vk::Extent2D,
     format: vk::SurfaceFormatKHR,
     present_mode: vk::PresentModeKHR,
+    supports_readback: bool,
     swapchain: vk::SwapchainKHR,
     swapchain_loader: extensions::khr::Swapchain,
     render_device: Arc<RenderDevice>,
@@ -37,6 +38,9 @@ impl Swapchain {
     /// * `render_device` - the device used to create vulkan resources
     /// * `framebuffer_size` - the size of the window's framebuffer in device
     ///   pixels.
+    /// * `transparent` - true when the swapchain images should be composited
+    ///   with the desktop using their alpha channel.
+    /// * `vsync` - true when presentation should wait for the vertical blank.
     /// * `previous_swapchain` - the previous swapchain (if any). This is
     ///   provided to the new swapchain and will be destroyed inside this
     ///   method.
@@ -53,16 +57,34 @@ impl Swapchain {
     pub unsafe fn new(
         render_device: Arc<RenderDevice>,
         framebuffer_size: (u32, u32),
+        transparent: bool,
+        vsync: bool,
         previous_swapchain: Option<Self>,
     ) -> Result<Self, GraphicsError> {
         let format =
             Self::choose_surface_format(&render_device.get_surface_formats()?)?;
-        let present_mode =
-            Self::choose_presentation_mode(&render_device.get_present_modes()?);
+        let present_mode = Self::choose_presentation_mode(
+            &render_device.get_present_modes()?,
+            vsync,
+        );
         let capabilities = render_device.get_surface_capabilities()?;
         let extent =
             Self::choose_swapchain_extent(capabilities, framebuffer_size);
         let min_image_count = Self::choose_image_count(capabilities);
+        let composite_alpha =
+            Self::choose_composite_alpha(capabilities, transparent);
+
+        // Swapchain images are copied to a buffer for screenshots when the
+        // surface allows it.
+        let supports_readback = capabilities
+            .supported_usage_flags
+            .contains(vk::ImageUsageFlags::TRANSFER_SRC);
+        let image_usage = if supports_readback {
+            vk::ImageUsageFlags::COLOR_ATTACHMENT
+                | vk::ImageUsageFlags::TRANSFER_SRC
+        } else {
+            vk::ImageUsageFlags::COLOR_ATTACHMENT
+        };
 
         let mut create_info = vk::SwapchainCreateInfoKHR {
             surface: *render_device.surface(),
@@ -73,11 +95,11 @@ impl Swapchain {
             image_color_space: format.color_space,
             image_extent: extent,
             image_array_layers: 1,
-            image_usage: vk::ImageUsageFlags::COLOR_ATTACHMENT,
+            image_usage,
 
             // window system settings
             present_mode,
-            composite_alpha: vk::CompositeAlphaFlagsKHR::OPAQUE,
+            composite_alpha,
             pre_transform: vk::SurfaceTransformFlagsKHR::IDENTITY,
             old_swapchain: if previous_swapchain.is_some() {
                 *previous_swapchain.as_ref().unwrap().raw()
@@ -126,6 +148,7 @@ impl Swapchain {
             extent,
             format,
             present_mode,
+            supports_readback,
             swapchain,
             swapchain_loader,
             render_device,
@@ -155,6 +178,11 @@ impl Swapchain {
         self.extent
     }
 
+    /// True when swapchain images can be copied to a buffer after rendering.
+    pub fn supports_readback(&self) -> bool {
+        self.supports_readback
+    }
+
     /// The presentation mode used by this swapchain.
     #[allow(dead_code)]
     pub fn present_mode(&self) -> vk::PresentModeKHR {
@@ -41,11 +41,14 @@ impl Swapchain {
     ///
     /// * `available_modes` - the presentation modes supported by the device and
     ///   surface.
+    /// * `vsync` - when true, always use FIFO so each frame waits for the
+    ///   vertical blank.
     pub(super) fn choose_presentation_mode(
         available_modes: &[vk::PresentModeKHR],
+        vsync: bool,
     ) -> vk::PresentModeKHR {
         let preferred_mode = vk::PresentModeKHR::MAILBOX;
-        if available_modes.contains(&preferred_mode) {
+        if !vsync && available_modes.contains(&preferred_mode) {
             log::trace!(
                 "Using preferred swapchain present mode {:?}",
                 preferred_mode
@@ -93,6 +96,42 @@ impl Swapchain {
         }
     }
 
+    /// Chose how swapchain images are composited with the desktop.
+    ///
+    /// # Params
+    ///
+    /// * `capabilities` - the available surface capabilities for the device
+    /// * `transparent` - true when the swapchain's alpha channel should be used
+    ///   for compositing
+    pub(super) fn choose_composite_alpha(
+        capabilities: vk::SurfaceCapabilitiesKHR,
+        transparent: bool,
+    ) -> vk::CompositeAlphaFlagsKHR {
+        if !transparent {
+            return vk::CompositeAlphaFlagsKHR::OPAQUE;
+        }
+
+        let preferred_modes = [
+            vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED,
+            vk::CompositeAlphaFlagsKHR::POST_MULTIPLIED,
+            vk::CompositeAlphaFlagsKHR::INHERIT,
+        ];
+        let supported = capabilities.supported_composite_alpha;
+        if let Some(&mode) = preferred_modes
+            .iter()
+            .find(|&&mode| supported.contains(mode))
+        {
+            log::trace!("Using composite alpha mode {:?}", mode);
+            return mode;
+        }
+
+        log::warn!(
+            "Transparent framebuffers are unsupported, available modes: {:?}",
+            supported
+        );
+        vk::CompositeAlphaFlagsKHR::OPAQUE
+    }
+
     /// Chose the number of swapchain images to use.
     ///
     /// # Params
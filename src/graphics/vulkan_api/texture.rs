@@ -1,8 +1,9 @@
-use crate::graphics::vulkan_api::raii;
+use crate::graphics::{vulkan_api::raii, SamplerSettings};
 
 /// Represents a 2D rgba texture which can be used by shaders.
 #[derive(Debug)]
 pub struct Texture2D {
     pub image_view: raii::ImageView,
     pub image: raii::Image,
+    pub sampler: SamplerSettings,
 }
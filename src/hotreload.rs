@@ -0,0 +1,404 @@
+//! Run a sketch from a dynamic library and reload it when the library is
+//! rebuilt.
+//!
+//! The sketch crate is compiled as a `cdylib` and exports its sketch with the
+//! [crate::export_sketch] macro:
+//!
+//! ```ignore
+//! #[derive(Default)]
+//! struct MySketch { /* ... */ }
+//!
+//! impl sim2d::Sketch for MySketch { /* ... */ }
+//!
+//! sim2d::export_sketch!(MySketch::default());
+//! ```
+//!
+//! A small host binary then calls [run] with the path to the compiled library.
+//! Rebuilding the library (e.g. with `cargo watch -x build`) swaps the new
+//! sketch in without closing the window. State survives the swap when the
+//! sketch implements [Sketch::save_state] and [Sketch::restore_state].
+
+use {
+    crate::{
+        application::Application,
+        graphics::{AssetLoader, Image},
+        live_reload::{modified_time, PollTimer},
+        sim2d::Sim2D,
+        DynSketch, Sketch,
+    },
+    anyhow::{Context, Result},
+    libloading::Library,
+    std::{
+        any::Any,
+        mem::ManuallyDrop,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::SystemTime,
+    },
+};
+
+/// The symbol exported by [crate::export_sketch].
+const CREATE_SKETCH_SYMBOL: &[u8] = b"sim2d_create_sketch\0";
+
+/// Export a sketch from a `cdylib` so it can be loaded with [crate::hotreload].
+///
+/// # Params
+///
+/// * `$sketch` - an expression which constructs a new instance of the sketch.
+///   It is evaluated every time the library is loaded.
+#[macro_export]
+macro_rules! export_sketch {
+    ($sketch:expr) => {
+        #[no_mangle]
+        pub fn sim2d_create_sketch() -> $crate::DynSketch {
+            Box::new($sketch)
+        }
+    };
+}
+
+// Public API
+// ----------
+
+/// Create and run an Application with the sketch exported by the library at
+/// `library_path`, reloading it every time the library changes on disk.
+///
+/// # Safety
+///
+/// The library is trusted to export a `sim2d_create_sketch` function with the
+/// signature generated by [crate::export_sketch]. The library must be built
+/// with the same compiler and the same version of sim2d as the host because
+/// sketches cross the library boundary as Rust trait objects.
+pub unsafe fn run(library_path: impl AsRef<Path>) -> Result<()> {
+    let sketch = HotReloadSketch::load(library_path.as_ref(), None)?;
+    Application::run(sketch)
+}
+
+// Private API
+// -----------
+
+/// The handle which keeps a library's code loaded. Sketches handed off with
+/// `load_sketch` share the handle of the sketch which created them.
+type LibraryHandle = Arc<dyn Any + Send + Sync>;
+
+/// A sketch which forwards to a sketch loaded from a dynamic library.
+struct HotReloadSketch {
+    // Fields are dropped in declaration order. The sketch must be dropped
+    // before the library which contains its code.
+    sketch: DynSketch,
+    library: LibraryHandle,
+
+    source_path: PathBuf,
+    modified: SystemTime,
+    pending_state: Option<Vec<u8>>,
+    poll: PollTimer,
+    changed_at: Option<SystemTime>,
+}
+
+impl HotReloadSketch {
+    /// Load the sketch from the library at `path`.
+    ///
+    /// # Params
+    ///
+    /// * `state` - state saved from the previous version of the sketch. It is
+    ///   restored after the new sketch's setup method is called.
+    ///
+    /// # Safety
+    ///
+    /// See [run].
+    unsafe fn load(path: &Path, state: Option<Vec<u8>>) -> Result<Self> {
+        let library = LoadedLibrary::load(path)?;
+        let create_sketch = library
+            .library
+            .get::<fn() -> DynSketch>(CREATE_SKETCH_SYMBOL)
+            .with_context(|| {
+                format!(
+                    "{:?} does not export a sketch, use sim2d::export_sketch!",
+                    path
+                )
+            })?;
+        let sketch = create_sketch();
+        let modified = library.modified;
+        Ok(Self::new(
+            sketch,
+            Arc::new(library),
+            path.to_owned(),
+            modified,
+            state,
+        ))
+    }
+
+    /// Forward to `sketch`, whose code lives in `library`.
+    fn new(
+        sketch: DynSketch,
+        library: LibraryHandle,
+        source_path: PathBuf,
+        modified: SystemTime,
+        state: Option<Vec<u8>>,
+    ) -> Self {
+        Self {
+            sketch,
+            library,
+            source_path,
+            modified,
+            pending_state: state,
+            poll: PollTimer::new(),
+            changed_at: None,
+        }
+    }
+
+    /// Check the library file for changes.
+    ///
+    /// Returns true when the file has changed since it was loaded and has been
+    /// stable since the last poll. Waiting for the file to settle avoids
+    /// loading a library which is still being written by the linker.
+    fn is_rebuilt(&mut self) -> bool {
+        if !self.poll.is_due() {
+            return false;
+        }
+        let modified = match modified_time(&self.source_path) {
+            Ok(modified) => modified,
+            Err(_) => return false,
+        };
+        if modified == self.modified {
+            return false;
+        }
+        let is_stable = self.changed_at == Some(modified);
+        self.changed_at = Some(modified);
+        is_stable
+    }
+
+    /// Load a new instance of the library with the current sketch's state.
+    fn reload(&mut self) -> Result<DynSketch> {
+        let state = self.sketch.save_state();
+        let sketch = unsafe { Self::load(&self.source_path, state)? };
+        log::info!("Reloaded sketch from {:?}", self.source_path);
+        Ok(Box::new(sketch))
+    }
+}
+
+impl Sketch for HotReloadSketch {
+    fn preload(&mut self, asset_loader: &mut AssetLoader) -> Result<()> {
+        self.sketch.preload(asset_loader)
+    }
+
+    fn setup(&mut self, sim: &mut Sim2D) {
+        self.sketch.setup(sim);
+        if let Some(state) = self.pending_state.take() {
+            self.sketch.restore_state(&state);
+        }
+    }
+
+    fn mouse_moved(&mut self, sim: &mut Sim2D) {
+        self.sketch.mouse_moved(sim);
+    }
+
+    fn mouse_pressed(&mut self, sim: &mut Sim2D) {
+        self.sketch.mouse_pressed(sim);
+    }
+
+    fn mouse_released(&mut self, sim: &mut Sim2D) {
+        self.sketch.mouse_released(sim);
+    }
+
+    fn mouse_clicked(&mut self, sim: &mut Sim2D) {
+        self.sketch.mouse_clicked(sim);
+    }
+
+    fn mouse_double_clicked(&mut self, sim: &mut Sim2D) {
+        self.sketch.mouse_double_clicked(sim);
+    }
+
+    fn char_typed(&mut self, sim: &mut Sim2D, c: char) {
+        self.sketch.char_typed(sim, c);
+    }
+
+    fn scrolled(&mut self, sim: &mut Sim2D, dx: f32, dy: f32) {
+        self.sketch.scrolled(sim, dx, dy);
+    }
+
+    fn key_pressed(
+        &mut self,
+        sim: &mut Sim2D,
+        key: glfw::Key,
+        modifiers: glfw::Modifiers,
+    ) {
+        self.sketch.key_pressed(sim, key, modifiers);
+    }
+
+    fn key_released(&mut self, sim: &mut Sim2D, key: glfw::Key) {
+        self.sketch.key_released(sim, key);
+    }
+
+    fn content_scale_changed(&mut self, sim: &mut Sim2D) {
+        self.sketch.content_scale_changed(sim);
+    }
+
+    fn image_pasted(&mut self, sim: &mut Sim2D, image: Image) {
+        self.sketch.image_pasted(sim, image);
+    }
+
+    fn raw_event(&mut self, sim: &mut Sim2D, event: &glfw::WindowEvent) {
+        self.sketch.raw_event(sim, event);
+    }
+
+    fn save_state(&mut self) -> Option<Vec<u8>> {
+        self.sketch.save_state()
+    }
+
+    fn restore_state(&mut self, state: &[u8]) {
+        self.sketch.restore_state(state);
+    }
+
+    fn load_sketch(&mut self) -> Option<DynSketch> {
+        if self.is_rebuilt() {
+            match self.reload() {
+                Ok(sketch) => return Some(sketch),
+                Err(error) => {
+                    // Keep running the old sketch and try again after the
+                    // next change.
+                    self.modified = self.changed_at.unwrap();
+                    log::error!("Unable to reload sketch!\n{:?}", error);
+                }
+            }
+        }
+
+        // The next sketch's code lives in the same library, so it keeps the
+        // library loaded after this sketch is dropped.
+        let sketch = self.sketch.load_sketch()?;
+        Some(Box::new(Self::new(
+            sketch,
+            self.library.clone(),
+            self.source_path.clone(),
+            self.modified,
+            None,
+        )))
+    }
+
+    fn update(&mut self, sim: &mut Sim2D) {
+        self.sketch.update(sim);
+    }
+}
+
+/// A dynamic library loaded from a private copy of the original file.
+///
+/// Most platforms won't load a library a second time from the same path while
+/// it is still open, and some won't let the linker overwrite a file which is
+/// loaded. Loading from a unique copy avoids both problems.
+struct LoadedLibrary {
+    library: ManuallyDrop<Library>,
+    copy_path: PathBuf,
+    modified: SystemTime,
+}
+
+impl LoadedLibrary {
+    /// # Safety
+    ///
+    /// See [run]. Loading a library runs its initialization routines.
+    unsafe fn load(source_path: &Path) -> Result<Self> {
+        static NEXT_COPY: AtomicUsize = AtomicUsize::new(0);
+
+        let modified = modified_time(source_path)?;
+        let file_name = source_path
+            .file_name()
+            .with_context(|| format!("{:?} is not a file", source_path))?;
+        let copy_path = std::env::temp_dir().join(format!(
+            "sim2d-hotreload-{}-{}-{}",
+            std::process::id(),
+            NEXT_COPY.fetch_add(1, Ordering::Relaxed),
+            file_name.to_string_lossy()
+        ));
+        std::fs::copy(source_path, &copy_path).with_context(|| {
+            format!("Unable to copy {:?} to {:?}", source_path, copy_path)
+        })?;
+
+        let library = Library::new(&copy_path).with_context(|| {
+            format!("Unable to load library {:?}", source_path)
+        })?;
+
+        Ok(Self {
+            library: ManuallyDrop::new(library),
+            copy_path,
+            modified,
+        })
+    }
+}
+
+impl Drop for LoadedLibrary {
+    fn drop(&mut self) {
+        unsafe {
+            // The library must be closed before the copy can be removed on
+            // every platform.
+            ManuallyDrop::drop(&mut self.library);
+        }
+        if let Err(error) = std::fs::remove_file(&self.copy_path) {
+            log::warn!("Unable to remove {:?}\n{:?}", self.copy_path, error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        std::sync::{Arc, Mutex},
+    };
+
+    type DropLog = Arc<Mutex<Vec<&'static str>>>;
+
+    /// Records when it is dropped, standing in for a loaded library or a
+    /// sketch which lives in one.
+    struct Dropped(&'static str, DropLog);
+
+    impl Drop for Dropped {
+        fn drop(&mut self) {
+            self.1.lock().unwrap().push(self.0);
+        }
+    }
+
+    struct FakeSketch {
+        next: Option<DynSketch>,
+        _dropped: Dropped,
+    }
+
+    impl Sketch for FakeSketch {
+        fn load_sketch(&mut self) -> Option<DynSketch> {
+            self.next.take()
+        }
+
+        fn update(&mut self, _sim: &mut Sim2D) {}
+    }
+
+    #[test]
+    fn library_outlives_handed_off_sketch() {
+        let log = DropLog::default();
+        let next = FakeSketch {
+            next: None,
+            _dropped: Dropped("next sketch", log.clone()),
+        };
+        let first = FakeSketch {
+            next: Some(Box::new(next)),
+            _dropped: Dropped("first sketch", log.clone()),
+        };
+        let library = Arc::new(Dropped("library", log.clone()));
+        let mut sketch = HotReloadSketch::new(
+            Box::new(first),
+            library,
+            PathBuf::from("missing-sketch-library"),
+            SystemTime::UNIX_EPOCH,
+            None,
+        );
+
+        let next = sketch.load_sketch().unwrap();
+        drop(sketch);
+        assert_eq!(*log.lock().unwrap(), ["first sketch"]);
+
+        drop(next);
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["first sketch", "next sketch", "library"]
+        );
+    }
+}
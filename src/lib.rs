@@ -1,13 +1,31 @@
+mod fragment_sketch;
+mod live_reload;
 mod sim2d;
 mod sketch;
+mod sketch_stack;
 mod window;
 
 pub mod application;
+#[cfg(feature = "audio")]
+pub mod audio;
 pub mod ext;
 pub mod graphics;
+pub mod hotreload;
 pub mod math;
+#[cfg(feature = "midi")]
+pub mod midi;
+pub mod osc;
+#[cfg(feature = "physics")]
+pub mod physics;
+#[cfg(feature = "scripting")]
+pub mod script;
+#[cfg(feature = "sync")]
+pub mod sync;
+pub mod timecode;
 
 pub use self::{
+    fragment_sketch::FragmentSketch,
     sim2d::Sim2D,
     sketch::{DynSketch, Sketch},
+    sketch_stack::SketchStack,
 };
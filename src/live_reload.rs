@@ -0,0 +1,95 @@
+//! Polling files for changes and showing load errors on screen, for the
+//! sketches which reload their code while they run.
+
+use {
+    crate::{graphics::Image, math::Vec2, sim2d::Sim2D},
+    anyhow::{Context, Result},
+    std::{
+        path::{Path, PathBuf},
+        time::{Duration, Instant, SystemTime},
+    },
+};
+
+/// How often watched files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Errors are drawn in this color so they stand out on most backgrounds.
+const ERROR_COLOR: [f32; 4] = [1.0, 0.2, 0.2, 1.0];
+
+/// Limits how often a file's metadata is read.
+pub(crate) struct PollTimer {
+    last_poll: Instant,
+}
+
+/// A file which is checked for a new modified time.
+pub(crate) struct WatchedFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    poll: PollTimer,
+}
+
+impl PollTimer {
+    pub fn new() -> Self {
+        Self {
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Returns true at most once per poll interval.
+    pub fn is_due(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+        true
+    }
+}
+
+impl WatchedFile {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+            modified: modified_time(path).ok(),
+            poll: PollTimer::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true when the file's modified time has changed since the
+    /// last time this returned true.
+    pub fn is_changed(&mut self) -> bool {
+        if !self.poll.is_due() {
+            return false;
+        }
+        let modified = modified_time(&self.path).ok();
+        if modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+}
+
+pub(crate) fn modified_time(path: &Path) -> Result<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .with_context(|| {
+            format!("Unable to read the modified time of {:?}", path)
+        })
+}
+
+/// Draw `error` in the window's top left corner, on top of what the sketch
+/// drew.
+pub(crate) fn draw_error(sim: &mut Sim2D, error: &str) {
+    let original_color = sim.g.fill_color;
+    let original_image = sim.g.image;
+    sim.g.fill_color = ERROR_COLOR;
+    sim.g.image = Image::none();
+    let top_left = Vec2::new(-sim.w.width(), sim.w.height()) * 0.5;
+    sim.g.text(top_left + Vec2::new(10.0, -10.0), error);
+    sim.g.fill_color = original_color;
+    sim.g.image = original_image;
+}
@@ -0,0 +1,92 @@
+//! Angle helpers. All angles are in radians and increase counter-clockwise.
+
+use {
+    super::{Rot2, Vec2},
+    std::f32::consts::{PI, TAU},
+};
+
+/// Rotation helpers for `Vec2`.
+///
+/// `Vec2` is an alias for a nalgebra type, so these are provided as a trait.
+/// Bring it into scope with `use sim2d::math::Vec2Ext`. nalgebra already has
+/// `angle` and `perp` methods which take a second vector, so the single-vector
+/// versions here are named `to_angle` and `perpendicular`.
+pub trait Vec2Ext {
+    /// A unit vector pointing in the direction of `angle`.
+    fn from_angle(angle: f32) -> Self;
+
+    /// The angle between the positive x axis and this vector, in (-PI, PI].
+    fn to_angle(&self) -> f32;
+
+    /// This vector rotated counter-clockwise by `theta`.
+    fn rotated(&self, theta: f32) -> Self;
+
+    /// This vector rotated counter-clockwise by a quarter turn.
+    fn perpendicular(&self) -> Self;
+}
+
+impl Vec2Ext for Vec2 {
+    fn from_angle(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(cos, sin)
+    }
+
+    fn to_angle(&self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    fn rotated(&self, theta: f32) -> Self {
+        Rot2::new(theta) * *self
+    }
+
+    fn perpendicular(&self) -> Self {
+        Vec2::new(-self.y, self.x)
+    }
+}
+
+/// Wrap an angle into the range (-PI, PI].
+pub fn wrap_angle(angle: f32) -> f32 {
+    let wrapped = (angle + PI).rem_euclid(TAU) - PI;
+    if wrapped == -PI {
+        PI
+    } else {
+        wrapped
+    }
+}
+
+/// The shortest signed rotation which turns `from` into `to`, in (-PI, PI].
+///
+/// Positive values are counter-clockwise. Useful for steering toward a
+/// target heading without spinning the long way around.
+pub fn angle_difference(from: f32, to: f32) -> f32 {
+    wrap_angle(to - from)
+}
+
+/// Interpolate between two angles along the shortest path.
+pub fn lerp_angle(from: f32, to: f32, t: f32) -> f32 {
+    wrap_angle(from + angle_difference(from, to) * t)
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, approx::assert_relative_eq, std::f32::consts::FRAC_PI_2};
+
+    #[test]
+    fn test_rotation_and_wrapping() {
+        let v = Vec2::from_angle(FRAC_PI_2) * 2.0;
+        assert_relative_eq!(v, Vec2::new(0.0, 2.0), epsilon = 1e-6);
+        assert_relative_eq!(v.to_angle(), FRAC_PI_2);
+        assert_relative_eq!(
+            v.rotated(FRAC_PI_2),
+            v.perpendicular(),
+            epsilon = 1e-6
+        );
+
+        assert_relative_eq!(wrap_angle(3.0 * PI), PI, epsilon = 1e-5);
+        assert_relative_eq!(
+            angle_difference(0.1, TAU - 0.1),
+            -0.2,
+            epsilon = 1e-5
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! SIMD operations over slices of points.
+//!
+//! Each function processes four `Vec2`s at a time as a single `f32x8`, then
+//! finishes any leftover points one at a time. Use these for hot loops over
+//! large particle systems where positions and velocities are stored in
+//! separate slices.
+
+use {
+    super::{Mat3, Rect, Vec2},
+    wide::f32x8,
+};
+
+/// The number of Vec2s packed into one f32x8.
+const POINTS_PER_LANE: usize = 4;
+
+/// Move every point by `offset`.
+pub fn translate(points: &mut [Vec2], offset: Vec2) {
+    let offset_lanes = repeat(offset.x, offset.y);
+    let (lanes, rest) = as_lanes_mut(points);
+    for lane in lanes {
+        store(lane, load(lane) + offset_lanes);
+    }
+    for point in rest {
+        *point += offset;
+    }
+}
+
+/// Multiply every point by `factor`.
+pub fn scale(points: &mut [Vec2], factor: f32) {
+    let (lanes, rest) = as_lanes_mut(points);
+    for lane in lanes {
+        store(lane, load(lane) * factor);
+    }
+    for point in rest {
+        *point *= factor;
+    }
+}
+
+/// Apply a homogeneous 2D transform, like `Transform2D::to_homogeneous`, to
+/// every point.
+///
+/// The bottom row of the matrix is ignored, so projective transforms are not
+/// supported.
+pub fn transform(points: &mut [Vec2], matrix: &Mat3) {
+    let m = matrix;
+    let diagonal = repeat(m[(0, 0)], m[(1, 1)]);
+    let off_diagonal = repeat(m[(0, 1)], m[(1, 0)]);
+    let translation = repeat(m[(0, 2)], m[(1, 2)]);
+
+    let (lanes, rest) = as_lanes_mut(points);
+    for lane in lanes {
+        let xy = load(lane);
+        let yx = f32x8::from(swap_pairs(lane));
+        let result =
+            xy.mul_add(diagonal, yx.mul_add(off_diagonal, translation));
+        store(lane, result);
+    }
+    for point in rest {
+        *point = m.transform_point(&(*point).into()).coords;
+    }
+}
+
+/// Clamp every point so it lies inside `bounds`.
+///
+/// Coordinates which are NaN move to the bounds' minimum.
+pub fn clamp(points: &mut [Vec2], bounds: &Rect) {
+    let min = repeat(bounds.min.x, bounds.min.y);
+    let max = repeat(bounds.max.x, bounds.max.y);
+    let (lanes, rest) = as_lanes_mut(points);
+    for lane in lanes {
+        // The fast min and max handle NaN differently on each platform, so
+        // replace NaN first to match the scalar loop below.
+        let xy = load(lane);
+        let xy = xy.is_nan().blend(min, xy);
+        store(lane, xy.fast_max(min).fast_min(max));
+    }
+    for point in rest {
+        *point = point.sup(&bounds.min).inf(&bounds.max);
+    }
+}
+
+/// Advance each position by its velocity: `position += velocity * dt`.
+///
+/// Panics if the slices have different lengths.
+pub fn integrate(positions: &mut [Vec2], velocities: &[Vec2], dt: f32) {
+    assert_eq!(
+        positions.len(),
+        velocities.len(),
+        "Every position needs a velocity"
+    );
+    let dt_lanes = f32x8::splat(dt);
+    let (position_lanes, position_rest) = as_lanes_mut(positions);
+    let (velocity_lanes, velocity_rest) = as_lanes(velocities);
+    for (position, velocity) in position_lanes.iter_mut().zip(velocity_lanes) {
+        let velocity = f32x8::from(*velocity);
+        store(position, velocity.mul_add(dt_lanes, load(position)));
+    }
+    for (position, velocity) in position_rest.iter_mut().zip(velocity_rest) {
+        *position += velocity * dt;
+    }
+}
+
+// Private API
+// -----------
+
+fn repeat(x: f32, y: f32) -> f32x8 {
+    f32x8::from([x, y, x, y, x, y, x, y])
+}
+
+fn load(lane: &[f32; 8]) -> f32x8 {
+    f32x8::from(*lane)
+}
+
+fn store(lane: &mut [f32; 8], value: f32x8) {
+    *lane = value.to_array();
+}
+
+fn swap_pairs(lane: &[f32; 8]) -> [f32; 8] {
+    [
+        lane[1], lane[0], lane[3], lane[2], lane[5], lane[4], lane[7], lane[6],
+    ]
+}
+
+/// Split points into groups of four, viewed as arrays of eight floats, and
+/// the leftover points.
+fn as_lanes_mut(points: &mut [Vec2]) -> (&mut [[f32; 8]], &mut [Vec2]) {
+    let lane_count = points.len() / POINTS_PER_LANE;
+    let (packed, rest) = points.split_at_mut(lane_count * POINTS_PER_LANE);
+    // SAFETY: Vec2 is a repr(C) wrapper around [f32; 2], so four Vec2s have
+    // the same size and alignment as [f32; 8]. The packed slice's length is a
+    // multiple of four.
+    let lanes = unsafe {
+        std::slice::from_raw_parts_mut(
+            packed.as_mut_ptr() as *mut [f32; 8],
+            lane_count,
+        )
+    };
+    (lanes, rest)
+}
+
+/// The read-only version of `as_lanes_mut`.
+fn as_lanes(points: &[Vec2]) -> (&[[f32; 8]], &[Vec2]) {
+    let lane_count = points.len() / POINTS_PER_LANE;
+    let (packed, rest) = points.split_at(lane_count * POINTS_PER_LANE);
+    // SAFETY: see as_lanes_mut.
+    let lanes = unsafe {
+        std::slice::from_raw_parts(
+            packed.as_ptr() as *const [f32; 8],
+            lane_count,
+        )
+    };
+    (lanes, rest)
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::math::Transform2D, approx::assert_relative_eq};
+
+    #[test]
+    fn test_batch_matches_scalar() {
+        let original: Vec<Vec2> = (0..7)
+            .map(|i| Vec2::new(i as f32, 10.0 - 3.0 * i as f32))
+            .collect();
+        let matrix =
+            Transform2D::new(Vec2::new(5.0, -2.0), 0.6, Vec2::new(2.0, 0.5))
+                .to_homogeneous();
+
+        let mut points = original.clone();
+        transform(&mut points, &matrix);
+        integrate(&mut points, &original, 0.5);
+        clamp(
+            &mut points,
+            &Rect::new(Vec2::new(-5.0, -5.0), Vec2::new(5.0, 5.0)),
+        );
+
+        for (point, original) in points.iter().zip(&original) {
+            let expected = (matrix.transform_point(&(*original).into()).coords
+                + original * 0.5)
+                .sup(&Vec2::new(-5.0, -5.0))
+                .inf(&Vec2::new(5.0, 5.0));
+            assert_relative_eq!(*point, expected, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_clamp_moves_nan_to_min() {
+        let mut points = vec![Vec2::new(1.0, 2.0); 7];
+        points[1].x = f32::NAN;
+        points[6].y = f32::NAN;
+        let bounds = Rect::new(Vec2::new(-5.0, -4.0), Vec2::new(5.0, 4.0));
+
+        clamp(&mut points, &bounds);
+
+        assert_eq!(points[1], Vec2::new(-5.0, 2.0));
+        assert_eq!(points[6], Vec2::new(1.0, -4.0));
+    }
+}
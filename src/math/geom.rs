@@ -0,0 +1,158 @@
+//! Intersection tests for segments, rays, and circles.
+
+use super::Vec2;
+
+/// The 2D cross product, or the z component of the 3D cross product of two
+/// vectors in the xy plane.
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+/// The point on the segment from `a` to `b` which is closest to `point`.
+pub fn closest_point_on_segment(point: Vec2, a: Vec2, b: Vec2) -> Vec2 {
+    let ab = b - a;
+    let length_squared = ab.magnitude_squared();
+    if length_squared == 0.0 {
+        return a;
+    }
+    let t = ((point - a).dot(&ab) / length_squared).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+/// The point where the segment from `a0` to `a1` crosses the segment from
+/// `b0` to `b1`.
+///
+/// Returns None if the segments don't touch. Parallel segments never
+/// intersect, even if they overlap.
+pub fn segment_intersection(
+    a0: Vec2,
+    a1: Vec2,
+    b0: Vec2,
+    b1: Vec2,
+) -> Option<Vec2> {
+    let da = a1 - a0;
+    let db = b1 - b0;
+    let denominator = cross(da, db);
+    if denominator.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    let offset = b0 - a0;
+    let t = cross(offset, db) / denominator;
+    let u = cross(offset, da) / denominator;
+    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+        Some(a0 + da * t)
+    } else {
+        None
+    }
+}
+
+/// Find where a ray first hits a circle.
+///
+/// Returns the distance along the ray, measured in multiples of `direction`,
+/// so `origin + direction * t` is the hit point. A ray which starts inside
+/// the circle hits it at t = 0. Returns None if the ray misses.
+///
+/// # Params
+///
+/// * `origin` - the start of the ray
+/// * `direction` - the ray's direction, which does not need to be normalized
+/// * `center` - the circle's center
+/// * `radius` - the circle's radius
+pub fn ray_circle(
+    origin: Vec2,
+    direction: Vec2,
+    center: Vec2,
+    radius: f32,
+) -> Option<f32> {
+    let offset = origin - center;
+    let c = offset.magnitude_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+
+    let a = direction.magnitude_squared();
+    let b = offset.dot(&direction);
+    let discriminant = b * b - a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / a;
+    if t >= 0.0 {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// How far two circles overlap.
+///
+/// Returns None if the circles don't overlap. Otherwise returns the distance
+/// the circles need to move apart along the line between their centers to
+/// just touch.
+pub fn circle_overlap(
+    center_a: Vec2,
+    radius_a: f32,
+    center_b: Vec2,
+    radius_b: f32,
+) -> Option<f32> {
+    let depth = radius_a + radius_b - (center_b - center_a).magnitude();
+    if depth > 0.0 {
+        Some(depth)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, approx::assert_relative_eq};
+
+    #[test]
+    fn test_segment_and_ray_intersections() {
+        let hit = segment_intersection(
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(-1.0, 1.0),
+            Vec2::new(1.0, -1.0),
+        );
+        assert_relative_eq!(hit.unwrap(), Vec2::zeros());
+        assert!(segment_intersection(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, -1.0),
+            Vec2::new(2.0, 1.0),
+        )
+        .is_none());
+
+        let t = ray_circle(
+            Vec2::new(-10.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::zeros(),
+            1.0,
+        );
+        assert_relative_eq!(t.unwrap(), 4.5);
+        assert!(ray_circle(
+            Vec2::new(-10.0, 0.0),
+            Vec2::new(-1.0, 0.0),
+            Vec2::zeros(),
+            1.0
+        )
+        .is_none());
+
+        assert_relative_eq!(
+            closest_point_on_segment(
+                Vec2::new(3.0, 2.0),
+                Vec2::zeros(),
+                Vec2::new(2.0, 0.0)
+            ),
+            Vec2::new(2.0, 0.0)
+        );
+        assert_relative_eq!(
+            circle_overlap(Vec2::zeros(), 1.0, Vec2::new(1.5, 0.0), 1.0)
+                .unwrap(),
+            0.5
+        );
+    }
+}
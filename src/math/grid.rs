@@ -0,0 +1,279 @@
+use {
+    super::Vec2,
+    std::ops::{Add, Index, IndexMut, Mul},
+};
+
+/// Offsets to the four cells which share an edge with a cell.
+const NEIGHBORS_4: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+/// Offsets to the eight cells which share an edge or a corner with a cell.
+const NEIGHBORS_8: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// A 2D grid of cells stored in a flat Vec, row by row.
+///
+/// Cells are addressed by (x, y) with (0, 0) at the bottom left. The grid
+/// also covers a region of world space so sketches can map between cells and
+/// world positions. By default each cell is one unit wide and the grid is
+/// centered on the origin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid2<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+
+    /// The world position of the grid's bottom left corner.
+    origin: Vec2,
+    cell_size: f32,
+}
+
+// Public API
+// ----------
+
+impl<T: Clone> Grid2<T> {
+    /// Create a grid with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self::from_cells(width, height, vec![value; width * height])
+    }
+}
+
+impl<T> Grid2<T> {
+    /// Create a grid by calling `f(x, y)` for every cell.
+    pub fn from_fn<F>(width: usize, height: usize, mut f: F) -> Self
+    where
+        F: FnMut(usize, usize) -> T,
+    {
+        let cells = (0..width * height)
+            .map(|index| f(index % width, index / width))
+            .collect();
+        Self::from_cells(width, height, cells)
+    }
+
+    /// Set the size of each cell in world units. The grid stays centered on
+    /// the same point.
+    pub fn with_cell_size(mut self, cell_size: f32) -> Self {
+        let center = self.world_center();
+        self.cell_size = cell_size;
+        self.with_center(center)
+    }
+
+    /// Move the grid so its center is at `center` in world space.
+    pub fn with_center(mut self, center: Vec2) -> Self {
+        self.origin = center - self.world_size() * 0.5;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// The size of the whole grid in world units.
+    pub fn world_size(&self) -> Vec2 {
+        Vec2::new(self.width as f32, self.height as f32) * self.cell_size
+    }
+
+    /// The center of the grid in world space.
+    pub fn world_center(&self) -> Vec2 {
+        self.origin + self.world_size() * 0.5
+    }
+
+    /// The cells in row order, starting with the bottom row.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.index_of(x, y).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.index_of(x, y).map(move |index| &mut self.cells[index])
+    }
+
+    /// Get a cell, wrapping coordinates which fall off one edge of the grid
+    /// around to the other edge.
+    ///
+    /// Panics if the grid is empty.
+    pub fn get_wrapped(&self, x: isize, y: isize) -> &T {
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+        &self[(x, y)]
+    }
+
+    /// The index into `cells()` for (x, y), or None if the coordinates are
+    /// outside of the grid.
+    pub fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// The (x, y) coordinates for an index into `cells()`.
+    pub fn coords_of(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
+    }
+
+    /// The world position of the center of cell (x, y).
+    pub fn cell_center(&self, x: usize, y: usize) -> Vec2 {
+        self.origin
+            + (Vec2::new(x as f32, y as f32) + Vec2::new(0.5, 0.5))
+                * self.cell_size
+    }
+
+    /// The cell which contains a world position, or None if the position is
+    /// outside of the grid.
+    pub fn world_to_cell(&self, pos: Vec2) -> Option<(usize, usize)> {
+        let local = (pos - self.origin) / self.cell_size;
+        if local.x < 0.0 || local.y < 0.0 {
+            return None;
+        }
+        let (x, y) = (local.x as usize, local.y as usize);
+        self.index_of(x, y).map(|_| (x, y))
+    }
+
+    /// Iterate over every cell along with its coordinates.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(index, cell)| (index % width, index / width, cell))
+    }
+
+    /// The coordinates of the cells which share an edge with (x, y). Cells
+    /// outside of the grid are skipped.
+    pub fn neighbors4(
+        &self,
+        x: usize,
+        y: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offset_cells(x, y, &NEIGHBORS_4)
+    }
+
+    /// The coordinates of the cells which share an edge or a corner with
+    /// (x, y). Cells outside of the grid are skipped.
+    pub fn neighbors8(
+        &self,
+        x: usize,
+        y: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offset_cells(x, y, &NEIGHBORS_8)
+    }
+
+    /// Sample the grid at a world position by blending the four nearest cell
+    /// centers.
+    ///
+    /// Positions outside of the grid use the value of the nearest edge cell.
+    /// Panics if the grid is empty.
+    pub fn sample_bilinear(&self, pos: Vec2) -> T
+    where
+        T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+    {
+        let half_cell = Vec2::new(0.5, 0.5);
+        let local = (pos - self.origin) / self.cell_size - half_cell;
+        let max_x = (self.width - 1) as f32;
+        let max_y = (self.height - 1) as f32;
+        let x = local.x.clamp(0.0, max_x);
+        let y = local.y.clamp(0.0, max_y);
+
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) =
+            ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (tx, ty) = (x.fract(), y.fract());
+
+        let bottom = self[(x0, y0)] * (1.0 - tx) + self[(x1, y0)] * tx;
+        let top = self[(x0, y1)] * (1.0 - tx) + self[(x1, y1)] * tx;
+        bottom * (1.0 - ty) + top * ty
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid2<T> {
+    type Output = T;
+
+    /// Panics if the coordinates are outside of the grid.
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y)
+            .unwrap_or_else(|| panic!("({}, {}) is outside of the grid", x, y))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid2<T> {
+    /// Panics if the coordinates are outside of the grid.
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y)
+            .unwrap_or_else(|| panic!("({}, {}) is outside of the grid", x, y))
+    }
+}
+
+// Private API
+// -----------
+
+impl<T> Grid2<T> {
+    fn from_cells(width: usize, height: usize, cells: Vec<T>) -> Self {
+        Self {
+            width,
+            height,
+            cells,
+            origin: Vec2::zeros(),
+            cell_size: 1.0,
+        }
+        .with_center(Vec2::zeros())
+    }
+
+    fn offset_cells<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        offsets.iter().filter_map(move |&(dx, dy)| {
+            let nx = x.checked_add_signed(dx)?;
+            let ny = y.checked_add_signed(dy)?;
+            self.index_of(nx, ny).map(|_| (nx, ny))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, approx::assert_relative_eq};
+
+    #[test]
+    fn test_mapping_and_sampling() {
+        let grid = Grid2::from_fn(4, 2, |x, y| (x + 10 * y) as f32)
+            .with_cell_size(2.0)
+            .with_center(Vec2::new(4.0, 2.0));
+
+        assert_eq!(grid.index_of(3, 1), Some(7));
+        assert_eq!(grid.coords_of(7), (3, 1));
+        assert_relative_eq!(grid.cell_center(0, 0), Vec2::new(1.0, 1.0));
+        assert_eq!(grid.world_to_cell(Vec2::new(7.9, 3.9)), Some((3, 1)));
+        assert_eq!(grid.world_to_cell(Vec2::new(-0.1, 1.0)), None);
+        assert_eq!(grid.neighbors8(0, 0).count(), 3);
+
+        assert_relative_eq!(grid.sample_bilinear(Vec2::new(2.0, 2.0)), 5.5);
+        assert_relative_eq!(grid.sample_bilinear(Vec2::new(-5.0, 0.0)), 0.0);
+    }
+}
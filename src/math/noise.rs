@@ -0,0 +1,363 @@
+//! Seeded gradient noise.
+//!
+//! Every function returns values roughly in the range [-1, 1] and is
+//! continuous, so sampling nearby points gives nearby values.
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+/// Gradients for 2D and 3D simplex noise. The midpoints of a cube's edges.
+#[rustfmt::skip]
+const GRAD3: [[f32; 3]; 12] = [
+    [ 1.0,  1.0,  0.0], [-1.0,  1.0,  0.0], [ 1.0, -1.0,  0.0], [-1.0, -1.0,  0.0],
+    [ 1.0,  0.0,  1.0], [-1.0,  0.0,  1.0], [ 1.0,  0.0, -1.0], [-1.0,  0.0, -1.0],
+    [ 0.0,  1.0,  1.0], [ 0.0, -1.0,  1.0], [ 0.0,  1.0, -1.0], [ 0.0, -1.0, -1.0],
+];
+
+/// A seeded source of Perlin and simplex noise.
+///
+/// Two instances created with the same seed always produce the same values.
+#[derive(Clone)]
+pub struct Noise {
+    perm: [usize; 512],
+}
+
+/// Settings for fractal Brownian motion, which sums several octaves of noise
+/// at increasing frequency and decreasing amplitude.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Fbm {
+    /// The number of layers of noise to sum.
+    pub octaves: u32,
+
+    /// How much the frequency increases with each octave.
+    pub lacunarity: f32,
+
+    /// How much the amplitude decreases with each octave.
+    pub gain: f32,
+}
+
+impl Default for Fbm {
+    fn default() -> Self {
+        Self {
+            octaves: 4,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+}
+
+impl Fbm {
+    /// Sum octaves of any noise function.
+    ///
+    /// # Params
+    ///
+    /// * `noise` - called with the frequency for each octave. It should sample
+    ///   noise at the point multiplied by the frequency.
+    pub fn sample(&self, noise: impl Fn(f32) -> f32) -> f32 {
+        let mut total = 0.0;
+        let mut total_amplitude = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        for _ in 0..self.octaves {
+            total += amplitude * noise(frequency);
+            total_amplitude += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+        if total_amplitude > 0.0 {
+            total / total_amplitude
+        } else {
+            0.0
+        }
+    }
+}
+
+// Public API
+// ----------
+
+impl Noise {
+    /// Create a new noise source with a shuffled permutation table.
+    pub fn new(seed: u64) -> Self {
+        let mut table: Vec<usize> = (0..256).collect();
+        table.shuffle(&mut StdRng::seed_from_u64(seed));
+
+        let mut perm = [0; 512];
+        for (i, value) in perm.iter_mut().enumerate() {
+            *value = table[i % 256];
+        }
+        Self { perm }
+    }
+
+    pub fn perlin1(&self, x: f32) -> f32 {
+        let (xi, x) = split(x);
+        let u = fade(x);
+        let p = &self.perm;
+        0.25 * lerp(u, grad1(p[xi], x), grad1(p[xi + 1], x - 1.0))
+    }
+
+    pub fn perlin2(&self, x: f32, y: f32) -> f32 {
+        let (xi, x) = split(x);
+        let (yi, y) = split(y);
+        let (u, v) = (fade(x), fade(y));
+        let p = &self.perm;
+        let (a, b) = (p[xi] + yi, p[xi + 1] + yi);
+        lerp(
+            v,
+            lerp(u, grad2(p[a], x, y), grad2(p[b], x - 1.0, y)),
+            lerp(
+                u,
+                grad2(p[a + 1], x, y - 1.0),
+                grad2(p[b + 1], x - 1.0, y - 1.0),
+            ),
+        )
+    }
+
+    pub fn perlin3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (xi, x) = split(x);
+        let (yi, y) = split(y);
+        let (zi, z) = split(z);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+        let p = &self.perm;
+        let a = p[xi] + yi;
+        let (aa, ab) = (p[a] + zi, p[a + 1] + zi);
+        let b = p[xi + 1] + yi;
+        let (ba, bb) = (p[b] + zi, p[b + 1] + zi);
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad3(p[aa], x, y, z), grad3(p[ba], x - 1.0, y, z)),
+                lerp(
+                    u,
+                    grad3(p[ab], x, y - 1.0, z),
+                    grad3(p[bb], x - 1.0, y - 1.0, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad3(p[aa + 1], x, y, z - 1.0),
+                    grad3(p[ba + 1], x - 1.0, y, z - 1.0),
+                ),
+                lerp(
+                    u,
+                    grad3(p[ab + 1], x, y - 1.0, z - 1.0),
+                    grad3(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+
+    pub fn simplex1(&self, x: f32) -> f32 {
+        let (i0, x0) = split(x);
+        let x1 = x0 - 1.0;
+        let corner = |hash: usize, x: f32| {
+            let t = 1.0 - x * x;
+            t * t * t * t * grad1(hash, x)
+        };
+        0.395 * (corner(self.perm[i0], x0) + corner(self.perm[i0 + 1], x1))
+    }
+
+    pub fn simplex2(&self, x: f32, y: f32) -> f32 {
+        let f2 = 0.5 * (3.0_f32.sqrt() - 1.0);
+        let g2 = (3.0 - 3.0_f32.sqrt()) / 6.0;
+
+        // Skew the input space to find the containing simplex.
+        let s = (x + y) * f2;
+        let (i, j) = ((x + s).floor(), (y + s).floor());
+        let t = (i + j) * g2;
+        let (x0, y0) = (x - (i - t), y - (j - t));
+
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let (x1, y1) = (x0 - i1 as f32 + g2, y0 - j1 as f32 + g2);
+        let (x2, y2) = (x0 - 1.0 + 2.0 * g2, y0 - 1.0 + 2.0 * g2);
+
+        let (ii, jj) = (wrap(i), wrap(j));
+        let p = &self.perm;
+        let corner = |hash: usize, x: f32, y: f32| {
+            let t = 0.5 - x * x - y * y;
+            if t < 0.0 {
+                0.0
+            } else {
+                let g = GRAD3[hash % 12];
+                t * t * t * t * (g[0] * x + g[1] * y)
+            }
+        };
+        70.0 * (corner(p[ii + p[jj]], x0, y0)
+            + corner(p[ii + i1 + p[jj + j1]], x1, y1)
+            + corner(p[ii + 1 + p[jj + 1]], x2, y2))
+    }
+
+    pub fn simplex3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let f3 = 1.0 / 3.0;
+        let g3 = 1.0 / 6.0;
+
+        // Skew the input space to find the containing simplex.
+        let s = (x + y + z) * f3;
+        let (i, j, k) = ((x + s).floor(), (y + s).floor(), (z + s).floor());
+        let t = (i + j + k) * g3;
+        let (x0, y0, z0) = (x - (i - t), y - (j - t), z - (k - t));
+
+        // Find which of the six tetrahedra contains the point.
+        let ((i1, j1, k1), (i2, j2, k2)) = if x0 >= y0 {
+            if y0 >= z0 {
+                ((1, 0, 0), (1, 1, 0))
+            } else if x0 >= z0 {
+                ((1, 0, 0), (1, 0, 1))
+            } else {
+                ((0, 0, 1), (1, 0, 1))
+            }
+        } else if y0 < z0 {
+            ((0, 0, 1), (0, 1, 1))
+        } else if x0 < z0 {
+            ((0, 1, 0), (0, 1, 1))
+        } else {
+            ((0, 1, 0), (1, 1, 0))
+        };
+
+        let offset = |corner: (usize, usize, usize), amount: f32| {
+            (
+                x0 - corner.0 as f32 + amount,
+                y0 - corner.1 as f32 + amount,
+                z0 - corner.2 as f32 + amount,
+            )
+        };
+        let c1 = offset((i1, j1, k1), g3);
+        let c2 = offset((i2, j2, k2), 2.0 * g3);
+        let c3 = offset((1, 1, 1), 3.0 * g3);
+
+        let (ii, jj, kk) = (wrap(i), wrap(j), wrap(k));
+        let p = &self.perm;
+        let hash = |di: usize, dj: usize, dk: usize| {
+            p[ii + di + p[jj + dj + p[kk + dk]]]
+        };
+        let corner = |hash: usize, (x, y, z): (f32, f32, f32)| {
+            let t = 0.6 - x * x - y * y - z * z;
+            if t < 0.0 {
+                0.0
+            } else {
+                let g = GRAD3[hash % 12];
+                t * t * t * t * (g[0] * x + g[1] * y + g[2] * z)
+            }
+        };
+        32.0 * (corner(hash(0, 0, 0), (x0, y0, z0))
+            + corner(hash(i1, j1, k1), c1)
+            + corner(hash(i2, j2, k2), c2)
+            + corner(hash(1, 1, 1), c3))
+    }
+
+    /// Fractal Brownian motion built from 1D Perlin noise.
+    pub fn fbm1(&self, x: f32, fbm: &Fbm) -> f32 {
+        fbm.sample(|frequency| self.perlin1(x * frequency))
+    }
+
+    /// Fractal Brownian motion built from 2D Perlin noise.
+    pub fn fbm2(&self, x: f32, y: f32, fbm: &Fbm) -> f32 {
+        fbm.sample(|frequency| self.perlin2(x * frequency, y * frequency))
+    }
+
+    /// Fractal Brownian motion built from 3D Perlin noise.
+    pub fn fbm3(&self, x: f32, y: f32, z: f32, fbm: &Fbm) -> f32 {
+        fbm.sample(|frequency| {
+            self.perlin3(x * frequency, y * frequency, z * frequency)
+        })
+    }
+}
+
+// Private API
+// -----------
+
+/// Split a coordinate into the wrapped lattice cell and the offset within the
+/// cell.
+fn split(x: f32) -> (usize, f32) {
+    let floor = x.floor();
+    (wrap(floor), x - floor)
+}
+
+fn wrap(floor: f32) -> usize {
+    (floor as i32 & 255) as usize
+}
+
+/// Perlin's quintic smoothstep.
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+fn grad1(hash: usize, x: f32) -> f32 {
+    let h = hash & 15;
+    let grad = 1.0 + (h & 7) as f32;
+    if h & 8 == 0 {
+        grad * x
+    } else {
+        -grad * x
+    }
+}
+
+fn grad2(hash: usize, x: f32, y: f32) -> f32 {
+    match hash & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+fn grad3(hash: usize, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    let u = if h & 1 == 0 { u } else { -u };
+    let v = if h & 2 == 0 { v } else { -v };
+    u + v
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_noise() {
+        let a = Noise::new(42);
+        let b = Noise::new(42);
+        for i in 0..100 {
+            let x = i as f32 * 0.37 - 18.0;
+            assert_eq!(a.perlin2(x, -x), b.perlin2(x, -x));
+            assert_eq!(a.simplex3(x, 0.5, x), b.simplex3(x, 0.5, x));
+        }
+    }
+
+    #[test]
+    fn test_noise_is_bounded() {
+        let noise = Noise::new(7);
+        let fbm = Fbm::default();
+        for i in 0..2000 {
+            let x = i as f32 * 0.173 - 150.0;
+            let y = i as f32 * -0.291 + 40.0;
+            for value in [
+                noise.perlin1(x),
+                noise.perlin2(x, y),
+                noise.perlin3(x, y, x * 0.5),
+                noise.simplex1(x),
+                noise.simplex2(x, y),
+                noise.simplex3(x, y, x * 0.5),
+                noise.fbm2(x, y, &fbm),
+            ] {
+                assert!((-1.05..=1.05).contains(&value), "{}", value);
+            }
+        }
+    }
+}
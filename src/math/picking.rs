@@ -0,0 +1,112 @@
+//! Map between screen and world coordinates and find the shape under a point.
+//!
+//! Screen coordinates are in pixels with the origin at the top left of the
+//! window and y pointing down, matching the cursor position reported by the
+//! window system. World coordinates are whatever space a projection maps
+//! into Vulkan device coordinates, like the matrices in `math::projection`.
+
+use super::{polygon, Mat4, Rect, Vec2, Vec3};
+
+/// A shape which can be hit-tested.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Rect(Rect),
+    Circle { center: Vec2, radius: f32 },
+    Polygon(Vec<Vec2>),
+}
+
+impl Shape {
+    /// Check if a point in world coordinates is inside the shape.
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Shape::Rect(rect) => rect.contains(point),
+            Shape::Circle { center, radius } => {
+                (point - center).magnitude_squared() <= radius * radius
+            }
+            Shape::Polygon(vertices) => {
+                polygon::contains_point(vertices, point)
+            }
+        }
+    }
+}
+
+/// Find the shape under a point.
+///
+/// Shapes later in the slice are treated as drawn on top, so the last shape
+/// which contains the point wins. Returns the shape's index.
+pub fn pick(shapes: &[Shape], point: Vec2) -> Option<usize> {
+    shapes.iter().rposition(|shape| shape.contains(point))
+}
+
+/// Convert a screen position to world coordinates.
+///
+/// Returns None if the projection can't be inverted.
+///
+/// # Params
+///
+/// * `screen` - the position in screen coordinates, usually the cursor
+/// * `viewport` - the region of the screen the projection renders into
+/// * `projection` - the matrix which maps world coordinates to device
+///   coordinates
+pub fn screen_to_world(
+    screen: Vec2,
+    viewport: &Rect,
+    projection: &Mat4,
+) -> Option<Vec2> {
+    let ndc = (screen - viewport.min).component_div(&viewport.size()) * 2.0
+        - Vec2::new(1.0, 1.0);
+    let inverse = projection.try_inverse()?;
+    let world = inverse.transform_point(&Vec3::new(ndc.x, ndc.y, 0.0).into());
+    Some(world.coords.xy())
+}
+
+/// Convert a world position to screen coordinates.
+///
+/// The inverse of `screen_to_world`.
+pub fn world_to_screen(
+    world: Vec2,
+    viewport: &Rect,
+    projection: &Mat4,
+) -> Vec2 {
+    let ndc = projection
+        .transform_point(&Vec3::new(world.x, world.y, 0.0).into())
+        .coords
+        .xy();
+    viewport.min
+        + (ndc + Vec2::new(1.0, 1.0)).component_mul(&viewport.size()) * 0.5
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*, crate::math::projection::symmetric_ortho,
+        approx::assert_relative_eq,
+    };
+
+    #[test]
+    fn test_screen_to_world_and_pick() {
+        let projection = symmetric_ortho(800.0, 600.0, 0.0, 1.0);
+        let viewport = Rect::new(Vec2::zeros(), Vec2::new(800.0, 600.0));
+
+        let world =
+            screen_to_world(Vec2::new(100.0, 50.0), &viewport, &projection)
+                .unwrap();
+        assert_relative_eq!(world, Vec2::new(-300.0, 250.0));
+        assert_relative_eq!(
+            world_to_screen(world, &viewport, &projection),
+            Vec2::new(100.0, 50.0),
+            epsilon = 1e-4
+        );
+
+        let shapes = [
+            Shape::Rect(Rect::from_center(world, Vec2::new(10.0, 10.0))),
+            Shape::Circle {
+                center: world + Vec2::new(4.0, 0.0),
+                radius: 1.0,
+            },
+        ];
+        assert_eq!(pick(&shapes, world), Some(0));
+        assert_eq!(pick(&shapes, world + Vec2::new(4.5, 0.0)), Some(1));
+        assert_eq!(pick(&shapes, Vec2::zeros()), None);
+    }
+}
@@ -0,0 +1,129 @@
+//! Utilities for polygons stored as a slice of vertices.
+//!
+//! Polygons are closed, so the last vertex connects back to the first. The
+//! vertices can wind in either direction.
+
+use super::Vec2;
+
+/// The 2D cross product of `b - a` and `c - a`. Positive when `a`, `b`, `c`
+/// turn counter-clockwise.
+fn turn(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+    let ab = b - a;
+    let ac = c - a;
+    ab.x * ac.y - ab.y * ac.x
+}
+
+/// Iterate over each edge of the polygon as a pair of vertices.
+fn edges(polygon: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+}
+
+/// Check if a point is inside the polygon using the even-odd rule.
+///
+/// Works for concave and self-intersecting polygons. Points exactly on an
+/// edge may be reported as inside or outside.
+pub fn contains_point(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for (a, b) in edges(polygon) {
+        if (a.y > point.y) != (b.y > point.y) {
+            let x = a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// The polygon's area. Positive when the vertices wind counter-clockwise and
+/// negative when they wind clockwise.
+pub fn signed_area(polygon: &[Vec2]) -> f32 {
+    let twice_area: f32 =
+        edges(polygon).map(|(a, b)| a.x * b.y - b.x * a.y).sum();
+    twice_area * 0.5
+}
+
+/// The polygon's center of mass.
+///
+/// Polygons with no area, like a single point or a line, return the average
+/// of their vertices. Returns None for an empty polygon.
+pub fn centroid(polygon: &[Vec2]) -> Option<Vec2> {
+    if polygon.is_empty() {
+        return None;
+    }
+
+    let area = signed_area(polygon);
+    if area.abs() <= f32::EPSILON {
+        let sum: Vec2 = polygon.iter().sum();
+        return Some(sum / polygon.len() as f32);
+    }
+
+    let sum: Vec2 = edges(polygon)
+        .map(|(a, b)| (a + b) * (a.x * b.y - b.x * a.y))
+        .sum();
+    Some(sum / (6.0 * area))
+}
+
+/// The convex hull of a set of points in counter-clockwise order.
+///
+/// Collinear points along the hull's edges are dropped.
+pub fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut sorted = points.to_vec();
+    sorted
+        .sort_by(|a, b| a.x.total_cmp(&b.x).then_with(|| a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    // Andrew's monotone chain: build the lower hull left to right, then the
+    // upper hull right to left.
+    let mut hull: Vec<Vec2> = Vec::with_capacity(sorted.len() + 1);
+    let is_concave = |hull: &[Vec2], point: Vec2| {
+        turn(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+    };
+    for &point in &sorted {
+        while hull.len() >= 2 && is_concave(&hull, point) {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+    let lower_len = hull.len() + 1;
+    for &point in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower_len && is_concave(&hull, point) {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+
+    // The last point is the first point again.
+    hull.pop();
+    hull
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, approx::assert_relative_eq};
+
+    #[test]
+    fn test_square_properties() {
+        let square = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        assert_relative_eq!(signed_area(&square), 4.0);
+        assert_relative_eq!(centroid(&square).unwrap(), Vec2::new(1.0, 1.0));
+        assert!(contains_point(&square, Vec2::new(1.0, 1.5)));
+        assert!(!contains_point(&square, Vec2::new(3.0, 1.0)));
+
+        let mut points = square.to_vec();
+        points.push(Vec2::new(1.0, 1.0));
+        points.push(Vec2::new(1.0, 0.0));
+        assert_eq!(convex_hull(&points), square.to_vec());
+    }
+}
@@ -0,0 +1,109 @@
+//! Projection matrices which map into Vulkan device coordinates.
+//!
+//! Every projection here follows the same conventions:
+//!
+//! * y points up. Vulkan's device coordinates point y down, so the projections
+//!   flip the y axis.
+//! * z points into the screen, so larger z values are further away.
+//! * depth is mapped to [0, 1], Vulkan's default depth range, rather than the
+//!   [-1, 1] range used by OpenGL.
+//!
+//! See 'View Volume' in the glossary: https://registry.khronos.org/vulkan/specs/1.3-extensions/html/vkspec.html#glossary
+
+use super::Mat4;
+
+/// Build an orthographic projection matrix which projects into Vulkan device
+/// coordinates (e.g. x in [-1, 1], y in [-1, 1], and z in [0, 1].
+#[rustfmt::skip]
+pub fn ortho_projection(
+    left: f32,
+    right: f32,
+    bottom: f32,
+    top: f32,
+    znear: f32,
+    zfar: f32,
+) -> Mat4 {
+    let w = right - left;
+    let h = top - bottom;
+    let d = zfar - znear;
+    Mat4::new(
+        2.0 / w,  0.0    , 0.0    , -1.0 * (right + left)/w,
+        0.0    , -2.0 / h, 0.0    , (top + bottom)/h       ,
+        0.0    ,  0.0    , 1.0 / d, -1.0 * znear / d       ,
+        0.0    ,  0.0    , 0.0    , 1.0                    ,
+    )
+}
+
+/// Build an orthographic projection centered on the origin.
+///
+/// This is the projection used for sketches: (0, 0) is the center of the
+/// screen and the visible area is `width` by `height` units.
+pub fn symmetric_ortho(width: f32, height: f32, znear: f32, zfar: f32) -> Mat4 {
+    let half_w = width / 2.0;
+    let half_h = height / 2.0;
+    ortho_projection(-half_w, half_w, -half_h, half_h, znear, zfar)
+}
+
+/// Build a perspective projection with the camera at the origin looking down
+/// the positive z axis.
+///
+/// # Params
+///
+/// * `fov_y` - the vertical field of view in radians
+/// * `aspect` - the viewport's width divided by its height
+/// * `znear` - the distance to the near plane, which maps to depth 0
+/// * `zfar` - the distance to the far plane, which maps to depth 1
+#[rustfmt::skip]
+pub fn perspective_projection(
+    fov_y: f32,
+    aspect: f32,
+    znear: f32,
+    zfar: f32,
+) -> Mat4 {
+    let f = 1.0 / (fov_y / 2.0).tan();
+    let d = zfar - znear;
+    Mat4::new(
+        f / aspect,  0.0, 0.0     , 0.0               ,
+        0.0       , -f  , 0.0     , 0.0               ,
+        0.0       ,  0.0, zfar / d, -znear * zfar / d ,
+        0.0       ,  0.0, 1.0     , 0.0               ,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::math::{Vec3, Vec4},
+        approx::assert_relative_eq,
+    };
+
+    #[test]
+    fn test_ortho_projection() {
+        let left = -20.0;
+        let right = 39.0;
+        let top = 100.0;
+        let bottom = -234.0;
+        let znear = 0.0;
+        let zfar = 100.0;
+        let proj = ortho_projection(left, right, bottom, top, znear, zfar);
+
+        assert_relative_eq!(-1.0, (proj * Vec4::new(left, 0.0, 0.0, 1.0)).x);
+        assert_relative_eq!(1.0, (proj * Vec4::new(right, 0.0, 0.0, 1.0)).x);
+        assert_relative_eq!(-1.0, (proj * Vec4::new(0.0, top, 0.0, 1.0)).y);
+        assert_relative_eq!(1.0, (proj * Vec4::new(0.0, bottom, 0.0, 1.0)).y);
+        assert_relative_eq!(0.0, (proj * Vec4::new(0.0, 0.0, znear, 1.0)).z);
+        assert_relative_eq!(1.0, (proj * Vec4::new(0.0, 0.0, zfar, 1.0)).z);
+    }
+
+    #[test]
+    fn test_perspective_projection() {
+        let proj =
+            perspective_projection(std::f32::consts::FRAC_PI_2, 2.0, 1.0, 10.0);
+        let near = proj.transform_point(&Vec3::new(0.0, 1.0, 1.0).into());
+        let far = proj.transform_point(&Vec3::new(20.0, 0.0, 10.0).into());
+
+        assert_relative_eq!(near.coords, Vec3::new(0.0, -1.0, 0.0));
+        assert_relative_eq!(far.coords, Vec3::new(1.0, 0.0, 1.0));
+    }
+}
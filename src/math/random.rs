@@ -0,0 +1,133 @@
+//! Seeded random number helpers.
+
+use {
+    super::{Vec2, Vec2Ext},
+    rand::{
+        distributions::uniform::{SampleRange, SampleUniform},
+        rngs::StdRng,
+        Rng, RngCore, SeedableRng,
+    },
+};
+
+/// A seeded random number generator.
+///
+/// Sketches get one through `sim.rng`. Reseeding with the same seed replays
+/// the same sequence of values, so a sketch can reproduce an output by
+/// logging its seed. Sequences are only stable for a given version of the
+/// `rand` crate.
+///
+/// Random also implements `rand::RngCore`, so it works anywhere the `rand`
+/// crate expects a generator, like `SliceRandom::shuffle`.
+#[derive(Debug, Clone)]
+pub struct Random {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Create a generator with a seed chosen by the operating system.
+    pub fn from_entropy() -> Self {
+        Self::new(rand::thread_rng().gen())
+    }
+
+    /// Restart the sequence from a new seed.
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+
+    /// The seed used to create the generator or passed to the last call to
+    /// `reseed`.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// A uniform random value in [0, 1).
+    pub fn random(&mut self) -> f32 {
+        self.rng.gen()
+    }
+
+    /// A uniform random value in a range like `0.0..1.0` or `-5..=5`.
+    ///
+    /// Panics if the range is empty.
+    pub fn random_range<T, R>(&mut self, range: R) -> T
+    where
+        T: SampleUniform,
+        R: SampleRange<T>,
+    {
+        self.rng.gen_range(range)
+    }
+
+    /// A normally distributed random value.
+    ///
+    /// # Params
+    ///
+    /// * `mean` - the center of the distribution
+    /// * `std_dev` - the standard deviation of the distribution
+    pub fn random_gaussian(&mut self, mean: f32, std_dev: f32) -> f32 {
+        // Box-Muller transform. u1 is in (0, 1] so the log is finite.
+        let u1 = 1.0 - self.random();
+        let u2 = self.random();
+        let z = (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos();
+        mean + std_dev * z
+    }
+
+    /// A random vector with length 1, uniformly distributed around the unit
+    /// circle.
+    pub fn random_unit_vec2(&mut self) -> Vec2 {
+        Vec2::from_angle(self.random_range(0.0..std::f32::consts::TAU))
+    }
+
+    /// A random boolean which is true with the given probability.
+    pub fn random_bool(&mut self, probability: f64) -> bool {
+        self.rng.gen_bool(probability.clamp(0.0, 1.0))
+    }
+}
+
+impl RngCore for Random {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, approx::assert_relative_eq};
+
+    #[test]
+    fn test_reseed_replays_sequence() {
+        let mut rng = Random::new(1234);
+        let first: Vec<f32> = (0..16).map(|_| rng.random()).collect();
+
+        rng.reseed(1234);
+        let second: Vec<f32> = (0..16).map(|_| rng.random()).collect();
+
+        assert_eq!(first, second);
+        assert_eq!(rng.seed(), 1234);
+        assert_relative_eq!(rng.random_unit_vec2().magnitude(), 1.0);
+    }
+}
@@ -0,0 +1,168 @@
+use {super::Vec2, ash::vk};
+
+/// An axis-aligned rectangle.
+///
+/// The rectangle includes its edges, so a point on the boundary is contained
+/// and rectangles which only share an edge intersect.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    /// The corner with the smallest x and y coordinates.
+    pub min: Vec2,
+
+    /// The corner with the largest x and y coordinates.
+    pub max: Vec2,
+}
+
+impl Default for Rect {
+    fn default() -> Self {
+        Self::new(Vec2::zeros(), Vec2::zeros())
+    }
+}
+
+impl Rect {
+    /// Create a rectangle from two corners. The corners can be given in any
+    /// order.
+    pub fn new(a: Vec2, b: Vec2) -> Self {
+        Self {
+            min: a.inf(&b),
+            max: a.sup(&b),
+        }
+    }
+
+    /// Create a rectangle centered on `center` with the given width and
+    /// height.
+    pub fn from_center(center: Vec2, size: Vec2) -> Self {
+        let half = size.abs() * 0.5;
+        Self {
+            min: center - half,
+            max: center + half,
+        }
+    }
+
+    /// The smallest rectangle which contains every point.
+    ///
+    /// Returns None when there are no points.
+    pub fn from_points(points: &[Vec2]) -> Option<Self> {
+        let (first, rest) = points.split_first()?;
+        Some(
+            rest.iter()
+                .fold(Self::new(*first, *first), |rect, point| Self {
+                    min: rect.min.inf(point),
+                    max: rect.max.sup(point),
+                }),
+        )
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    /// The overlapping region of two rectangles, if there is one.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.intersects(other) {
+            return None;
+        }
+        Some(Self {
+            min: self.min.sup(&other.min),
+            max: self.max.inf(&other.max),
+        })
+    }
+
+    /// The smallest rectangle which contains both rectangles.
+    pub fn union(&self, other: &Rect) -> Rect {
+        Self {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+
+    /// Grow the rectangle by `amount` on every side. Negative amounts shrink
+    /// the rectangle, but never past its center.
+    pub fn inflate(&self, amount: f32) -> Rect {
+        let size = self.size() + Vec2::new(amount, amount) * 2.0;
+        Self::from_center(self.center(), size.sup(&Vec2::zeros()))
+    }
+
+    /// Move the rectangle by `offset`.
+    pub fn translate(&self, offset: Vec2) -> Rect {
+        Self {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+}
+
+/// Convert to a Vulkan rectangle in framebuffer pixels.
+///
+/// The rectangle is rounded outward to whole pixels and clamped so the offset
+/// is never negative.
+impl From<Rect> for vk::Rect2D {
+    fn from(rect: Rect) -> Self {
+        let min = rect.min.map(|v| v.floor().max(0.0));
+        let max = rect.max.map(|v| v.ceil()).sup(&min);
+        vk::Rect2D {
+            offset: vk::Offset2D {
+                x: min.x as i32,
+                y: min.y as i32,
+            },
+            extent: vk::Extent2D {
+                width: (max.x - min.x) as u32,
+                height: (max.y - min.y) as u32,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_intersection_and_union() {
+        let a = Rect::new(Vec2::new(0.0, 0.0), Vec2::new(4.0, 4.0));
+        let b = Rect::from_center(Vec2::new(4.0, 4.0), Vec2::new(4.0, 4.0));
+        let c = Rect::new(Vec2::new(10.0, 10.0), Vec2::new(9.0, 9.0));
+
+        assert!(a.contains(Vec2::new(4.0, 0.0)));
+        assert_eq!(
+            a.intersection(&b),
+            Some(Rect::new(Vec2::new(2.0, 2.0), Vec2::new(4.0, 4.0)))
+        );
+        assert_eq!(a.intersection(&c), None);
+        assert_eq!(
+            a.union(&c),
+            Rect::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0))
+        );
+        assert_eq!(
+            a.inflate(-3.0),
+            Rect::from_center(a.center(), Vec2::zeros())
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use {
+    super::{Mat3, Vec2},
+    std::ops::{Mul, MulAssign},
+};
+
+/// A 2D rotation stored as a unit complex number.
+///
+/// Composing and applying rotations only takes a few multiplies, with no
+/// trig calls, so Rot2 is cheaper than building a rotation matrix when a
+/// sketch rotates many points by the same angle.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rot2 {
+    cos: f32,
+    sin: f32,
+}
+
+impl Default for Rot2 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Rot2 {
+    /// A counter-clockwise rotation by `angle` radians.
+    pub fn new(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self { cos, sin }
+    }
+
+    pub fn identity() -> Self {
+        Self { cos: 1.0, sin: 0.0 }
+    }
+
+    /// The rotation which turns the direction of `from` into the direction
+    /// of `to`.
+    ///
+    /// Returns the identity if either vector is zero.
+    pub fn between(from: Vec2, to: Vec2) -> Self {
+        let cos = from.dot(&to);
+        let sin = from.x * to.y - from.y * to.x;
+        let length = cos.hypot(sin);
+        if length == 0.0 {
+            return Self::identity();
+        }
+        Self {
+            cos: cos / length,
+            sin: sin / length,
+        }
+    }
+
+    /// The rotation angle in radians, in (-PI, PI].
+    pub fn angle(&self) -> f32 {
+        self.sin.atan2(self.cos)
+    }
+
+    pub fn cos(&self) -> f32 {
+        self.cos
+    }
+
+    pub fn sin(&self) -> f32 {
+        self.sin
+    }
+
+    /// The rotation in the opposite direction.
+    pub fn inverse(&self) -> Self {
+        Self {
+            cos: self.cos,
+            sin: -self.sin,
+        }
+    }
+
+    pub fn rotate(&self, vector: Vec2) -> Vec2 {
+        Vec2::new(
+            self.cos * vector.x - self.sin * vector.y,
+            self.sin * vector.x + self.cos * vector.y,
+        )
+    }
+
+    /// The rotation as a homogeneous 2D matrix.
+    #[rustfmt::skip]
+    pub fn to_homogeneous(&self) -> Mat3 {
+        Mat3::new(
+            self.cos, -self.sin, 0.0,
+            self.sin,  self.cos, 0.0,
+            0.0     ,  0.0     , 1.0,
+        )
+    }
+
+    /// Restore unit length after many compositions have let rounding errors
+    /// build up.
+    pub fn renormalize(&mut self) {
+        let length = self.cos.hypot(self.sin);
+        self.cos /= length;
+        self.sin /= length;
+    }
+}
+
+impl Mul for Rot2 {
+    type Output = Rot2;
+
+    /// Compose two rotations. The result rotates by both angles.
+    fn mul(self, rhs: Rot2) -> Rot2 {
+        Rot2 {
+            cos: self.cos * rhs.cos - self.sin * rhs.sin,
+            sin: self.sin * rhs.cos + self.cos * rhs.sin,
+        }
+    }
+}
+
+impl MulAssign for Rot2 {
+    fn mul_assign(&mut self, rhs: Rot2) {
+        *self = *self * rhs;
+    }
+}
+
+impl Mul<Vec2> for Rot2 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: Vec2) -> Vec2 {
+        self.rotate(rhs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, approx::assert_relative_eq, std::f32::consts::FRAC_PI_2};
+
+    #[test]
+    fn test_compose_and_invert() {
+        let quarter = Rot2::new(FRAC_PI_2);
+        let v = Vec2::new(2.0, 1.0);
+
+        assert_relative_eq!(quarter * v, Vec2::new(-1.0, 2.0), epsilon = 1e-6);
+        assert_relative_eq!(quarter * quarter * v, -v, epsilon = 1e-6);
+        assert_relative_eq!(quarter.inverse() * (quarter * v), v);
+        assert_relative_eq!(
+            quarter.to_homogeneous() * Mat3::new_translation(&v),
+            Mat3::new_rotation(FRAC_PI_2) * Mat3::new_translation(&v),
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            Rot2::between(v, quarter * v * 3.0).angle(),
+            FRAC_PI_2
+        );
+    }
+}
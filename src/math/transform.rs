@@ -0,0 +1,188 @@
+use super::{Mat3, Mat4, Vec2};
+
+/// A 2D transform made of a scale, then a rotation, then a translation.
+///
+/// A transform can have a parent. The parent is applied after the child, so a
+/// child's translation, rotation, and scale are relative to its parent.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transform2D {
+    pub translation: Vec2,
+
+    /// The counter-clockwise rotation in radians.
+    pub rotation: f32,
+
+    pub scale: Vec2,
+
+    parent: Option<Box<Transform2D>>,
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform2D {
+    pub fn new(translation: Vec2, rotation: f32, scale: Vec2) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+            parent: None,
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(Vec2::zeros(), 0.0, Vec2::new(1.0, 1.0))
+    }
+
+    pub fn from_translation(translation: Vec2) -> Self {
+        Self::new(translation, 0.0, Vec2::new(1.0, 1.0))
+    }
+
+    pub fn from_rotation(rotation: f32) -> Self {
+        Self::new(Vec2::zeros(), rotation, Vec2::new(1.0, 1.0))
+    }
+
+    pub fn from_scale(scale: Vec2) -> Self {
+        Self::new(Vec2::zeros(), 0.0, scale)
+    }
+
+    /// Make this transform relative to `parent`.
+    pub fn with_parent(mut self, parent: Transform2D) -> Self {
+        self.parent = Some(Box::new(parent));
+        self
+    }
+
+    pub fn parent(&self) -> Option<&Transform2D> {
+        self.parent.as_deref()
+    }
+
+    pub fn parent_mut(&mut self) -> Option<&mut Transform2D> {
+        self.parent.as_deref_mut()
+    }
+
+    /// The homogeneous matrix for this transform, ignoring any parent.
+    pub fn local_to_homogeneous(&self) -> Mat3 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (sx, sy) = (self.scale.x, self.scale.y);
+        #[rustfmt::skip]
+        let matrix = Mat3::new(
+            cos * sx, -sin * sy, self.translation.x,
+            sin * sx,  cos * sy, self.translation.y,
+            0.0     ,  0.0     , 1.0               ,
+        );
+        matrix
+    }
+
+    /// The homogeneous matrix which maps from this transform's local space to
+    /// world space, including every parent.
+    pub fn to_homogeneous(&self) -> Mat3 {
+        match &self.parent {
+            Some(parent) => {
+                parent.to_homogeneous() * self.local_to_homogeneous()
+            }
+            None => self.local_to_homogeneous(),
+        }
+    }
+
+    /// The homogeneous matrix which maps from world space back to this
+    /// transform's local space.
+    ///
+    /// Axes with a scale of zero can't be inverted and produce infinities.
+    pub fn inverse_homogeneous(&self) -> Mat3 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (ix, iy) = (1.0 / self.scale.x, 1.0 / self.scale.y);
+        let t = self.translation;
+        #[rustfmt::skip]
+        let local_inverse = Mat3::new(
+             cos * ix, sin * ix, -(cos * t.x + sin * t.y) * ix,
+            -sin * iy, cos * iy,  (sin * t.x - cos * t.y) * iy,
+             0.0     , 0.0     ,  1.0                         ,
+        );
+        match &self.parent {
+            Some(parent) => local_inverse * parent.inverse_homogeneous(),
+            None => local_inverse,
+        }
+    }
+
+    /// The world transform as a 4x4 matrix which leaves z untouched.
+    ///
+    /// Useful anywhere the renderer expects a `Mat4`.
+    pub fn to_mat4(&self) -> Mat4 {
+        let m = self.to_homogeneous();
+        #[rustfmt::skip]
+        let matrix = Mat4::new(
+            m[(0, 0)], m[(0, 1)], 0.0, m[(0, 2)],
+            m[(1, 0)], m[(1, 1)], 0.0, m[(1, 2)],
+            0.0      , 0.0      , 1.0, 0.0      ,
+            0.0      , 0.0      , 0.0, 1.0      ,
+        );
+        matrix
+    }
+
+    /// Compose two transforms. The result applies `other` first, then `self`.
+    pub fn compose(&self, other: &Transform2D) -> Mat3 {
+        self.to_homogeneous() * other.to_homogeneous()
+    }
+
+    /// Map a point from local space to world space.
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        self.to_homogeneous().transform_point(&point.into()).coords
+    }
+
+    /// Map a direction from local space to world space. Translation is
+    /// ignored.
+    pub fn transform_vector(&self, vector: Vec2) -> Vec2 {
+        self.to_homogeneous().transform_vector(&vector)
+    }
+
+    /// Map a point from world space to local space.
+    pub fn inverse_transform_point(&self, point: Vec2) -> Vec2 {
+        self.inverse_homogeneous()
+            .transform_point(&point.into())
+            .coords
+    }
+
+    /// Map a direction from world space to local space. Translation is
+    /// ignored.
+    pub fn inverse_transform_vector(&self, vector: Vec2) -> Vec2 {
+        self.inverse_homogeneous().transform_vector(&vector)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, approx::assert_relative_eq, std::f32::consts::FRAC_PI_2};
+
+    #[test]
+    fn test_transform_point_with_parent() {
+        let parent = Transform2D::new(
+            Vec2::new(10.0, 0.0),
+            FRAC_PI_2,
+            Vec2::new(2.0, 2.0),
+        );
+        let child = Transform2D::from_translation(Vec2::new(1.0, 0.0))
+            .with_parent(parent);
+
+        let world = child.transform_point(Vec2::new(1.0, 0.0));
+        assert_relative_eq!(world, Vec2::new(10.0, 4.0), epsilon = 1e-5);
+
+        let local = child.inverse_transform_point(world);
+        assert_relative_eq!(local, Vec2::new(1.0, 0.0), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_inverse_homogeneous() {
+        let transform =
+            Transform2D::new(Vec2::new(-3.0, 7.0), 0.7, Vec2::new(0.5, 3.0))
+                .with_parent(Transform2D::from_rotation(-1.2));
+
+        assert_relative_eq!(
+            transform.to_homogeneous() * transform.inverse_homogeneous(),
+            Mat3::identity(),
+            epsilon = 1e-5
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! MIDI input from hardware controllers.
+//!
+//! This module is only available with the `midi` feature.
+//!
+//! ```ignore
+//! let mut midi = midi::Input::open("nanoKONTROL")?;
+//!
+//! // in update
+//! midi.update();
+//! self.radius = 10.0 + midi.cc(0, 16) * 200.0;
+//! for message in midi.messages() {
+//!     if let midi::Message::NoteOn { note, .. } = message {
+//!         // ...
+//!     }
+//! }
+//! ```
+
+use {
+    anyhow::{anyhow, bail, Context, Result},
+    midir::{MidiInput, MidiInputConnection},
+    std::sync::mpsc::{self, Receiver, Sender},
+};
+
+const CLIENT_NAME: &str = "sim2d";
+
+/// A MIDI channel voice message.
+///
+/// Channels are numbered 0-15, and notes, velocities, and controller values
+/// are 0-127.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Message {
+    NoteOn {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+    },
+
+    /// Also sent for a note on with zero velocity, which many devices use
+    /// in place of a note off.
+    NoteOff {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+    },
+
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+
+    /// The pitch wheel position from -8192 to 8191, where 0 is centered.
+    PitchBend {
+        channel: u8,
+        value: i16,
+    },
+}
+
+/// Receives MIDI messages from one or more devices and collects them once
+/// per frame.
+///
+/// Connections are closed when the input is dropped.
+pub struct Input {
+    receiver: Receiver<Message>,
+    messages: Vec<Message>,
+
+    /// The latest value for every controller on every channel.
+    controls: [[u8; 128]; 16],
+
+    /// The velocity of every held note on every channel, zero when the note
+    /// is up.
+    notes: [[u8; 128]; 16],
+    port_names: Vec<String>,
+    _connections: Vec<MidiInputConnection<Sender<Message>>>,
+}
+
+// Public API
+// ----------
+
+impl Message {
+    /// Parse a raw MIDI message. Returns None for messages other than note,
+    /// control change, and pitch bend messages.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let (status, data) = bytes.split_first()?;
+        let channel = status & 0x0F;
+        let message = match (status & 0xF0, data) {
+            (0x80, &[note, velocity, ..]) => Message::NoteOff {
+                channel,
+                note,
+                velocity,
+            },
+            (0x90, &[note, 0, ..]) => Message::NoteOff {
+                channel,
+                note,
+                velocity: 0,
+            },
+            (0x90, &[note, velocity, ..]) => Message::NoteOn {
+                channel,
+                note,
+                velocity,
+            },
+            (0xB0, &[controller, value, ..]) => Message::ControlChange {
+                channel,
+                controller,
+                value,
+            },
+            (0xE0, &[lsb, msb, ..]) => Message::PitchBend {
+                channel,
+                value: (((msb as i16) << 7) | lsb as i16) - 8192,
+            },
+            _ => return None,
+        };
+        Some(message)
+    }
+}
+
+impl Input {
+    /// The names of every available MIDI input port.
+    pub fn port_names() -> Result<Vec<String>> {
+        let midi = MidiInput::new(CLIENT_NAME)
+            .context("Unable to initialize MIDI input!")?;
+        midi.ports()
+            .iter()
+            .map(|port| {
+                midi.port_name(port)
+                    .context("Unable to read a MIDI port name!")
+            })
+            .collect()
+    }
+
+    /// Connect to every port with a name containing `name`. An empty name
+    /// connects to every port.
+    ///
+    /// Fails if no port matches.
+    pub fn open(name: &str) -> Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let mut connections = vec![];
+        let mut port_names = vec![];
+
+        let ports = MidiInput::new(CLIENT_NAME)
+            .context("Unable to initialize MIDI input!")?
+            .ports();
+        for port in &ports {
+            // Connecting consumes the client, so each port needs its own.
+            let midi = MidiInput::new(CLIENT_NAME)
+                .context("Unable to initialize MIDI input!")?;
+            let port_name = midi
+                .port_name(port)
+                .context("Unable to read a MIDI port name!")?;
+            if !port_name.contains(name) {
+                continue;
+            }
+            let connection = midi
+                .connect(
+                    port,
+                    CLIENT_NAME,
+                    |_timestamp, bytes, sender: &mut Sender<Message>| {
+                        if let Some(message) = Message::parse(bytes) {
+                            // The input was dropped, nothing is listening.
+                            let _ = sender.send(message);
+                        }
+                    },
+                    sender.clone(),
+                )
+                .map_err(|error| anyhow!("{}", error))
+                .with_context(|| {
+                    format!("Unable to connect to MIDI port {}", port_name)
+                })?;
+            log::info!("Connected to MIDI port {}", port_name);
+            connections.push(connection);
+            port_names.push(port_name);
+        }
+
+        if connections.is_empty() {
+            bail!("No MIDI input port matches {:?}", name);
+        }
+
+        Ok(Self {
+            receiver,
+            messages: vec![],
+            controls: [[0; 128]; 16],
+            notes: [[0; 128]; 16],
+            port_names,
+            _connections: connections,
+        })
+    }
+
+    /// Collect the messages received since the last update. Call once per
+    /// frame.
+    pub fn update(&mut self) {
+        self.messages.clear();
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::NoteOn {
+                    channel,
+                    note,
+                    velocity,
+                } => self.notes[channel as usize][note as usize] = velocity,
+                Message::NoteOff { channel, note, .. } => {
+                    self.notes[channel as usize][note as usize] = 0
+                }
+                Message::ControlChange {
+                    channel,
+                    controller,
+                    value,
+                } => {
+                    self.controls[channel as usize][controller as usize] = value
+                }
+                Message::PitchBend { .. } => (),
+            }
+            self.messages.push(message);
+        }
+    }
+
+    /// The messages received before the last update, oldest first.
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// The latest value of a controller, such as a knob or fader, scaled to
+    /// the range 0-1. Controllers which haven't sent a value are 0.
+    pub fn cc(&self, channel: u8, controller: u8) -> f32 {
+        self.controls[channel as usize & 0x0F][controller as usize & 0x7F]
+            as f32
+            / 127.0
+    }
+
+    /// True while a note is held.
+    pub fn is_note_down(&self, channel: u8, note: u8) -> bool {
+        self.notes[channel as usize & 0x0F][note as usize & 0x7F] > 0
+    }
+
+    /// The names of the connected ports.
+    pub fn connected_ports(&self) -> &[String] {
+        &self.port_names
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Message::parse(&[0x93, 60, 100]),
+            Some(Message::NoteOn {
+                channel: 3,
+                note: 60,
+                velocity: 100
+            })
+        );
+        assert_eq!(
+            Message::parse(&[0x90, 60, 0]),
+            Some(Message::NoteOff {
+                channel: 0,
+                note: 60,
+                velocity: 0
+            })
+        );
+        assert_eq!(
+            Message::parse(&[0xB1, 7, 127]),
+            Some(Message::ControlChange {
+                channel: 1,
+                controller: 7,
+                value: 127
+            })
+        );
+        assert_eq!(
+            Message::parse(&[0xE0, 0, 0x40]),
+            Some(Message::PitchBend {
+                channel: 0,
+                value: 0
+            })
+        );
+        assert_eq!(Message::parse(&[0xF8]), None);
+        assert_eq!(Message::parse(&[0x90, 60]), None);
+    }
+}
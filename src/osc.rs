@@ -0,0 +1,412 @@
+//! Open Sound Control over UDP, for controlling sketches from TouchOSC, Max,
+//! SuperCollider, and other OSC tools.
+//!
+//! ```ignore
+//! let mut osc = osc::Socket::bind(9000)?;
+//!
+//! // in update
+//! osc.update();
+//! for message in osc.messages_to("/1/fader1") {
+//!     self.speed = message.float(0).unwrap_or(self.speed);
+//! }
+//! let mouse = sim.w.mouse_pos();
+//! let message = osc::Message::new("/sketch/mouse")
+//!     .with_arg(mouse.x)
+//!     .with_arg(mouse.y);
+//! osc.send("127.0.0.1:57120", &message)?;
+//! ```
+
+use {
+    anyhow::{bail, Context, Result},
+    std::{
+        io::ErrorKind,
+        net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    },
+};
+
+/// The largest packet which can be received.
+const MAX_PACKET_SIZE: usize = 65_507;
+
+/// A single OSC argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Arg {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    Blob(Vec<u8>),
+    Bool(bool),
+    Nil,
+}
+
+/// An OSC message: an address pattern like `/1/fader1` and its arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub address: String,
+    pub args: Vec<Arg>,
+}
+
+/// A UDP socket which receives and sends OSC messages.
+pub struct Socket {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+    messages: Vec<Message>,
+}
+
+impl From<i32> for Arg {
+    fn from(value: i32) -> Self {
+        Arg::Int(value)
+    }
+}
+
+impl From<i64> for Arg {
+    fn from(value: i64) -> Self {
+        Arg::Long(value)
+    }
+}
+
+impl From<f32> for Arg {
+    fn from(value: f32) -> Self {
+        Arg::Float(value)
+    }
+}
+
+impl From<f64> for Arg {
+    fn from(value: f64) -> Self {
+        Arg::Double(value)
+    }
+}
+
+impl From<&str> for Arg {
+    fn from(value: &str) -> Self {
+        Arg::String(value.to_owned())
+    }
+}
+
+impl From<String> for Arg {
+    fn from(value: String) -> Self {
+        Arg::String(value)
+    }
+}
+
+impl From<Vec<u8>> for Arg {
+    fn from(value: Vec<u8>) -> Self {
+        Arg::Blob(value)
+    }
+}
+
+impl From<bool> for Arg {
+    fn from(value: bool) -> Self {
+        Arg::Bool(value)
+    }
+}
+
+// Public API
+// ----------
+
+impl Arg {
+    /// The argument as a float, converting from any numeric argument. Bools
+    /// are 1.0 or 0.0.
+    pub fn as_f32(&self) -> Option<f32> {
+        match *self {
+            Arg::Int(value) => Some(value as f32),
+            Arg::Long(value) => Some(value as f32),
+            Arg::Float(value) => Some(value),
+            Arg::Double(value) => Some(value as f32),
+            Arg::Bool(value) => Some(if value { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Arg::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Message {
+    /// Create a message with no arguments.
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+            args: vec![],
+        }
+    }
+
+    pub fn with_arg(mut self, arg: impl Into<Arg>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// The argument at `index` as a float. See `Arg::as_f32`.
+    pub fn float(&self, index: usize) -> Option<f32> {
+        self.args.get(index).and_then(Arg::as_f32)
+    }
+
+    /// Encode the message as an OSC packet.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut tags = String::from(",");
+        let mut data = vec![];
+        for arg in &self.args {
+            match arg {
+                Arg::Int(value) => {
+                    tags.push('i');
+                    data.extend(value.to_be_bytes());
+                }
+                Arg::Long(value) => {
+                    tags.push('h');
+                    data.extend(value.to_be_bytes());
+                }
+                Arg::Float(value) => {
+                    tags.push('f');
+                    data.extend(value.to_be_bytes());
+                }
+                Arg::Double(value) => {
+                    tags.push('d');
+                    data.extend(value.to_be_bytes());
+                }
+                Arg::String(value) => {
+                    tags.push('s');
+                    push_string(&mut data, value);
+                }
+                Arg::Blob(value) => {
+                    tags.push('b');
+                    data.extend((value.len() as u32).to_be_bytes());
+                    data.extend(value);
+                    pad(&mut data);
+                }
+                Arg::Bool(true) => tags.push('T'),
+                Arg::Bool(false) => tags.push('F'),
+                Arg::Nil => tags.push('N'),
+            }
+        }
+
+        let mut packet = vec![];
+        push_string(&mut packet, &self.address);
+        push_string(&mut packet, &tags);
+        packet.extend(data);
+        packet
+    }
+
+    /// Decode an OSC packet. Bundles are flattened into their messages and
+    /// their time tags are ignored.
+    pub fn decode(packet: &[u8]) -> Result<Vec<Message>> {
+        let mut messages = vec![];
+        decode_packet(packet, &mut messages)?;
+        Ok(messages)
+    }
+}
+
+impl Socket {
+    /// Listen for OSC messages on a UDP port on every network interface.
+    pub fn bind(port: u16) -> Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))
+            .with_context(|| format!("Unable to bind OSC port {}", port))?;
+        socket
+            .set_nonblocking(true)
+            .context("Unable to make the OSC socket non-blocking!")?;
+        Ok(Self {
+            socket,
+            buffer: vec![0; MAX_PACKET_SIZE],
+            messages: vec![],
+        })
+    }
+
+    /// The address the socket is listening on. Useful after binding port 0
+    /// to let the system pick a free port.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.socket
+            .local_addr()
+            .context("Unable to get the OSC socket address!")
+    }
+
+    /// Collect the messages received since the last update. Call once per
+    /// frame.
+    ///
+    /// Malformed packets are logged and skipped.
+    pub fn update(&mut self) {
+        self.messages.clear();
+        loop {
+            let (size, from) = match self.socket.recv_from(&mut self.buffer) {
+                Ok(received) => received,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => {
+                    log::warn!("Unable to receive OSC packet: {}", error);
+                    break;
+                }
+            };
+            if let Err(error) =
+                decode_packet(&self.buffer[..size], &mut self.messages)
+            {
+                log::warn!("Invalid OSC packet from {}: {:?}", from, error);
+            }
+        }
+    }
+
+    /// The messages received before the last update, oldest first.
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// The messages received before the last update with exactly this
+    /// address.
+    pub fn messages_to<'a>(
+        &'a self,
+        address: &'a str,
+    ) -> impl Iterator<Item = &'a Message> + 'a {
+        self.messages
+            .iter()
+            .filter(move |message| message.address == address)
+    }
+
+    /// Send a message to another application.
+    pub fn send(
+        &self,
+        to: impl ToSocketAddrs,
+        message: &Message,
+    ) -> Result<()> {
+        self.socket
+            .send_to(&message.encode(), to)
+            .with_context(|| {
+                format!("Unable to send OSC message to {}", message.address)
+            })?;
+        Ok(())
+    }
+}
+
+// Private API
+// -----------
+
+/// Append a null terminated string padded to a multiple of four bytes.
+fn push_string(data: &mut Vec<u8>, value: &str) {
+    data.extend(value.as_bytes());
+    data.push(0);
+    pad(data);
+}
+
+fn pad(data: &mut Vec<u8>) {
+    let padding = (4 - data.len() % 4) % 4;
+    data.resize(data.len() + padding, 0);
+}
+
+fn decode_packet(packet: &[u8], messages: &mut Vec<Message>) -> Result<()> {
+    let mut reader = Reader { data: packet };
+    if packet.starts_with(b"#bundle\0") {
+        reader.take(8)?;
+        let _time_tag = reader.take(8)?;
+        while !reader.data.is_empty() {
+            let size = reader.u32()? as usize;
+            decode_packet(reader.take(size)?, messages)?;
+        }
+        return Ok(());
+    }
+
+    let address = reader.string()?.to_owned();
+    if !address.starts_with('/') {
+        bail!("OSC addresses must start with '/', got {:?}", address);
+    }
+    let mut message = Message::new(address);
+
+    // Very old senders omit the type tags, treat that as no arguments.
+    let tags = if reader.data.is_empty() {
+        ","
+    } else {
+        reader.string()?
+    };
+    let tags = match tags.strip_prefix(',') {
+        Some(tags) => tags,
+        None => bail!("Invalid OSC type tags {:?}", tags),
+    };
+    for tag in tags.chars() {
+        let arg = match tag {
+            'i' => Arg::Int(reader.u32()? as i32),
+            'h' => Arg::Long(reader.u64()? as i64),
+            'f' => Arg::Float(f32::from_bits(reader.u32()?)),
+            'd' => Arg::Double(f64::from_bits(reader.u64()?)),
+            's' | 'S' => Arg::String(reader.string()?.to_owned()),
+            'b' => {
+                let size = reader.u32()? as usize;
+                let blob = reader.take(size)?.to_vec();
+                reader.take((4 - size % 4) % 4)?;
+                Arg::Blob(blob)
+            }
+            'T' => Arg::Bool(true),
+            'F' => Arg::Bool(false),
+            'N' => Arg::Nil,
+            _ => bail!("Unsupported OSC type tag {:?}", tag),
+        };
+        message.args.push(arg);
+    }
+    messages.push(message);
+    Ok(())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8]> {
+        if count > self.data.len() {
+            bail!("The OSC packet ended early");
+        }
+        let (taken, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let high = self.u32()? as u64;
+        let low = self.u32()? as u64;
+        Ok((high << 32) | low)
+    }
+
+    fn string(&mut self) -> Result<&'a str> {
+        let end = match self.data.iter().position(|&byte| byte == 0) {
+            Some(end) => end,
+            None => bail!("An OSC string is missing its terminator"),
+        };
+        let bytes = self.take((end / 4 + 1) * 4)?;
+        std::str::from_utf8(&bytes[..end])
+            .context("An OSC string is not valid UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let message = Message::new("/1/fader1")
+            .with_arg(3)
+            .with_arg(0.5f32)
+            .with_arg("abc")
+            .with_arg(vec![1u8, 2, 3, 4, 5])
+            .with_arg(true)
+            .with_arg(2.25f64);
+        let packet = message.encode();
+        assert_eq!(packet.len() % 4, 0);
+        assert_eq!(Message::decode(&packet)?, vec![message.clone()]);
+        assert_eq!(message.float(1), Some(0.5));
+
+        let mut bundle = b"#bundle\0".to_vec();
+        bundle.extend(1u64.to_be_bytes());
+        for _ in 0..2 {
+            bundle.extend((packet.len() as u32).to_be_bytes());
+            bundle.extend(&packet);
+        }
+        assert_eq!(Message::decode(&bundle)?.len(), 2);
+
+        assert!(Message::decode(&packet[..packet.len() - 2]).is_err());
+        Ok(())
+    }
+}
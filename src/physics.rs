@@ -0,0 +1,388 @@
+//! 2D rigid body physics backed by rapier.
+//!
+//! This module is only available with the `physics` feature.
+//!
+//! ```ignore
+//! // in preload
+//! self.physics = Physics::new(Vec2::new(0.0, -500.0));
+//! let ground = (Vec2::new(-400.0, -300.0), Vec2::new(400.0, -300.0));
+//! self.physics.add_line(ground.0, ground.1);
+//! self.ball =
+//!     self.physics.add_circle(Vec2::zeros(), 20.0, BodyType::Dynamic);
+//!
+//! // in update
+//! self.physics.update(sim.dt());
+//! if let Some((pos, angle)) = self.physics.position(self.ball) {
+//!     sim.g.rect_centered(pos, Vec2::new(40.0, 40.0), angle);
+//! }
+//! self.physics.draw_debug(&mut sim.g);
+//! ```
+//!
+//! Shapes use the same coordinates, sizes, and angles as G2D. The rapier
+//! crate is re-exported for joints, queries, and anything else the helpers
+//! don't cover.
+
+pub use rapier2d::{
+    self,
+    prelude::{ColliderHandle, RigidBodyHandle, RigidBodyType as BodyType},
+};
+use {
+    crate::{
+        graphics::{Image, G2D},
+        math::Vec2,
+    },
+    anyhow::{Context, Result},
+    rapier2d::{
+        parry::shape::TypedShape,
+        prelude::{
+            CCDSolver, Collider, ColliderBuilder, ColliderSet,
+            DefaultBroadPhase, ImpulseJointSet, IntegrationParameters,
+            IslandManager, Isometry, MultibodyJointSet, NarrowPhase,
+            PhysicsPipeline, Point, QueryPipeline, RigidBody, RigidBodyBuilder,
+            RigidBodySet,
+        },
+    },
+};
+
+/// The number of line segments used to draw a circle collider.
+const CIRCLE_SEGMENTS: usize = 24;
+
+/// A rapier world which steps with a fixed timestep.
+pub struct Physics {
+    /// Acceleration applied to every dynamic body, in units per second
+    /// squared.
+    pub gravity: Vec2,
+
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    integration_parameters: IntegrationParameters,
+    pipeline: PhysicsPipeline,
+    islands: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+    query_pipeline: QueryPipeline,
+
+    /// Time which hasn't been simulated yet because it's less than a step.
+    accumulator: f32,
+
+    /// The most steps taken in one update, so a slow frame doesn't make
+    /// the next frame even slower.
+    max_steps: u32,
+}
+
+// Public API
+// ----------
+
+impl Physics {
+    /// Create an empty world which steps 60 times per second.
+    pub fn new(gravity: Vec2) -> Self {
+        let mut integration_parameters = IntegrationParameters::default();
+        integration_parameters.dt = 1.0 / 60.0;
+        Self {
+            gravity,
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            integration_parameters,
+            pipeline: PhysicsPipeline::new(),
+            islands: IslandManager::new(),
+            broad_phase: DefaultBroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd_solver: CCDSolver::new(),
+            query_pipeline: QueryPipeline::new(),
+            accumulator: 0.0,
+            max_steps: 8,
+        }
+    }
+
+    /// Set the length of each physics step in seconds.
+    pub fn set_timestep(&mut self, seconds: f32) {
+        self.integration_parameters.dt = seconds.max(f32::EPSILON);
+    }
+
+    pub fn timestep(&self) -> f32 {
+        self.integration_parameters.dt
+    }
+
+    /// Advance the simulation by `dt` seconds using as many fixed steps as
+    /// fit. Leftover time carries over to the next update. Returns the
+    /// number of steps taken.
+    ///
+    /// Call once per frame with `sim.dt()`.
+    pub fn update(&mut self, dt: f32) -> u32 {
+        let timestep = self.timestep();
+        self.accumulator += dt.max(0.0);
+        let mut steps = 0;
+        while self.accumulator >= timestep && steps < self.max_steps {
+            self.step();
+            self.accumulator -= timestep;
+            steps += 1;
+        }
+        if steps == self.max_steps {
+            // Drop the time which couldn't be simulated rather than trying
+            // to catch up forever.
+            self.accumulator = self.accumulator.min(timestep);
+        }
+        steps
+    }
+
+    /// Advance the simulation by exactly one timestep.
+    pub fn step(&mut self) {
+        self.pipeline.step(
+            &self.gravity,
+            &self.integration_parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd_solver,
+            Some(&mut self.query_pipeline),
+            &(),
+            &(),
+        );
+    }
+
+    /// Add a circle, like a sprite drawn with `rect_centered` and a round
+    /// image.
+    pub fn add_circle(
+        &mut self,
+        center: Vec2,
+        radius: f32,
+        body_type: BodyType,
+    ) -> RigidBodyHandle {
+        let body = RigidBodyBuilder::new(body_type).translation(center).build();
+        self.insert(body, vec![ColliderBuilder::ball(radius).build()])
+    }
+
+    /// Add a rectangle matching `G2D::rect_centered`.
+    pub fn add_rect(
+        &mut self,
+        center: Vec2,
+        size: Vec2,
+        angle: f32,
+        body_type: BodyType,
+    ) -> RigidBodyHandle {
+        let body = RigidBodyBuilder::new(body_type)
+            .translation(center)
+            .rotation(angle)
+            .build();
+        let collider =
+            ColliderBuilder::cuboid(size.x * 0.5, size.y * 0.5).build();
+        self.insert(body, vec![collider])
+    }
+
+    /// Add the convex hull of a polygon. The body's position is the center
+    /// of the polygon's bounding box.
+    pub fn add_polygon(
+        &mut self,
+        points: &[Vec2],
+        body_type: BodyType,
+    ) -> Result<RigidBodyHandle> {
+        let (min, max) = points.iter().fold(
+            (Vec2::repeat(f32::MAX), Vec2::repeat(f32::MIN)),
+            |(min, max), point| (min.inf(point), max.sup(point)),
+        );
+        let center = (min + max) * 0.5;
+        let local: Vec<Point<f32>> = points
+            .iter()
+            .map(|point| Point::from(point - center))
+            .collect();
+        let collider = ColliderBuilder::convex_hull(&local)
+            .context("Unable to build a collider from the polygon!")?
+            .build();
+        let body = RigidBodyBuilder::new(body_type).translation(center).build();
+        Ok(self.insert(body, vec![collider]))
+    }
+
+    /// Add a fixed line matching `G2D::line`, for floors and walls.
+    pub fn add_line(&mut self, start: Vec2, end: Vec2) -> RigidBodyHandle {
+        let body = RigidBodyBuilder::fixed().build();
+        let collider =
+            ColliderBuilder::segment(start.into(), end.into()).build();
+        self.insert(body, vec![collider])
+    }
+
+    /// Add a body built with rapier directly, along with its colliders.
+    pub fn insert(
+        &mut self,
+        body: RigidBody,
+        colliders: Vec<Collider>,
+    ) -> RigidBodyHandle {
+        let handle = self.bodies.insert(body);
+        for collider in colliders {
+            self.colliders.insert_with_parent(
+                collider,
+                handle,
+                &mut self.bodies,
+            );
+        }
+        handle
+    }
+
+    /// Remove a body and its colliders.
+    pub fn remove(&mut self, handle: RigidBodyHandle) {
+        self.bodies.remove(
+            handle,
+            &mut self.islands,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            true,
+        );
+    }
+
+    /// A body's position and counter-clockwise rotation in radians, ready
+    /// to pass to `G2D::rect_centered`.
+    pub fn position(&self, handle: RigidBodyHandle) -> Option<(Vec2, f32)> {
+        let body = self.bodies.get(handle)?;
+        Some((*body.translation(), body.rotation().angle()))
+    }
+
+    pub fn body(&self, handle: RigidBodyHandle) -> Option<&RigidBody> {
+        self.bodies.get(handle)
+    }
+
+    /// Access a body to apply forces, set velocities, and so on.
+    pub fn body_mut(
+        &mut self,
+        handle: RigidBodyHandle,
+    ) -> Option<&mut RigidBody> {
+        self.bodies.get_mut(handle)
+    }
+
+    pub fn bodies(&self) -> &RigidBodySet {
+        &self.bodies
+    }
+
+    pub fn colliders(&self) -> &ColliderSet {
+        &self.colliders
+    }
+
+    /// Outline every collider with lines, using the current fill color and
+    /// line width. Shapes without an outline, like height fields, are
+    /// skipped.
+    pub fn draw_debug(&self, g: &mut G2D) {
+        let original_image = g.image;
+        g.image = Image::none();
+        for (_, collider) in self.colliders.iter() {
+            draw_collider(g, collider.position(), collider);
+        }
+        g.image = original_image;
+    }
+}
+
+impl Default for Physics {
+    /// A world with no gravity.
+    fn default() -> Self {
+        Self::new(Vec2::zeros())
+    }
+}
+
+// Private API
+// -----------
+
+fn draw_collider(g: &mut G2D, position: &Isometry<f32>, collider: &Collider) {
+    let transform = |point: Point<f32>| (position * point).coords;
+    match collider.shape().as_typed_shape() {
+        TypedShape::Ball(ball) => {
+            let outline: Vec<Vec2> = (0..CIRCLE_SEGMENTS)
+                .map(|i| {
+                    let angle = std::f32::consts::TAU * i as f32
+                        / CIRCLE_SEGMENTS as f32;
+                    let offset =
+                        Vec2::new(angle.cos(), angle.sin()) * ball.radius;
+                    transform(Point::from(offset))
+                })
+                .collect();
+            draw_closed(g, &outline);
+        }
+        TypedShape::Cuboid(cuboid) => {
+            let h = cuboid.half_extents;
+            let outline: Vec<Vec2> =
+                [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                    .iter()
+                    .map(|&(x, y)| transform(Point::new(x * h.x, y * h.y)))
+                    .collect();
+            draw_closed(g, &outline);
+        }
+        TypedShape::ConvexPolygon(polygon) => {
+            let outline: Vec<Vec2> =
+                polygon.points().iter().map(|&p| transform(p)).collect();
+            draw_closed(g, &outline);
+        }
+        TypedShape::Segment(segment) => {
+            g.line(transform(segment.a), transform(segment.b));
+        }
+        TypedShape::Polyline(polyline) => {
+            for segment in polyline.segments() {
+                g.line(transform(segment.a), transform(segment.b));
+            }
+        }
+        TypedShape::Triangle(triangle) => {
+            let outline = [triangle.a, triangle.b, triangle.c].map(transform);
+            draw_closed(g, &outline);
+        }
+        _ => (),
+    }
+}
+
+fn draw_closed(g: &mut G2D, outline: &[Vec2]) {
+    for (i, &start) in outline.iter().enumerate() {
+        g.line(start, outline[(i + 1) % outline.len()]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, approx::assert_relative_eq};
+
+    #[test]
+    fn test_ball_lands_on_line() {
+        let mut physics = Physics::new(Vec2::new(0.0, -500.0));
+        physics.add_line(Vec2::new(-200.0, -100.0), Vec2::new(200.0, -100.0));
+        let ball = physics.add_circle(Vec2::zeros(), 10.0, BodyType::Dynamic);
+
+        for _ in 0..180 {
+            physics.update(1.0 / 60.0);
+        }
+
+        let (pos, _) = physics.position(ball).unwrap();
+        assert_relative_eq!(pos, Vec2::new(0.0, -90.0), epsilon = 1.0);
+    }
+
+    #[test]
+    fn test_update_limits_steps() {
+        let mut physics = Physics::default();
+
+        assert_eq!(physics.update(1.0), physics.max_steps);
+
+        // Only one step of the missed time is kept.
+        assert_eq!(physics.update(0.0), 1);
+        assert_eq!(physics.update(0.0), 0);
+    }
+
+    #[test]
+    fn test_polygon_is_centered() -> Result<()> {
+        let mut physics = Physics::default();
+        let square = [
+            Vec2::new(10.0, 10.0),
+            Vec2::new(30.0, 10.0),
+            Vec2::new(30.0, 20.0),
+            Vec2::new(10.0, 20.0),
+        ];
+
+        let handle = physics.add_polygon(&square, BodyType::Fixed)?;
+
+        let (pos, angle) = physics.position(handle).unwrap();
+        assert_relative_eq!(pos, Vec2::new(20.0, 15.0));
+        assert_relative_eq!(angle, 0.0);
+        Ok(())
+    }
+}
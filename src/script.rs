@@ -0,0 +1,504 @@
+//! Live-code sketches in [rhai](https://rhai.rs) without recompiling Rust.
+//!
+//! This module is only available with the `scripting` feature.
+//!
+//! A script defines an `update` function, called once per frame, and
+//! optionally a `setup` function, called once when the sketch starts. State
+//! which should last between frames goes on `this`:
+//!
+//! ```text
+//! fn setup() {
+//!     this.angle = 0.0;
+//! }
+//!
+//! fn update() {
+//!     this.angle += dt();
+//!     clear_color(0.1, 0.1, 0.1);
+//!     fill_color(1.0, 0.5, 0.2);
+//!     rect_centered(mouse_x(), mouse_y(), 100, 100, this.angle);
+//! }
+//! ```
+//!
+//! Run it with:
+//!
+//! ```ignore
+//! Application::run(ScriptSketch::new("sketch.rhai")?)
+//! ```
+//!
+//! Saving the file reloads the script. `this` survives the reload and `setup`
+//! isn't called again. When the script has an error the previous version keeps
+//! running and the error is drawn in the corner of the window.
+//!
+//! # Drawing
+//!
+//! Coordinates match [crate::graphics::G2D]: the origin is the center of the
+//! window and y points up. Numbers can be written as integers or floats.
+//!
+//! * `rect(x, y, width, height)` and `rect(x, y, width, height, angle)`
+//! * `rect_centered(x, y, width, height)` and `rect_centered(x, y, width,
+//!   height, angle)`
+//! * `line(x1, y1, x2, y2)`
+//! * `text(x, y, string)`
+//! * `fill_color(r, g, b)` and `fill_color(r, g, b, a)`
+//! * `clear_color(r, g, b)` and `clear_color(r, g, b, a)`
+//! * `line_width(width)`
+//!
+//! # Input
+//!
+//! * `mouse_x()`, `mouse_y()`
+//! * `mouse_down()`, `right_mouse_down()`
+//! * `key_down(name)` where name is a `glfw::Key` variant like `"A"`,
+//!   `"Space"`, or `"Num1"`
+//! * `width()`, `height()`
+//! * `dt()`, `time()`, `frame()`
+//! * `random(min, max)`
+//!
+//! `print` writes to the log.
+
+use {
+    crate::{
+        graphics::{Image, G2D},
+        live_reload::{draw_error, WatchedFile},
+        math::Vec2,
+        sim2d::Sim2D,
+        Sketch,
+    },
+    anyhow::{Context, Result},
+    rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST},
+    std::{
+        collections::HashSet,
+        path::Path,
+        sync::{Arc, Mutex},
+    },
+};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// A sketch which runs a rhai script and reloads it when the file changes.
+pub struct ScriptSketch {
+    file: WatchedFile,
+    runtime: Runtime,
+    needs_setup: bool,
+    keys_down: HashSet<String>,
+    time: f32,
+    frame: i64,
+
+    /// Why the file on disk couldn't be loaded. Kept until it loads, while
+    /// the previous script keeps running.
+    load_error: Option<String>,
+
+    /// The error from the most recent frame, if the script failed.
+    run_error: Option<String>,
+}
+
+// Public API
+// ----------
+
+impl ScriptSketch {
+    /// Load the script at `path`.
+    ///
+    /// Fails if the file can't be read or doesn't compile. Later changes
+    /// which don't compile are reported on screen instead.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let mut runtime = Runtime::new();
+        runtime.load(&read_script(&path)?)?;
+        Ok(Self {
+            file: WatchedFile::new(&path),
+            runtime,
+            needs_setup: true,
+            keys_down: HashSet::new(),
+            time: 0.0,
+            frame: 0,
+            load_error: None,
+            run_error: None,
+        })
+    }
+
+    /// The error from the last time the script was loaded or run, if it
+    /// failed.
+    pub fn error(&self) -> Option<&str> {
+        self.load_error.as_deref().or(self.run_error.as_deref())
+    }
+}
+
+impl Sketch for ScriptSketch {
+    fn key_pressed(
+        &mut self,
+        _sim: &mut Sim2D,
+        key: glfw::Key,
+        _modifiers: glfw::Modifiers,
+    ) {
+        self.keys_down.insert(format!("{:?}", key));
+    }
+
+    fn key_released(&mut self, _sim: &mut Sim2D, key: glfw::Key) {
+        self.keys_down.remove(&format!("{:?}", key));
+    }
+
+    fn update(&mut self, sim: &mut Sim2D) {
+        self.reload_if_changed();
+
+        self.time += sim.dt();
+        self.runtime.set_input(Input {
+            mouse: sim.w.mouse_pos(),
+            mouse_down: sim.w.is_left_mouse_button_pressed(),
+            right_mouse_down: sim.w.is_right_mouse_button_pressed(),
+            keys_down: self.keys_down.clone(),
+            size: Vec2::new(sim.w.width(), sim.w.height()),
+            dt: sim.dt(),
+            time: self.time,
+            frame: self.frame,
+        });
+        self.frame += 1;
+
+        let result = if self.needs_setup {
+            self.needs_setup = false;
+            self.runtime.call("setup")
+        } else {
+            Ok(())
+        }
+        .and_then(|_| self.runtime.call("update"));
+        self.runtime.draw(&mut sim.g);
+
+        self.run_error = match result {
+            Ok(()) => None,
+            Err(error) => Some(self.report(format!("{}", error))),
+        };
+        if let Some(error) = self.error() {
+            draw_error(sim, error);
+        }
+    }
+}
+
+// Private API
+// -----------
+
+impl ScriptSketch {
+    fn reload_if_changed(&mut self) {
+        if !self.file.is_changed() {
+            return;
+        }
+
+        let path = self.file.path().to_owned();
+        self.load_error =
+            match read_script(&path).and_then(|s| self.runtime.load(&s)) {
+                Ok(()) => {
+                    log::info!("Reloaded script {:?}", path);
+                    None
+                }
+                Err(error) => Some(self.report(format!("{:#}", error))),
+            };
+    }
+
+    /// Log an error unless it's already on screen, so a failing update
+    /// doesn't flood the log every frame.
+    fn report(&self, error: String) -> String {
+        if self.error() != Some(error.as_str()) {
+            log::error!("{:?}: {}", self.file.path(), error);
+        }
+        error
+    }
+}
+
+/// The input state scripts can read during a call.
+#[derive(Debug, Clone, Default)]
+struct Input {
+    mouse: Vec2,
+    mouse_down: bool,
+    right_mouse_down: bool,
+    keys_down: HashSet<String>,
+    size: Vec2,
+    dt: f32,
+    time: f32,
+    frame: i64,
+}
+
+/// A drawing call made by the script, replayed onto G2D after the call.
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Rect(Vec2, Vec2, f32),
+    RectCentered(Vec2, Vec2, f32),
+    Line(Vec2, Vec2),
+    Text(Vec2, String),
+    FillColor([f32; 4]),
+    ClearColor([f32; 4]),
+    LineWidth(f32),
+}
+
+/// State shared between the sketch and the functions registered with the
+/// engine.
+#[derive(Debug, Default)]
+struct Shared {
+    input: Input,
+    commands: Vec<Command>,
+}
+
+/// The engine, the compiled script, and the script's `this` object.
+struct Runtime {
+    engine: Engine,
+    ast: AST,
+    this: Dynamic,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Runtime {
+    fn new() -> Self {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let mut engine = Engine::new();
+        engine.on_print(|text| log::info!("{}", text));
+        engine
+            .on_debug(|text, _, position| log::debug!("{} {}", position, text));
+        register_drawing(&mut engine, &shared);
+        register_input(&mut engine, &shared);
+        Self {
+            engine,
+            ast: AST::empty(),
+            this: Dynamic::from_map(Map::new()),
+            shared,
+        }
+    }
+
+    /// Compile the source and use it for the following calls. The previous
+    /// script is kept when compilation fails.
+    fn load(&mut self, source: &str) -> Result<()> {
+        let ast = self
+            .engine
+            .compile(source)
+            .map_err(|error| anyhow::anyhow!("{}", error))?;
+        self.engine
+            .run_ast_with_scope(&mut Scope::new(), &ast)
+            .map_err(|error| anyhow::anyhow!("{}", error))?;
+        self.ast = ast;
+        Ok(())
+    }
+
+    fn set_input(&mut self, input: Input) {
+        self.shared.lock().unwrap().input = input;
+    }
+
+    /// Call a function with no arguments if the script defines it.
+    fn call(&mut self, name: &str) -> ScriptResult<()> {
+        if !self.ast.iter_functions().any(|f| f.name == name) {
+            return Ok(());
+        }
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.this);
+        self.engine
+            .call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                &self.ast,
+                name,
+                (),
+            )
+            .map(|_| ())
+    }
+
+    /// Replay the script's drawing onto G2D.
+    fn draw(&mut self, g: &mut G2D) {
+        let commands =
+            std::mem::take(&mut self.shared.lock().unwrap().commands);
+        let original_image = g.image;
+        g.image = Image::none();
+        for command in commands {
+            match command {
+                Command::Rect(pos, size, angle) => g.rect(pos, size, angle),
+                Command::RectCentered(pos, size, angle) => {
+                    g.rect_centered(pos, size, angle)
+                }
+                Command::Line(start, end) => g.line(start, end),
+                Command::Text(pos, text) => g.text(pos, text),
+                Command::FillColor(color) => g.fill_color = color,
+                Command::ClearColor(color) => g.clear_color = color,
+                Command::LineWidth(width) => g.line_width = width,
+            }
+        }
+        g.image = original_image;
+    }
+}
+
+fn register_drawing(engine: &mut Engine, shared: &Arc<Mutex<Shared>>) {
+    let push = |shared: &Arc<Mutex<Shared>>| {
+        let shared = shared.clone();
+        move |command: Command| shared.lock().unwrap().commands.push(command)
+    };
+
+    let draw = push(shared);
+    engine.register_fn(
+        "rect",
+        move |x: Dynamic, y: Dynamic, w: Dynamic, h: Dynamic| {
+            draw(Command::Rect(vec2(x, y)?, vec2(w, h)?, 0.0));
+            ScriptResult::Ok(())
+        },
+    );
+    let draw = push(shared);
+    engine.register_fn(
+        "rect",
+        move |x: Dynamic, y: Dynamic, w: Dynamic, h: Dynamic, a: Dynamic| {
+            draw(Command::Rect(vec2(x, y)?, vec2(w, h)?, number(a)?));
+            ScriptResult::Ok(())
+        },
+    );
+    let draw = push(shared);
+    engine.register_fn(
+        "rect_centered",
+        move |x: Dynamic, y: Dynamic, w: Dynamic, h: Dynamic| {
+            draw(Command::RectCentered(vec2(x, y)?, vec2(w, h)?, 0.0));
+            ScriptResult::Ok(())
+        },
+    );
+    let draw = push(shared);
+    engine.register_fn(
+        "rect_centered",
+        move |x: Dynamic, y: Dynamic, w: Dynamic, h: Dynamic, a: Dynamic| {
+            let angle = number(a)?;
+            draw(Command::RectCentered(vec2(x, y)?, vec2(w, h)?, angle));
+            ScriptResult::Ok(())
+        },
+    );
+    let draw = push(shared);
+    engine.register_fn(
+        "line",
+        move |x1: Dynamic, y1: Dynamic, x2: Dynamic, y2: Dynamic| {
+            draw(Command::Line(vec2(x1, y1)?, vec2(x2, y2)?));
+            ScriptResult::Ok(())
+        },
+    );
+    let draw = push(shared);
+    engine.register_fn("text", move |x: Dynamic, y: Dynamic, text: &str| {
+        draw(Command::Text(vec2(x, y)?, text.to_owned()));
+        ScriptResult::Ok(())
+    });
+    let draw = push(shared);
+    engine.register_fn("line_width", move |width: Dynamic| {
+        draw(Command::LineWidth(number(width)?));
+        ScriptResult::Ok(())
+    });
+
+    for (name, is_fill) in [("fill_color", true), ("clear_color", false)] {
+        let command = move |color: [f32; 4]| {
+            if is_fill {
+                Command::FillColor(color)
+            } else {
+                Command::ClearColor(color)
+            }
+        };
+        let draw = push(shared);
+        engine.register_fn(name, move |r: Dynamic, g: Dynamic, b: Dynamic| {
+            draw(command([number(r)?, number(g)?, number(b)?, 1.0]));
+            ScriptResult::Ok(())
+        });
+        let draw = push(shared);
+        engine.register_fn(
+            name,
+            move |r: Dynamic, g: Dynamic, b: Dynamic, a: Dynamic| {
+                let color = [number(r)?, number(g)?, number(b)?, number(a)?];
+                draw(command(color));
+                ScriptResult::Ok(())
+            },
+        );
+    }
+}
+
+fn register_input(engine: &mut Engine, shared: &Arc<Mutex<Shared>>) {
+    let input = |shared: &Arc<Mutex<Shared>>| {
+        let shared = shared.clone();
+        move || shared.lock().unwrap().input.clone()
+    };
+
+    let read = input(shared);
+    engine.register_fn("mouse_x", move || read().mouse.x as f64);
+    let read = input(shared);
+    engine.register_fn("mouse_y", move || read().mouse.y as f64);
+    let read = input(shared);
+    engine.register_fn("mouse_down", move || read().mouse_down);
+    let read = input(shared);
+    engine.register_fn("right_mouse_down", move || read().right_mouse_down);
+    let read = input(shared);
+    engine.register_fn("key_down", move |name: &str| {
+        read().keys_down.contains(name)
+    });
+    let read = input(shared);
+    engine.register_fn("width", move || read().size.x as f64);
+    let read = input(shared);
+    engine.register_fn("height", move || read().size.y as f64);
+    let read = input(shared);
+    engine.register_fn("dt", move || read().dt as f64);
+    let read = input(shared);
+    engine.register_fn("time", move || read().time as f64);
+    let read = input(shared);
+    engine.register_fn("frame", move || read().frame);
+
+    engine.register_fn("random", |min: Dynamic, max: Dynamic| {
+        let (min, max) = (number(min)?, number(max)?);
+        ScriptResult::Ok((min + (max - min) * rand::random::<f32>()) as f64)
+    });
+}
+
+/// Accept integers and floats wherever a number is expected.
+fn number(value: Dynamic) -> ScriptResult<f32> {
+    if let Ok(float) = value.as_float() {
+        return Ok(float as f32);
+    }
+    if let Ok(int) = value.as_int() {
+        return Ok(int as f32);
+    }
+    Err(format!("Expected a number, found {}", value.type_name()).into())
+}
+
+fn vec2(x: Dynamic, y: Dynamic) -> ScriptResult<Vec2> {
+    Ok(Vec2::new(number(x)?, number(y)?))
+}
+
+fn read_script(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path)
+        .with_context(|| format!("Unable to read script {:?}", path))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_runtime() -> Result<()> {
+        let mut runtime = Runtime::new();
+        runtime.load(
+            "
+            fn setup() { this.x = 1; }
+            fn update() {
+                this.x += 1;
+                fill_color(1, 0.5, 0);
+                rect_centered(this.x, mouse_y(), 10, 10);
+            }
+            ",
+        )?;
+        runtime.set_input(Input {
+            mouse: Vec2::new(0.0, 7.0),
+            ..Input::default()
+        });
+        runtime.call("setup").unwrap();
+        runtime.call("update").unwrap();
+
+        // Reloading keeps `this`.
+        runtime.load("fn update() { rect(this.x, 0, 1, 1); }")?;
+        assert!(runtime.load("fn update() {").is_err());
+        runtime.call("update").unwrap();
+
+        let commands = &runtime.shared.lock().unwrap().commands;
+        assert_eq!(
+            commands,
+            &[
+                Command::FillColor([1.0, 0.5, 0.0, 1.0]),
+                Command::RectCentered(
+                    Vec2::new(2.0, 7.0),
+                    Vec2::new(10.0, 10.0),
+                    0.0
+                ),
+                Command::Rect(Vec2::new(2.0, 0.0), Vec2::new(1.0, 1.0), 0.0),
+            ]
+        );
+        Ok(())
+    }
+}
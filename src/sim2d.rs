@@ -1,6 +1,14 @@
 use {
-    crate::{application::WindowState, graphics::G2D},
-    std::time::Duration,
+    crate::{
+        application::{
+            frame_history::FrameHistory, FrameStats, Transition, WindowState,
+        },
+        graphics::{BackgroundShader, Image, RenderTimings, G2D},
+        math::random::Random,
+    },
+    anyhow::Result,
+    image::RgbaImage,
+    std::{path::PathBuf, time::Duration},
 };
 
 /// The API entrypoint.
@@ -11,30 +19,218 @@ pub struct Sim2D {
     pub g: G2D,
     pub w: WindowState,
 
-    pub(crate) delta_time: f32,
-    pub(crate) avg_frame_time: Duration,
-    pub(crate) avg_sim_time: Duration,
-    pub(crate) avg_render_time: Duration,
+    /// The sketch's random number generator. Call `sim.rng.reseed(seed)` to
+    /// make a sketch's output reproducible.
+    pub rng: Random,
+
+    pub(crate) delta_time: f64,
+    pub(crate) time_scale: f32,
+    pub(crate) frame_stats: FrameStats,
+    pub(crate) last_update_time: Duration,
+    pub(crate) last_render_timings: RenderTimings,
+
+    pub(crate) paused: bool,
+    pub(crate) step_requested: bool,
+    pub(crate) debug_keybindings: bool,
+    pub(crate) update_while_minimized: bool,
+    pub(crate) transition: Transition,
+    pub(crate) capture_requested: bool,
+    pub(crate) captured_frame: Option<RgbaImage>,
+    pub(crate) frame_history: FrameHistory,
+    pub(crate) image_updates: Vec<(Image, RgbaImage)>,
+    pub(crate) background_shader: Option<BackgroundShader>,
+    #[cfg(feature = "clipboard")]
+    pub(crate) paste_requested: bool,
 }
 
 // Public API
 // ----------
 
 impl Sim2D {
+    /// The time since the last frame in seconds, multiplied by the time
+    /// scale.
     pub fn dt(&self) -> f32 {
+        self.dt_f64() as f32
+    }
+
+    /// The real time since the last frame in seconds, ignoring the time scale.
+    pub fn unscaled_dt(&self) -> f32 {
+        self.delta_time as f32
+    }
+
+    /// The same as `dt()`, but in double precision.
+    ///
+    /// Long-running simulations which accumulate time or integrate with the
+    /// `math::DVec2` types can use this to avoid f32 rounding errors.
+    pub fn dt_f64(&self) -> f64 {
+        self.delta_time * self.time_scale as f64
+    }
+
+    /// The same as `unscaled_dt()`, but in double precision.
+    pub fn unscaled_dt_f64(&self) -> f64 {
         self.delta_time
     }
 
+    /// Scale the time reported by `dt()`.
+    ///
+    /// Values below 1 slow the simulation down and values above 1 speed it
+    /// up. Negative values are clamped to 0.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
     pub fn avg_frame_time(&self) -> &Duration {
-        &self.avg_frame_time
+        &self.frame_stats.frame.average
     }
 
     pub fn avg_sim_time(&self) -> &Duration {
-        &self.avg_sim_time
+        &self.frame_stats.simulation.average
     }
 
     pub fn avg_render_time(&self) -> &Duration {
-        &self.avg_render_time
+        &self.frame_stats.render.average
+    }
+
+    /// Detailed timing statistics for the last second of frames.
+    pub fn frame_stats(&self) -> &FrameStats {
+        &self.frame_stats
+    }
+
+    /// How long the previous frame's update took, including input handling
+    /// and transitions.
+    pub fn last_update_time(&self) -> Duration {
+        self.last_update_time
+    }
+
+    /// How long each stage of rendering the previous frame took on the CPU.
+    pub fn last_render_timings(&self) -> RenderTimings {
+        self.last_render_timings
+    }
+
+    /// Stop calling the sketch's update method.
+    ///
+    /// The last frame keeps being rendered and input callbacks still fire, so
+    /// the sketch can resume itself.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resume calling the sketch's update method every frame.
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.step_requested = false;
+    }
+
+    /// Pause if running, resume if paused.
+    pub fn toggle_pause(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Advance exactly one frame while paused.
+    ///
+    /// Does nothing if the simulation is not paused.
+    pub fn step_frame(&mut self) {
+        self.step_requested = self.paused;
+    }
+
+    /// Keep calling the sketch's update method while the window is minimized.
+    ///
+    /// Nothing is rendered while minimized and updates are throttled to about
+    /// 10 per second. Disabled by default, so minimized sketches pause.
+    pub fn set_update_while_minimized(&mut self, enabled: bool) {
+        self.update_while_minimized = enabled;
+    }
+
+    /// Set the transition used when the current sketch hands off to the next
+    /// sketch with `Sketch::load_sketch`.
+    pub fn set_transition(&mut self, transition: Transition) {
+        self.transition = transition;
+    }
+
+    /// Enable or disable the debug keybindings for pausing and stepping.
+    ///
+    /// When enabled, F9 toggles pause and F10 advances a single frame while
+    /// paused.
+    pub fn set_debug_keybindings(&mut self, enabled: bool) {
+        self.debug_keybindings = enabled;
+    }
+
+    /// Copy the pixels of the frame being drawn once it has been rendered.
+    ///
+    /// The image is available from `take_captured_frame` once the GPU has
+    /// finished drawing the frame, usually two or three updates later.
+    pub fn capture_frame(&mut self) {
+        self.capture_requested = true;
+    }
+
+    /// Take the most recently captured frame, if there is one.
+    pub fn take_captured_frame(&mut self) -> Option<RgbaImage> {
+        self.captured_frame.take()
+    }
+
+    /// Replace an image's pixels before the next frame is drawn, for images
+    /// which change every frame like video.
+    ///
+    /// `pixels` must be the same size as the image, otherwise the update is
+    /// skipped with a warning. Only the full size mip level is replaced, so
+    /// load changing images without mipmaps. Only the last update for each
+    /// image in a frame is used.
+    pub fn update_image(&mut self, image: Image, pixels: RgbaImage) {
+        self.image_updates
+            .retain(|(pending, _)| pending.texture_id() != image.texture_id());
+        self.image_updates.push((image, pixels));
+    }
+
+    /// Load the image on the system clipboard before the next update and
+    /// pass it to the sketch's `image_pasted` callback.
+    ///
+    /// Nothing happens when the clipboard doesn't hold an image. Bind it to
+    /// the usual shortcut in `key_pressed`:
+    ///
+    /// ```ignore
+    /// if key == Key::V && modifiers.contains(Modifiers::Control) {
+    ///     sim.paste_image();
+    /// }
+    /// ```
+    #[cfg(feature = "clipboard")]
+    pub fn paste_image(&mut self) {
+        self.paste_requested = true;
+    }
+
+    /// Keep a rolling buffer of the last `seconds` of rendered frames so
+    /// they can be saved with `export_gif`. Zero stops recording.
+    ///
+    /// Frames are kept at 20 per second and shrunk to at most 480 pixels
+    /// wide. Each kept frame is copied back from the GPU like
+    /// `capture_frame`.
+    pub fn record_frame_history(&mut self, seconds: f32) {
+        self.frame_history.set_duration(seconds as f64);
+    }
+
+    /// Save the last `seconds` of recorded frames as a looping GIF.
+    ///
+    /// The GIF is quantized to 256 colors with dithering and written on a
+    /// background thread, so the file appears shortly after this returns.
+    /// Fails if `record_frame_history` hasn't been called or no frames have
+    /// been recorded yet.
+    pub fn export_gif(
+        &mut self,
+        path: impl Into<PathBuf>,
+        seconds: f32,
+    ) -> Result<()> {
+        self.frame_history.export_gif(path.into(), seconds as f64)
     }
 }
 
@@ -47,10 +243,24 @@ impl Sim2D {
         Self {
             g,
             w,
+            rng: Random::from_entropy(),
             delta_time: 0.0,
-            avg_frame_time: Duration::default(),
-            avg_sim_time: Duration::default(),
-            avg_render_time: Duration::default(),
+            time_scale: 1.0,
+            frame_stats: FrameStats::default(),
+            last_update_time: Duration::ZERO,
+            last_render_timings: RenderTimings::default(),
+            paused: false,
+            step_requested: false,
+            debug_keybindings: false,
+            update_while_minimized: false,
+            transition: Transition::Cut,
+            capture_requested: false,
+            captured_frame: None,
+            frame_history: FrameHistory::default(),
+            image_updates: vec![],
+            background_shader: None,
+            #[cfg(feature = "clipboard")]
+            paste_requested: false,
         }
     }
 }
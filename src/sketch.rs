@@ -1,5 +1,8 @@
 use {
-    crate::{graphics::AssetLoader, sim2d::Sim2D},
+    crate::{
+        graphics::{AssetLoader, Image},
+        sim2d::Sim2D,
+    },
     anyhow::Result,
 };
 
@@ -36,12 +39,87 @@ pub trait Sketch {
     /// Called any time the mouse is released.
     fn mouse_released(&mut self, _sim: &mut Sim2D) {}
 
+    /// Called when the left mouse button is pressed and released without
+    /// dragging.
+    ///
+    /// The drag threshold can be configured with
+    /// `sim.w.set_click_distance()`.
+    fn mouse_clicked(&mut self, _sim: &mut Sim2D) {}
+
+    /// Called when the left mouse button is pressed twice in quick
+    /// succession.
+    ///
+    /// The thresholds can be configured with `sim.w.set_double_click_time()`
+    /// and `sim.w.set_click_distance()`.
+    fn mouse_double_clicked(&mut self, _sim: &mut Sim2D) {}
+
+    /// Called when a unicode character is entered with the keyboard.
+    ///
+    /// Unlike `key_pressed`, this respects the keyboard layout and input
+    /// methods, so it is the right choice for text entry. Characters composed
+    /// with an input method are only delivered after they are committed.
+    fn char_typed(&mut self, _sim: &mut Sim2D, _c: char) {}
+
+    /// Called any time the mouse wheel or touchpad is scrolled.
+    ///
+    /// # Params
+    ///
+    /// * `dx` - the horizontal scroll offset for this event
+    /// * `dy` - the vertical scroll offset for this event
+    fn scrolled(&mut self, _sim: &mut Sim2D, _dx: f32, _dy: f32) {}
+
     /// Called when a key on the keyboard is pressed.
-    fn key_pressed(&mut self, _sim: &mut Sim2D, _key: glfw::Key) {}
+    ///
+    /// # Params
+    ///
+    /// * `key` - the key which was pressed
+    /// * `modifiers` - the modifier keys (shift, control, alt, super) which
+    ///   were held when the key was pressed. e.g. check for ctrl+s with `key ==
+    ///   Key::S && modifiers.contains(Modifiers::Control)`
+    fn key_pressed(
+        &mut self,
+        _sim: &mut Sim2D,
+        _key: glfw::Key,
+        _modifiers: glfw::Modifiers,
+    ) {
+    }
 
     /// Called when a key on the keyboard is released.
     fn key_released(&mut self, _sim: &mut Sim2D, _key: glfw::Key) {}
 
+    /// Called when the window's content scale changes. For example, when the
+    /// window is moved to a monitor with a different DPI.
+    ///
+    /// The new scale is available with `sim.w.content_scale()`.
+    fn content_scale_changed(&mut self, _sim: &mut Sim2D) {}
+
+    /// Called with the clipboard's image after `sim.paste_image()`.
+    ///
+    /// The image is a new texture which can be drawn like any other image.
+    /// Nothing is called when the clipboard doesn't hold an image. Requires
+    /// the `clipboard` feature.
+    fn image_pasted(&mut self, _sim: &mut Sim2D, _image: Image) {}
+
+    /// Called for every raw GLFW window event before the more specific
+    /// callbacks like `mouse_pressed` or `key_pressed`.
+    ///
+    /// This is an escape hatch for events which don't have a dedicated
+    /// callback, like focus changes or the window being iconified.
+    fn raw_event(&mut self, _sim: &mut Sim2D, _event: &glfw::WindowEvent) {}
+
+    /// Serialize any state which should survive a hot reload.
+    ///
+    /// See [crate::hotreload]. The bytes are passed to `restore_state` on the
+    /// newly loaded sketch.
+    fn save_state(&mut self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Restore state saved by `save_state` before a hot reload.
+    ///
+    /// This is called after `setup`.
+    fn restore_state(&mut self, _state: &[u8]) {}
+
     /// Implement to return a new boxed sketch to hand off to another sketch.
     ///
     /// This way sketches can be chained together.
@@ -0,0 +1,229 @@
+use {
+    crate::{
+        graphics::{AssetLoader, Image},
+        math::{Rect, Vec2},
+        sim2d::Sim2D,
+        DynSketch, Sketch,
+    },
+    anyhow::Result,
+};
+
+/// Runs several sketches in one window.
+///
+/// Layers are updated and drawn in the order they were added, so later layers
+/// draw on top of earlier ones. A layer either covers the whole window or a
+/// rectangular viewport. Inside a viewport the sketch sees the viewport's size
+/// as the window size and its center as the origin.
+///
+/// Input is routed like this:
+///
+/// * mouse events go to the topmost layer under the cursor, except that a
+///   release goes to the layer which received the press
+/// * keyboard and text events go to the focused layer. Pressing a mouse button
+///   focuses the layer under the cursor, and the last layer starts focused.
+/// * all other events go to every layer
+///
+/// Viewports don't clip, so sprites drawn outside of a viewport stay visible.
+/// Sketches inside a viewport can't resize the window.
+///
+/// `save_state` and `load_sketch` aren't forwarded to the layers, so a
+/// stack doesn't keep its layers' state across a hot reload and a layer
+/// can't hand off to another sketch.
+#[derive(Default)]
+pub struct SketchStack {
+    layers: Vec<Layer>,
+    focused: usize,
+    pressed: Option<usize>,
+}
+
+struct Layer {
+    sketch: DynSketch,
+    viewport: Option<(Vec2, Vec2)>,
+}
+
+// Public API
+// ----------
+
+impl SketchStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a layer which covers the whole window.
+    pub fn add_layer(&mut self, sketch: impl Sketch + Send + 'static) {
+        self.push(sketch, None);
+    }
+
+    /// Add a layer which covers part of the window.
+    ///
+    /// # Params
+    ///
+    /// * `center` - the viewport's center in window coordinates
+    /// * `size` - the viewport's width and height
+    pub fn add_viewport(
+        &mut self,
+        sketch: impl Sketch + Send + 'static,
+        center: Vec2,
+        size: Vec2,
+    ) {
+        self.push(sketch, Some((center, size)));
+    }
+}
+
+// Private API
+// -----------
+
+impl SketchStack {
+    fn push(
+        &mut self,
+        sketch: impl Sketch + Send + 'static,
+        viewport: Option<(Vec2, Vec2)>,
+    ) {
+        self.layers.push(Layer {
+            sketch: Box::new(sketch),
+            viewport,
+        });
+        self.focused = self.layers.len() - 1;
+    }
+
+    /// The index of the topmost layer under the mouse.
+    fn hovered(&self, sim: &Sim2D) -> Option<usize> {
+        let mouse = sim.w.mouse_pos();
+        self.layers.iter().rposition(|layer| match layer.viewport {
+            None => true,
+            Some((center, size)) => {
+                Rect::from_center(center, size).contains(mouse)
+            }
+        })
+    }
+
+    /// Call `f` for the layer at `index` with the window state mapped into
+    /// the layer's viewport.
+    fn with_layer<F>(&mut self, index: usize, sim: &mut Sim2D, f: F)
+    where
+        F: FnOnce(&mut DynSketch, &mut Sim2D),
+    {
+        let layer = &mut self.layers[index];
+        match layer.viewport {
+            None => f(&mut layer.sketch, sim),
+            Some((center, size)) => {
+                let restore = sim.w.enter_viewport(center, size);
+                let first_sprite = sim.g.sprite_count();
+                f(&mut layer.sketch, sim);
+                sim.g.offset_sprites_since(first_sprite, center);
+                sim.w.exit_viewport(restore);
+            }
+        }
+    }
+
+    fn for_each_layer<F>(&mut self, sim: &mut Sim2D, mut f: F)
+    where
+        F: FnMut(&mut DynSketch, &mut Sim2D),
+    {
+        for index in 0..self.layers.len() {
+            self.with_layer(index, sim, &mut f);
+        }
+    }
+
+    fn with_hovered<F>(&mut self, sim: &mut Sim2D, f: F)
+    where
+        F: FnOnce(&mut DynSketch, &mut Sim2D),
+    {
+        if let Some(index) = self.hovered(sim) {
+            self.with_layer(index, sim, f);
+        }
+    }
+
+    fn with_focused<F>(&mut self, sim: &mut Sim2D, f: F)
+    where
+        F: FnOnce(&mut DynSketch, &mut Sim2D),
+    {
+        if self.focused < self.layers.len() {
+            self.with_layer(self.focused, sim, f);
+        }
+    }
+}
+
+impl Sketch for SketchStack {
+    fn preload(&mut self, asset_loader: &mut AssetLoader) -> Result<()> {
+        for layer in &mut self.layers {
+            layer.sketch.preload(asset_loader)?;
+        }
+        Ok(())
+    }
+
+    fn setup(&mut self, sim: &mut Sim2D) {
+        self.for_each_layer(sim, |sketch, sim| sketch.setup(sim));
+    }
+
+    fn mouse_moved(&mut self, sim: &mut Sim2D) {
+        self.with_hovered(sim, |sketch, sim| sketch.mouse_moved(sim));
+    }
+
+    fn mouse_pressed(&mut self, sim: &mut Sim2D) {
+        self.pressed = self.hovered(sim);
+        if let Some(index) = self.pressed {
+            self.focused = index;
+        }
+        self.with_hovered(sim, |sketch, sim| sketch.mouse_pressed(sim));
+    }
+
+    fn mouse_released(&mut self, sim: &mut Sim2D) {
+        // Release the layer which saw the press, even when the mouse was
+        // dragged off of it, so it doesn't get stuck mid-drag.
+        if let Some(index) = self.pressed.take().or_else(|| self.hovered(sim)) {
+            self.with_layer(index, sim, |sketch, sim| {
+                sketch.mouse_released(sim)
+            });
+        }
+    }
+
+    fn mouse_clicked(&mut self, sim: &mut Sim2D) {
+        self.with_hovered(sim, |sketch, sim| sketch.mouse_clicked(sim));
+    }
+
+    fn mouse_double_clicked(&mut self, sim: &mut Sim2D) {
+        self.with_hovered(sim, |sketch, sim| sketch.mouse_double_clicked(sim));
+    }
+
+    fn char_typed(&mut self, sim: &mut Sim2D, c: char) {
+        self.with_focused(sim, |sketch, sim| sketch.char_typed(sim, c));
+    }
+
+    fn scrolled(&mut self, sim: &mut Sim2D, dx: f32, dy: f32) {
+        self.with_hovered(sim, |sketch, sim| sketch.scrolled(sim, dx, dy));
+    }
+
+    fn key_pressed(
+        &mut self,
+        sim: &mut Sim2D,
+        key: glfw::Key,
+        modifiers: glfw::Modifiers,
+    ) {
+        self.with_focused(sim, |sketch, sim| {
+            sketch.key_pressed(sim, key, modifiers)
+        });
+    }
+
+    fn key_released(&mut self, sim: &mut Sim2D, key: glfw::Key) {
+        self.with_focused(sim, |sketch, sim| sketch.key_released(sim, key));
+    }
+
+    fn content_scale_changed(&mut self, sim: &mut Sim2D) {
+        self.for_each_layer(sim, |sketch, sim| {
+            sketch.content_scale_changed(sim)
+        });
+    }
+
+    fn image_pasted(&mut self, sim: &mut Sim2D, image: Image) {
+        self.with_focused(sim, |sketch, sim| sketch.image_pasted(sim, image));
+    }
+
+    fn raw_event(&mut self, sim: &mut Sim2D, event: &glfw::WindowEvent) {
+        self.for_each_layer(sim, |sketch, sim| sketch.raw_event(sim, event));
+    }
+
+    fn update(&mut self, sim: &mut Sim2D) {
+        self.for_each_layer(sim, |sketch, sim| sketch.update(sim));
+    }
+}
@@ -0,0 +1,397 @@
+//! Share sketch state between Sim2D instances over TCP, for installations
+//! where several machines or windows must animate in lockstep.
+//!
+//! One instance leads. It updates the state and sends it every frame. The
+//! other instances follow and draw whatever state the leader sent last.
+//!
+//! This module is only available with the `sync` feature.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct State {
+//!     particles: Vec<Vec2>,
+//! }
+//!
+//! // in update, on the leader
+//! self.state.step(sim.dt());
+//! self.leader.send(&self.state);
+//!
+//! // in update, on each follower
+//! if let Some(state) = self.follower.receive() {
+//!     self.state = state;
+//! }
+//! ```
+//!
+//! States are encoded as JSON, so send the state needed to draw a frame
+//! rather than everything the simulation uses.
+
+use {
+    anyhow::{bail, Context, Result},
+    serde::{de::DeserializeOwned, Serialize},
+    std::{
+        io::{ErrorKind, Read, Write},
+        marker::PhantomData,
+        net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+        time::{Duration, Instant},
+    },
+};
+
+/// Followers which fall this far behind are disconnected instead of
+/// buffering without bound.
+const MAX_UNSENT_BYTES: usize = 16 * 1024 * 1024;
+
+/// How often a follower tries to reconnect after losing the leader.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Each message is a u32 length, then a u64 frame number, then the state.
+const HEADER_SIZE: usize = 12;
+
+/// Sends the state to every connected follower.
+pub struct Leader<T> {
+    listener: TcpListener,
+    followers: Vec<Connection>,
+    frame: u64,
+    _state: PhantomData<fn(&T)>,
+}
+
+/// Receives the state from a leader.
+pub struct Follower<T> {
+    address: SocketAddr,
+    connection: Option<Connection>,
+    last_attempt: Instant,
+    frame: Option<u64>,
+    _state: PhantomData<fn() -> T>,
+}
+
+// Public API
+// ----------
+
+impl<T: Serialize> Leader<T> {
+    /// Accept followers on a TCP port on every network interface.
+    pub fn bind(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .with_context(|| format!("Unable to bind sync port {}", port))?;
+        listener
+            .set_nonblocking(true)
+            .context("Unable to make the sync listener non-blocking!")?;
+        Ok(Self {
+            listener,
+            followers: vec![],
+            frame: 0,
+            _state: PhantomData,
+        })
+    }
+
+    /// The address followers connect to. Useful after binding port 0 to let
+    /// the system pick a free port.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener
+            .local_addr()
+            .context("Unable to get the sync listener address!")
+    }
+
+    /// Accept new followers, then send them the state for this frame. Call
+    /// once per frame.
+    ///
+    /// Followers which disconnect or fall too far behind are dropped with a
+    /// warning.
+    pub fn send(&mut self, state: &T) -> Result<()> {
+        self.accept_followers();
+
+        let message = encode(self.frame, state)?;
+        self.frame += 1;
+        self.followers.retain_mut(|follower| {
+            follower.unsent.extend_from_slice(&message);
+            match follower.flush() {
+                Ok(()) if follower.unsent.len() <= MAX_UNSENT_BYTES => true,
+                Ok(()) => {
+                    log::warn!(
+                        "Disconnecting sync follower {}, it fell behind",
+                        follower.peer
+                    );
+                    false
+                }
+                Err(error) => {
+                    log::warn!(
+                        "Lost sync follower {}: {}",
+                        follower.peer,
+                        error
+                    );
+                    false
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// The number of frames sent so far.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn follower_count(&self) -> usize {
+        self.followers.len()
+    }
+}
+
+impl<T: DeserializeOwned> Follower<T> {
+    /// Connect to a leader. Fails if the leader isn't reachable.
+    ///
+    /// Later disconnects are retried automatically.
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Self> {
+        let address = address
+            .to_socket_addrs()
+            .context("Invalid sync leader address!")?
+            .next()
+            .context("The sync leader address didn't resolve!")?;
+        let connection = Connection::connect(address)?;
+        Ok(Self {
+            address,
+            connection: Some(connection),
+            last_attempt: Instant::now(),
+            frame: None,
+            _state: PhantomData,
+        })
+    }
+
+    /// The newest state received since the last call. Older states which
+    /// arrived in the same frame are skipped. Call once per frame.
+    pub fn receive(&mut self) -> Option<T> {
+        self.reconnect_if_needed();
+        let connection = self.connection.as_mut()?;
+
+        let message = connection
+            .read_available()
+            .map_err(anyhow::Error::from)
+            .and_then(|()| connection.take_newest_message());
+        let (frame, payload) = match message {
+            Ok(message) => message?,
+            Err(error) => {
+                // The stream can't be realigned after a bad header, so start
+                // over with a new connection.
+                log::warn!(
+                    "Lost the sync leader {}: {:#}",
+                    self.address,
+                    error
+                );
+                self.connection = None;
+                return None;
+            }
+        };
+        match serde_json::from_slice(&payload) {
+            Ok(state) => {
+                self.frame = Some(frame);
+                Some(state)
+            }
+            Err(error) => {
+                log::warn!("Invalid sync state for frame {}: {}", frame, error);
+                None
+            }
+        }
+    }
+
+    /// The leader's frame number for the most recently received state.
+    pub fn frame(&self) -> Option<u64> {
+        self.frame
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+}
+
+// Private API
+// -----------
+
+/// A non-blocking stream with buffers for partial reads and writes.
+struct Connection {
+    stream: TcpStream,
+    peer: SocketAddr,
+    unsent: Vec<u8>,
+    received: Vec<u8>,
+}
+
+impl Connection {
+    fn new(stream: TcpStream, peer: SocketAddr) -> Result<Self> {
+        stream
+            .set_nonblocking(true)
+            .context("Unable to make the sync stream non-blocking!")?;
+        stream
+            .set_nodelay(true)
+            .context("Unable to disable Nagle's algorithm!")?;
+        Ok(Self {
+            stream,
+            peer,
+            unsent: vec![],
+            received: vec![],
+        })
+    }
+
+    fn connect(address: SocketAddr) -> Result<Self> {
+        let stream = TcpStream::connect_timeout(&address, RECONNECT_INTERVAL)
+            .with_context(|| {
+            format!("Unable to connect to sync leader {}", address)
+        })?;
+        Self::new(stream, address)
+    }
+
+    /// Write as much of the unsent data as the socket accepts.
+    fn flush(&mut self) -> std::io::Result<()> {
+        while !self.unsent.is_empty() {
+            match self.stream.write(&self.unsent) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.unsent.drain(..written);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => (),
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    /// Read everything which has arrived so far.
+    fn read_available(&mut self) -> std::io::Result<()> {
+        let mut chunk = [0u8; 64 * 1024];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(size) => self.received.extend_from_slice(&chunk[..size]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    return Ok(())
+                }
+                Err(error) if error.kind() == ErrorKind::Interrupted => (),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Remove every complete message and return the last one.
+    fn take_newest_message(&mut self) -> Result<Option<(u64, Vec<u8>)>> {
+        let (newest, consumed) = match newest_message(&self.received)? {
+            Some(newest) => newest,
+            None => return Ok(None),
+        };
+        let message = (newest.0, newest.1.to_vec());
+        self.received.drain(..consumed);
+        Ok(Some(message))
+    }
+}
+
+impl<T> Leader<T> {
+    fn accept_followers(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, peer)) => match Connection::new(stream, peer) {
+                    Ok(connection) => {
+                        log::info!("Sync follower {} connected", peer);
+                        self.followers.push(connection);
+                    }
+                    Err(error) => {
+                        log::warn!("Unable to accept {}: {:?}", peer, error)
+                    }
+                },
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => {
+                    log::warn!("Unable to accept sync follower: {}", error);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl<T> Follower<T> {
+    fn reconnect_if_needed(&mut self) {
+        if self.connection.is_some()
+            || self.last_attempt.elapsed() < RECONNECT_INTERVAL
+        {
+            return;
+        }
+        self.last_attempt = Instant::now();
+        match Connection::connect(self.address) {
+            Ok(connection) => {
+                log::info!("Reconnected to sync leader {}", self.address);
+                self.connection = Some(connection);
+            }
+            Err(error) => log::trace!("{:?}", error),
+        }
+    }
+}
+
+fn encode<T: Serialize>(frame: u64, state: &T) -> Result<Vec<u8>> {
+    let payload =
+        serde_json::to_vec(state).context("Unable to encode sync state!")?;
+    let mut message = Vec::with_capacity(HEADER_SIZE + payload.len());
+    message.extend(((payload.len() + 8) as u32).to_be_bytes());
+    message.extend(frame.to_be_bytes());
+    message.extend(payload);
+    Ok(message)
+}
+
+/// A message's frame number and payload.
+type Message<'a> = (u64, &'a [u8]);
+
+/// Find the last complete message in `data`. Returns the message's frame
+/// and payload, and the number of bytes used by every complete message.
+///
+/// Fails when a header's length is too short to hold the frame number.
+fn newest_message(data: &[u8]) -> Result<Option<(Message<'_>, usize)>> {
+    let mut newest = None;
+    let mut offset = 0;
+    while data.len() - offset >= HEADER_SIZE {
+        let header = &data[offset..offset + HEADER_SIZE];
+        let length =
+            u32::from_be_bytes([header[0], header[1], header[2], header[3]])
+                as usize;
+        if length < 8 {
+            bail!("Invalid sync message length {}", length);
+        }
+        let end = offset + 4 + length;
+        if end > data.len() {
+            break;
+        }
+        let mut frame = [0u8; 8];
+        frame.copy_from_slice(&header[4..]);
+        newest =
+            Some((u64::from_be_bytes(frame), &data[offset + HEADER_SIZE..end]));
+        offset = end;
+    }
+    Ok(newest.map(|newest| (newest, offset)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_leader_to_follower() -> Result<()> {
+        let mut leader = Leader::<Vec<f32>>::bind(0)?;
+        let port = leader.local_addr()?.port();
+        let mut follower = Follower::<Vec<f32>>::connect(("127.0.0.1", port))?;
+
+        // The newest state wins when several arrive between receives.
+        let mut received = None;
+        for _ in 0..100 {
+            leader.send(&vec![1.0, 2.0])?;
+            leader.send(&vec![3.0])?;
+            std::thread::sleep(Duration::from_millis(10));
+            received = follower.receive();
+            if received == Some(vec![3.0]) {
+                break;
+            }
+        }
+        assert_eq!(leader.follower_count(), 1);
+        assert_eq!(received, Some(vec![3.0]));
+        assert_eq!(follower.frame().map(|frame| frame % 2), Some(1));
+
+        let partial = encode(7, &vec![1.0f32])?;
+        assert!(newest_message(&partial[..partial.len() - 1])?.is_none());
+
+        let mut invalid = encode(8, &vec![2.0f32])?;
+        invalid[..4].copy_from_slice(&4u32.to_be_bytes());
+        assert!(newest_message(&invalid).is_err());
+        Ok(())
+    }
+}
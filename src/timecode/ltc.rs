@@ -0,0 +1,277 @@
+#[cfg(feature = "audio")]
+use {
+    super::TimecodeClock,
+    crate::audio,
+    anyhow::Result,
+    std::time::{Duration, Instant},
+};
+
+use super::{FrameRate, Timecode};
+
+/// Each LTC frame is 80 bits, ending with a 16 bit sync word.
+const BITS_PER_FRAME: u32 = 80;
+
+/// The sync word 0011 1111 1111 1101, in the order it's transmitted, read
+/// as a little-endian number.
+const SYNC_WORD: u16 = 0xBFFC;
+
+/// Signals quieter than this are treated as silence.
+const THRESHOLD: f32 = 0.02;
+
+/// Decodes SMPTE linear timecode from audio samples.
+///
+/// The frame rate is measured from the signal, so the decoder works with
+/// any rate without configuration.
+#[derive(Debug, Clone)]
+pub struct LtcDecoder {
+    sample_rate: f32,
+
+    /// The estimated length of one bit in samples.
+    bit_period: f32,
+
+    /// Whether the signal was last above or below the threshold.
+    is_high: bool,
+
+    /// Samples since the last transition.
+    since_transition: f32,
+
+    /// True after the first half of a one bit.
+    half_bit: bool,
+
+    /// The last 80 bits, with the newest in the highest bit.
+    bits: u128,
+}
+
+/// Audio timecode from the default audio input, usually a line input fed
+/// by the show controller.
+#[cfg(feature = "audio")]
+pub struct LtcInput {
+    input: audio::Input,
+    decoder: LtcDecoder,
+    clock: TimecodeClock,
+}
+
+// Public API
+// ----------
+
+impl LtcDecoder {
+    pub fn new(sample_rate: u32) -> Self {
+        let sample_rate = sample_rate as f32;
+        Self {
+            sample_rate,
+            bit_period: sample_rate / (BITS_PER_FRAME as f32 * 25.0),
+            is_high: false,
+            since_transition: 0.0,
+            half_bit: false,
+            bits: 0,
+        }
+    }
+
+    /// Decode a block of mono samples.
+    ///
+    /// Returns every complete frame, with the index of the sample where
+    /// the frame ended. The timecode is the frame which just ended, so the
+    /// current time at that sample is one frame later.
+    pub fn push(&mut self, samples: &[f32]) -> Vec<(usize, Timecode)> {
+        let mut frames = vec![];
+        for (index, &sample) in samples.iter().enumerate() {
+            self.since_transition += 1.0;
+            let is_high = if self.is_high {
+                sample > -THRESHOLD
+            } else {
+                sample > THRESHOLD
+            };
+            if is_high == self.is_high {
+                continue;
+            }
+            self.is_high = is_high;
+            let interval = std::mem::replace(&mut self.since_transition, 0.0);
+            if let Some(timecode) = self.transition(interval) {
+                frames.push((index, timecode));
+            }
+        }
+        frames
+    }
+}
+
+#[cfg(feature = "audio")]
+impl LtcInput {
+    /// Start decoding timecode from the system's default input device.
+    pub fn open_default() -> Result<Self> {
+        let input = audio::Input::open_default()?;
+        Ok(Self {
+            decoder: LtcDecoder::new(input.sample_rate()),
+            input,
+            clock: TimecodeClock::new(),
+        })
+    }
+
+    /// Decode the audio received since the last update. Call once per
+    /// frame.
+    pub fn update(&mut self) {
+        self.input.update();
+        let now = Instant::now();
+        let samples = self.input.samples();
+        let sample_rate = self.input.sample_rate() as f64;
+        for (index, timecode) in self.decoder.push(samples) {
+            // The newest sample arrived about now, so earlier samples
+            // arrived earlier by their distance from the end.
+            let age = (samples.len() - index) as f64 / sample_rate;
+            let received =
+                now.checked_sub(Duration::from_secs_f64(age)).unwrap_or(now);
+            let next = Timecode::from_seconds(
+                timecode.to_seconds() + 1.0 / timecode.rate.fps(),
+                timecode.rate,
+            );
+            self.clock.sync(next, received, true);
+        }
+    }
+
+    pub fn clock(&self) -> &TimecodeClock {
+        &self.clock
+    }
+
+    /// Adjust how long the clock keeps running through dropouts.
+    pub fn clock_mut(&mut self) -> &mut TimecodeClock {
+        &mut self.clock
+    }
+}
+
+// Private API
+// -----------
+
+impl LtcDecoder {
+    /// Handle a transition `interval` samples after the previous one.
+    ///
+    /// LTC uses biphase mark code: every bit starts with a transition, and
+    /// ones have a second transition in the middle.
+    fn transition(&mut self, interval: f32) -> Option<Timecode> {
+        // Ignore intervals far outside the range of 24 to 30 fps, like
+        // noise or the end of a recording.
+        let max_period = self.sample_rate / (BITS_PER_FRAME as f32 * 24.0);
+        let min_period = self.sample_rate / (BITS_PER_FRAME as f32 * 30.0);
+        if interval < min_period * 0.3 || interval > max_period * 1.5 {
+            self.half_bit = false;
+            return None;
+        }
+
+        let bit = if interval > self.bit_period * 0.75 {
+            self.half_bit = false;
+            self.adjust_period(interval);
+            0
+        } else if self.half_bit {
+            self.half_bit = false;
+            self.adjust_period(interval * 2.0);
+            1
+        } else {
+            self.half_bit = true;
+            return None;
+        };
+
+        self.bits = (self.bits >> 1) | ((bit as u128) << (BITS_PER_FRAME - 1));
+        if (self.bits >> 64) as u16 != SYNC_WORD {
+            return None;
+        }
+        self.decode()
+    }
+
+    fn adjust_period(&mut self, period: f32) {
+        self.bit_period += (period - self.bit_period) * 0.1;
+    }
+
+    /// Read the timecode from a frame which ends with the sync word.
+    fn decode(&self) -> Option<Timecode> {
+        let field = |start: u32, len: u32| {
+            ((self.bits >> start) & ((1 << len) - 1)) as u8
+        };
+        let fps = self.sample_rate / (self.bit_period * BITS_PER_FRAME as f32);
+        let drop_frame = field(10, 1) == 1;
+        let rate = match fps {
+            fps if drop_frame && fps > 27.5 => FrameRate::Fps30Drop,
+            fps if fps > 27.5 => FrameRate::Fps30,
+            fps if fps > 24.5 => FrameRate::Fps25,
+            _ => FrameRate::Fps24,
+        };
+        let timecode = Timecode {
+            frames: field(0, 4) + 10 * field(8, 2),
+            seconds: field(16, 4) + 10 * field(24, 3),
+            minutes: field(32, 4) + 10 * field(40, 3),
+            hours: field(48, 4) + 10 * field(56, 2),
+            rate,
+        };
+        let is_valid = timecode.frames < rate.nominal_fps() as u8
+            && timecode.seconds < 60
+            && timecode.minutes < 60
+            && timecode.hours < 24;
+        is_valid.then_some(timecode)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Encode a timecode as one LTC frame of biphase mark audio.
+    fn encode(
+        timecode: &Timecode,
+        samples_per_bit: usize,
+        level: &mut f32,
+    ) -> Vec<f32> {
+        let digits = |value: u8| (value % 10, value / 10);
+        let mut bits: u128 = 0;
+        let mut set = |start: u32, value: u8| bits |= (value as u128) << start;
+        let (frame_units, frame_tens) = digits(timecode.frames);
+        let (second_units, second_tens) = digits(timecode.seconds);
+        let (minute_units, minute_tens) = digits(timecode.minutes);
+        let (hour_units, hour_tens) = digits(timecode.hours);
+        set(0, frame_units);
+        set(8, frame_tens);
+        set(10, (timecode.rate == FrameRate::Fps30Drop) as u8);
+        set(16, second_units);
+        set(24, second_tens);
+        set(32, minute_units);
+        set(40, minute_tens);
+        set(48, hour_units);
+        set(56, hour_tens);
+        bits |= (SYNC_WORD as u128) << 64;
+
+        let mut samples = vec![];
+        for i in 0..BITS_PER_FRAME {
+            *level = -*level;
+            samples.resize(samples.len() + samples_per_bit / 2, *level);
+            if (bits >> i) & 1 == 1 {
+                *level = -*level;
+            }
+            samples.resize(samples.len() + samples_per_bit / 2, *level);
+        }
+        samples
+    }
+
+    #[test]
+    fn test_decode() {
+        // 48kHz at 25fps is 24 samples per bit.
+        let mut decoder = LtcDecoder::new(48_000);
+        let mut level = 0.5;
+        let start = Timecode {
+            hours: 10,
+            minutes: 59,
+            seconds: 58,
+            frames: 20,
+            rate: FrameRate::Fps25,
+        };
+        let mut decoded = vec![];
+        for frame in 0..10 {
+            let timecode = Timecode::from_seconds(
+                start.to_seconds() + frame as f64 / 25.0 + 0.001,
+                FrameRate::Fps25,
+            );
+            let samples = encode(&timecode, 24, &mut level);
+            decoded.extend(decoder.push(&samples).into_iter().map(|(_, t)| t));
+        }
+        // The last bit of a frame is only complete when the next frame
+        // starts, so the final frame is still pending.
+        assert_eq!(decoded.len(), 9);
+        assert_eq!(decoded[0].to_string(), "10:59:58:20");
+        assert_eq!(decoded[8].to_string(), "10:59:59:03");
+    }
+}
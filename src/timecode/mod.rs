@@ -0,0 +1,259 @@
+//! Chase external SMPTE timecode so playback-based installations stay locked
+//! to a show controller.
+//!
+//! Timecode arrives as MIDI Time Code with the `midi` feature, or as LTC
+//! audio with the `audio` feature. Both feed a `TimecodeClock`, which
+//! smooths over the gaps between messages:
+//!
+//! ```ignore
+//! // in setup
+//! self.mtc = timecode::MtcInput::open("")?;
+//!
+//! // in update
+//! self.mtc.update();
+//! if let Some(seconds) = self.mtc.clock().seconds() {
+//!     self.show.seek(seconds);
+//! }
+//! ```
+//!
+//! Use the clock's time instead of `Instant` or an accumulated `sim.dt()`,
+//! which drift away from the controller over a long show.
+
+mod ltc;
+#[cfg(feature = "midi")]
+mod mtc;
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+pub use self::ltc::LtcDecoder;
+#[cfg(feature = "audio")]
+pub use self::ltc::LtcInput;
+#[cfg(feature = "midi")]
+pub use self::mtc::MtcInput;
+
+/// How long a clock keeps running without a new timecode before it stops.
+const DEFAULT_FREEWHEEL: Duration = Duration::from_millis(250);
+
+/// SMPTE frame rates.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrameRate {
+    Fps24,
+    Fps25,
+
+    /// 29.97 frames per second, where frame numbers 0 and 1 are skipped at
+    /// the start of every minute except each tenth minute.
+    Fps30Drop,
+    Fps30,
+}
+
+/// A SMPTE timecode address.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub rate: FrameRate,
+}
+
+/// The time from the most recent timecode, advanced with the local clock
+/// until the next one arrives.
+#[derive(Debug, Clone)]
+pub struct TimecodeClock {
+    /// The latest timecode in seconds, when it was received, and whether
+    /// the source is playing.
+    latest: Option<(f64, Instant, bool)>,
+    rate: Option<FrameRate>,
+    freewheel: Duration,
+}
+
+// Public API
+// ----------
+
+impl FrameRate {
+    /// The number of frames per second, 29.97 for drop-frame.
+    pub fn fps(self) -> f64 {
+        match self {
+            FrameRate::Fps24 => 24.0,
+            FrameRate::Fps25 => 25.0,
+            FrameRate::Fps30Drop => 30000.0 / 1001.0,
+            FrameRate::Fps30 => 30.0,
+        }
+    }
+
+    /// The number of frame labels in each second.
+    fn nominal_fps(self) -> u32 {
+        match self {
+            FrameRate::Fps24 => 24,
+            FrameRate::Fps25 => 25,
+            FrameRate::Fps30Drop | FrameRate::Fps30 => 30,
+        }
+    }
+}
+
+impl Timecode {
+    /// The number of frames since 00:00:00:00.
+    pub fn frame_count(&self) -> u64 {
+        let fps = self.rate.nominal_fps() as u64;
+        let total_minutes = self.hours as u64 * 60 + self.minutes as u64;
+        let labels = (total_minutes * 60 + self.seconds as u64) * fps
+            + self.frames as u64;
+        if self.rate == FrameRate::Fps30Drop {
+            labels - 2 * (total_minutes - total_minutes / 10)
+        } else {
+            labels
+        }
+    }
+
+    /// The time since 00:00:00:00 in seconds.
+    pub fn to_seconds(&self) -> f64 {
+        self.frame_count() as f64 / self.rate.fps()
+    }
+
+    /// The timecode for a time in seconds. Times past 24 hours wrap.
+    pub fn from_seconds(seconds: f64, rate: FrameRate) -> Self {
+        let fps = rate.nominal_fps() as u64;
+        let frames_per_day = 24.0 * 60.0 * 60.0 * rate.fps();
+        let mut frame =
+            ((seconds * rate.fps()).max(0.0) % frames_per_day).floor() as u64;
+        if rate == FrameRate::Fps30Drop {
+            // Add back the skipped labels: 18 per ten minutes, and 2 for
+            // every minute after the first in the current ten minutes.
+            let per_ten_minutes = 17982;
+            let per_minute = 1798;
+            let tens = frame / per_ten_minutes;
+            let rest = frame % per_ten_minutes;
+            frame += 18 * tens;
+            if rest > 2 {
+                frame += 2 * ((rest - 2) / per_minute);
+            }
+        }
+        Self {
+            hours: (frame / (fps * 3600)) as u8,
+            minutes: (frame / (fps * 60) % 60) as u8,
+            seconds: (frame / fps % 60) as u8,
+            frames: (frame % fps) as u8,
+            rate,
+        }
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.rate == FrameRate::Fps30Drop {
+            ';'
+        } else {
+            ':'
+        };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours, self.minutes, self.seconds, separator, self.frames
+        )
+    }
+}
+
+impl TimecodeClock {
+    pub fn new() -> Self {
+        Self {
+            latest: None,
+            rate: None,
+            freewheel: DEFAULT_FREEWHEEL,
+        }
+    }
+
+    /// Set how long the clock keeps running after timecode stops arriving.
+    /// Longer times ride out dropouts, shorter times stop sooner when the
+    /// show stops.
+    pub fn set_freewheel(&mut self, freewheel: Duration) {
+        self.freewheel = freewheel;
+    }
+
+    /// Jump to a timecode received at `received`.
+    ///
+    /// # Params
+    ///
+    /// * `playing` - false for a locate while the source is stopped, so the
+    ///   clock holds the position instead of running.
+    pub fn sync(
+        &mut self,
+        timecode: Timecode,
+        received: Instant,
+        playing: bool,
+    ) {
+        self.latest = Some((timecode.to_seconds(), received, playing));
+        self.rate = Some(timecode.rate);
+    }
+
+    /// The current time in seconds, or None before the first timecode.
+    pub fn seconds(&self) -> Option<f64> {
+        let (seconds, received, playing) = self.latest?;
+        if !playing {
+            return Some(seconds);
+        }
+        let elapsed = received.elapsed().min(self.freewheel);
+        Some(seconds + elapsed.as_secs_f64())
+    }
+
+    /// The current time as a timecode, or None before the first timecode.
+    pub fn timecode(&self) -> Option<Timecode> {
+        Some(Timecode::from_seconds(self.seconds()?, self.rate?))
+    }
+
+    /// True while timecode is arriving and the source is playing.
+    pub fn is_running(&self) -> bool {
+        match self.latest {
+            Some((_, received, playing)) => {
+                playing && received.elapsed() < self.freewheel
+            }
+            None => false,
+        }
+    }
+}
+
+impl Default for TimecodeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_drop_frame() {
+        let timecode = Timecode {
+            hours: 1,
+            minutes: 23,
+            seconds: 45,
+            frames: 12,
+            rate: FrameRate::Fps30Drop,
+        };
+        assert_eq!(timecode.to_string(), "01:23:45;12");
+        for rate in [FrameRate::Fps30Drop, FrameRate::Fps25] {
+            let timecode = Timecode { rate, ..timecode };
+            let seconds = timecode.to_seconds() + 0.001;
+            assert_eq!(Timecode::from_seconds(seconds, rate), timecode);
+        }
+
+        // 00:00:59;29 is followed by 00:01:00;02.
+        let before = Timecode {
+            hours: 0,
+            minutes: 0,
+            seconds: 59,
+            frames: 29,
+            rate: FrameRate::Fps30Drop,
+        };
+        let after = Timecode {
+            minutes: 1,
+            seconds: 0,
+            frames: 2,
+            ..before
+        };
+        assert_eq!(before.frame_count() + 1, after.frame_count());
+    }
+}
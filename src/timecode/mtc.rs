@@ -0,0 +1,212 @@
+use {
+    super::{FrameRate, Timecode, TimecodeClock},
+    anyhow::{anyhow, bail, Context, Result},
+    midir::{MidiInput, MidiInputConnection},
+    std::{
+        sync::mpsc::{self, Receiver, Sender},
+        time::Instant,
+    },
+};
+
+const CLIENT_NAME: &str = "sim2d-mtc";
+
+/// A timecode, when it arrived, and whether the source is playing.
+type Sync = (Timecode, Instant, bool);
+
+/// MIDI Time Code from a show controller or DAW.
+///
+/// Quarter frame messages are chased while the source plays, and full frame
+/// messages move the clock while it's stopped.
+pub struct MtcInput {
+    receiver: Receiver<Sync>,
+    clock: TimecodeClock,
+    _connections: Vec<MidiInputConnection<(Sender<Sync>, MtcParser)>>,
+}
+
+// Public API
+// ----------
+
+impl MtcInput {
+    /// Connect to every MIDI port with a name containing `name`. An empty
+    /// name connects to every port.
+    ///
+    /// Fails if no port matches.
+    pub fn open(name: &str) -> Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let mut connections = vec![];
+
+        let ports = MidiInput::new(CLIENT_NAME)
+            .context("Unable to initialize MIDI input!")?
+            .ports();
+        for port in &ports {
+            // Connecting consumes the client, so each port needs its own.
+            let midi = MidiInput::new(CLIENT_NAME)
+                .context("Unable to initialize MIDI input!")?;
+            let port_name = midi
+                .port_name(port)
+                .context("Unable to read a MIDI port name!")?;
+            if !port_name.contains(name) {
+                continue;
+            }
+            let connection = midi
+                .connect(
+                    port,
+                    CLIENT_NAME,
+                    |_timestamp, bytes, (sender, parser)| {
+                        if let Some((timecode, playing)) = parser.parse(bytes) {
+                            // The input was dropped, nothing is listening.
+                            let _ = sender.send((
+                                timecode,
+                                Instant::now(),
+                                playing,
+                            ));
+                        }
+                    },
+                    (sender.clone(), MtcParser::default()),
+                )
+                .map_err(|error| anyhow!("{}", error))
+                .with_context(|| {
+                    format!("Unable to connect to MIDI port {}", port_name)
+                })?;
+            log::info!("Listening for MTC on {}", port_name);
+            connections.push(connection);
+        }
+
+        if connections.is_empty() {
+            bail!("No MIDI input port matches {:?}", name);
+        }
+
+        Ok(Self {
+            receiver,
+            clock: TimecodeClock::new(),
+            _connections: connections,
+        })
+    }
+
+    /// Apply the timecode received since the last update. Call once per
+    /// frame.
+    pub fn update(&mut self) {
+        for (timecode, received, playing) in self.receiver.try_iter() {
+            self.clock.sync(timecode, received, playing);
+        }
+    }
+
+    pub fn clock(&self) -> &TimecodeClock {
+        &self.clock
+    }
+
+    /// Adjust how long the clock keeps running through dropouts.
+    pub fn clock_mut(&mut self) -> &mut TimecodeClock {
+        &mut self.clock
+    }
+}
+
+// Private API
+// -----------
+
+/// Assembles timecode from quarter frame and full frame messages.
+#[derive(Debug, Default)]
+struct MtcParser {
+    /// The data nibble from each of the 8 quarter frame pieces.
+    pieces: [u8; 8],
+
+    /// A bit for each piece received since piece 0.
+    received: u8,
+}
+
+impl MtcParser {
+    /// Parse one MIDI message. Returns the current timecode and whether the
+    /// source is playing when the message completes one.
+    fn parse(&mut self, bytes: &[u8]) -> Option<(Timecode, bool)> {
+        match bytes {
+            [0xF1, data] => self.quarter_frame(*data),
+            // A full frame sysex: F0 7F <device> 01 01 hh mm ss ff F7
+            [0xF0, 0x7F, _, 0x01, 0x01, fields @ ..] if fields.len() >= 4 => {
+                let [h, m, s, f] = [fields[0], fields[1], fields[2], fields[3]];
+                Some((timecode(h >> 5, h, m, s, f)?, false))
+            }
+            _ => None,
+        }
+    }
+
+    fn quarter_frame(&mut self, data: u8) -> Option<(Timecode, bool)> {
+        let piece = (data >> 4) as usize & 0x07;
+        if piece == 0 {
+            self.received = 0;
+        }
+        self.pieces[piece] = data & 0x0F;
+        self.received |= 1 << piece;
+        if piece != 7 || self.received != 0xFF {
+            return None;
+        }
+
+        let p = &self.pieces;
+        let timecode = timecode(
+            p[7] >> 1,
+            p[6] | (p[7] & 0x01) << 4,
+            p[4] | p[5] << 4,
+            p[2] | p[3] << 4,
+            p[0] | p[1] << 4,
+        )?;
+
+        // The pieces describe the frame when piece 0 was sent, and piece 7
+        // arrives 1.75 frames later.
+        let seconds = timecode.to_seconds() + 1.75 / timecode.rate.fps();
+        Some((Timecode::from_seconds(seconds, timecode.rate), true))
+    }
+}
+
+/// Build a timecode from MTC fields. `rate` is the 2 bit rate code.
+fn timecode(
+    rate: u8,
+    hours: u8,
+    minutes: u8,
+    seconds: u8,
+    frames: u8,
+) -> Option<Timecode> {
+    let rate = match rate & 0x03 {
+        0 => FrameRate::Fps24,
+        1 => FrameRate::Fps25,
+        2 => FrameRate::Fps30Drop,
+        _ => FrameRate::Fps30,
+    };
+    let timecode = Timecode {
+        hours: hours & 0x1F,
+        minutes: minutes & 0x3F,
+        seconds: seconds & 0x3F,
+        frames: frames & 0x1F,
+        rate,
+    };
+    let is_valid = timecode.hours < 24
+        && timecode.minutes < 60
+        && timecode.seconds < 60
+        && timecode.frames < rate.nominal_fps() as u8;
+    is_valid.then_some(timecode)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mut parser = MtcParser::default();
+
+        // 01:02:03:04 at 25fps
+        let full_frame =
+            [0xF0, 0x7F, 0x7F, 0x01, 0x01, 0x21, 0x02, 0x03, 0x04, 0xF7];
+        let (timecode, playing) = parser.parse(&full_frame).unwrap();
+        assert_eq!(timecode.to_string(), "01:02:03:04");
+        assert_eq!(timecode.rate, FrameRate::Fps25);
+        assert!(!playing);
+
+        // 10:20:30:20 at 30fps, which is 0A:14:1E:14 in hex.
+        let pieces = [0x04, 0x11, 0x2E, 0x31, 0x44, 0x51, 0x6A, 0x76];
+        for piece in &pieces[..7] {
+            assert_eq!(parser.parse(&[0xF1, *piece]), None);
+        }
+        let (timecode, playing) = parser.parse(&[0xF1, pieces[7]]).unwrap();
+        assert_eq!(timecode.to_string(), "10:20:30:21");
+        assert!(playing);
+    }
+}
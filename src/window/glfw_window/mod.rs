@@ -1,7 +1,7 @@
 mod window_state;
 
 use {
-    crate::graphics::vulkan_api::RenderDevice,
+    crate::{graphics::vulkan_api::RenderDevice, window::WindowHints},
     anyhow::{bail, Context, Result},
     ash::vk,
     ccthw_ash_instance::{PhysicalDeviceFeatures, VulkanInstance},
@@ -30,8 +30,11 @@ impl GlfwWindow {
     /// # Params
     ///
     /// * `window_title` - The title shown on the window's top bar.
+    /// * `hints` - Window properties which can only be set before the window is
+    ///   created.
     pub fn new(
         window_title: impl AsRef<str>,
+        hints: &WindowHints,
     ) -> Result<(Self, Receiver<(f64, WindowEvent)>)> {
         let mut glfw = glfw::init_no_callbacks()?;
 
@@ -41,6 +44,9 @@ impl GlfwWindow {
 
         glfw.window_hint(WindowHint::ClientApi(ClientApiHint::NoApi));
         glfw.window_hint(WindowHint::ScaleToMonitor(true));
+        glfw.window_hint(WindowHint::Decorated(hints.decorated));
+        glfw.window_hint(WindowHint::Floating(hints.always_on_top));
+        glfw.window_hint(WindowHint::TransparentFramebuffer(hints.transparent));
 
         let (mut window_handle, event_receiver) = glfw
             .create_window(
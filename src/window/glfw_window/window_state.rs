@@ -4,8 +4,8 @@ use {
         window::{glfw_window::GlfwWindow, WindowState},
     },
     anyhow::{Context, Result},
-    glfw::{Action, MouseButton, WindowEvent, WindowMode},
-    std::collections::HashSet,
+    glfw::{Action, Cursor, MouseButton, PixelImage, WindowEvent, WindowMode},
+    std::{collections::HashSet, time::Duration},
 };
 
 impl GlfwWindow {
@@ -14,9 +14,11 @@ impl GlfwWindow {
         let (window_x, window_y) = self.get_pos();
         let (w, h) = self.get_size();
         let (mouse_x, mouse_y) = self.get_cursor_pos();
+        let (scale_x, scale_y) = self.get_content_scale();
         WindowState {
             toggle_fullscreen: false,
             is_fullscreen: self.is_glfw_window_fullscreen(),
+            fullscreen_monitor: None,
             needs_resized: false,
             should_close: self.should_close(),
 
@@ -28,13 +30,45 @@ impl GlfwWindow {
             width: w as f32,
             height: h as f32,
 
+            opacity: self.get_opacity(),
+            needs_opacity_update: false,
+
+            content_scale: Vec2::new(scale_x, scale_y),
+
             mouse_pos: Vec2::new(mouse_x as f32, mouse_y as f32),
+            mouse_delta: Vec2::zeros(),
+            cursor_pos: (mouse_x, mouse_y),
+            raw_mouse_motion: false,
+            needs_raw_mouse_motion_update: false,
             left_button_pressed: false,
             middle_button_pressed: false,
             right_button_pressed: false,
 
+            double_click_time: Duration::from_millis(400),
+            click_distance: 4.0,
+            press_pos: None,
+            press_was_double_click: false,
+            is_dragging: false,
+            last_click: None,
+            mouse_gesture: None,
+
+            cursor_mode: self.get_cursor_mode(),
+            needs_cursor_mode_update: false,
+            cursor_image: None,
+            needs_cursor_image_update: false,
+
+            pen_pressure: None,
+            pen_tilt: None,
+
+            scroll: Vec2::zeros(),
+
             keyboard_button_pressed: false,
             pressed_keys: HashSet::with_capacity(26),
+
+            just_pressed_keys: HashSet::with_capacity(26),
+            just_released_keys: HashSet::with_capacity(26),
+
+            typed_text: String::new(),
         }
     }
 
@@ -59,6 +93,47 @@ impl GlfwWindow {
             window_state.mouse_pos.y = 0.5 * window_state.height - my as f32;
         }
 
+        if window_state.needs_opacity_update {
+            window_state.needs_opacity_update = false;
+            self.set_opacity(window_state.opacity);
+        }
+
+        if window_state.needs_raw_mouse_motion_update {
+            window_state.needs_raw_mouse_motion_update = false;
+            if self.glfw.supports_raw_motion() {
+                self.set_raw_mouse_motion(window_state.raw_mouse_motion);
+            } else {
+                log::warn!("Raw mouse motion is not supported!");
+            }
+        }
+
+        if window_state.needs_cursor_mode_update {
+            window_state.needs_cursor_mode_update = false;
+            self.set_cursor_mode(window_state.cursor_mode);
+        }
+
+        if window_state.needs_cursor_image_update {
+            window_state.needs_cursor_image_update = false;
+            let cursor = window_state.cursor_image.as_ref().map(
+                |(image, hotspot_x, hotspot_y)| {
+                    let pixels = image
+                        .pixels()
+                        .map(|pixel| u32::from_ne_bytes(pixel.0))
+                        .collect();
+                    Cursor::create_from_pixels(
+                        PixelImage {
+                            width: image.width(),
+                            height: image.height(),
+                            pixels,
+                        },
+                        *hotspot_x,
+                        *hotspot_y,
+                    )
+                },
+            );
+            self.set_cursor(cursor);
+        }
+
         self.set_should_close(window_state.should_close);
         Ok(())
     }
@@ -72,7 +147,8 @@ impl GlfwWindow {
             WindowEvent::MouseButton(button, Action::Press, _) => {
                 match button {
                     MouseButton::Button1 => {
-                        window_state.left_button_pressed = true
+                        window_state.left_button_pressed = true;
+                        window_state.left_button_down();
                     }
                     MouseButton::Button2 => {
                         window_state.right_button_pressed = true
@@ -86,7 +162,8 @@ impl GlfwWindow {
             WindowEvent::MouseButton(button, Action::Release, _) => {
                 match button {
                     MouseButton::Button1 => {
-                        window_state.left_button_pressed = false
+                        window_state.left_button_pressed = false;
+                        window_state.left_button_up();
                     }
                     MouseButton::Button2 => {
                         window_state.right_button_pressed = false
@@ -100,14 +177,29 @@ impl GlfwWindow {
             WindowEvent::Key(key, _, Action::Press, _) => {
                 window_state.keyboard_button_pressed = true;
                 window_state.pressed_keys.insert(key);
+                window_state.just_pressed_keys.insert(key);
             }
             WindowEvent::Key(key, _, Action::Release, _) => {
                 window_state.keyboard_button_pressed = false;
                 window_state.pressed_keys.remove(&key);
+                window_state.just_released_keys.insert(key);
+            }
+            WindowEvent::Char(c) => {
+                window_state.typed_text.push(c);
             }
             WindowEvent::CursorPos(x, y) => {
+                let (last_x, last_y) = window_state.cursor_pos;
+                window_state.cursor_pos = (x, y);
+                window_state.mouse_delta.x += (x - last_x) as f32;
+                window_state.mouse_delta.y += (last_y - y) as f32;
+
                 window_state.mouse_pos.x = x as f32 - 0.5 * window_state.width;
                 window_state.mouse_pos.y = 0.5 * window_state.height - y as f32;
+                window_state.left_button_moved();
+            }
+            WindowEvent::Scroll(x, y) => {
+                window_state.scroll.x += x as f32;
+                window_state.scroll.y += y as f32;
             }
             WindowEvent::Close => {
                 window_state.should_close = true;
@@ -116,6 +208,9 @@ impl GlfwWindow {
                 window_state.width = width as f32;
                 window_state.height = height as f32;
             }
+            WindowEvent::ContentScale(x, y) => {
+                window_state.content_scale = Vec2::new(x, y);
+            }
             _ => (),
         }
         Ok(())
@@ -124,7 +219,7 @@ impl GlfwWindow {
     /// Toggle application fullscreen.
     ///
     /// If the window is currently windowed then swap to fullscreen using
-    /// whatever the primary monitor advertises as the primary video mode.
+    /// whatever the chosen monitor advertises as the primary video mode.
     ///
     /// If the window is currently fullscreen, then swap to windowed and
     /// restore the window's previous size and location.
@@ -152,27 +247,57 @@ impl GlfwWindow {
             (window_state.window_x, window_state.window_y) = self.get_pos();
 
             let mut glfw = self.glfw.clone();
-            glfw.with_primary_monitor(|_, monitor_opt| -> Result<()> {
-                let monitor = monitor_opt
-                    .context("Unable to determine the primary monitor!")?;
-                let video_mode = monitor
-                    .get_video_mode()
-                    .context("Unable to get a primary video mode!")?;
-                self.set_monitor(
-                    WindowMode::FullScreen(monitor),
-                    0,
-                    0,
-                    video_mode.width,
-                    video_mode.height,
-                    Some(video_mode.refresh_rate),
-                );
-                Ok(())
-            })?;
+            let used_chosen_monitor = match window_state.fullscreen_monitor {
+                Some(index) => glfw.with_connected_monitors(
+                    |_, monitors| -> Result<bool> {
+                        match monitors.get(index) {
+                            Some(monitor) => {
+                                self.set_fullscreen_monitor(monitor)?;
+                                Ok(true)
+                            }
+                            None => {
+                                log::warn!(
+                                    "Monitor {} is not connected, using the primary monitor",
+                                    index
+                                );
+                                Ok(false)
+                            }
+                        }
+                    },
+                )?,
+                None => false,
+            };
+            if !used_chosen_monitor {
+                glfw.with_primary_monitor(|_, monitor_opt| -> Result<()> {
+                    let monitor = monitor_opt
+                        .context("Unable to determine the primary monitor!")?;
+                    self.set_fullscreen_monitor(monitor)
+                })?;
+            }
         }
         window_state.is_fullscreen = self.is_glfw_window_fullscreen();
         Ok(())
     }
 
+    /// Make the window fullscreen on the monitor using its current video mode.
+    fn set_fullscreen_monitor(
+        &mut self,
+        monitor: &glfw::Monitor,
+    ) -> Result<()> {
+        let video_mode = monitor
+            .get_video_mode()
+            .context("Unable to get a primary video mode!")?;
+        self.set_monitor(
+            WindowMode::FullScreen(monitor),
+            0,
+            0,
+            video_mode.width,
+            video_mode.height,
+            Some(video_mode.refresh_rate),
+        );
+        Ok(())
+    }
+
     fn is_glfw_window_fullscreen(&self) -> bool {
         self.with_window_mode(|mode| match mode {
             WindowMode::Windowed => false,
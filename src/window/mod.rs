@@ -1,9 +1,61 @@
 mod glfw_window;
+#[cfg(all(feature = "tablet", target_os = "linux"))]
+pub(crate) mod tablet;
 
-use {crate::math::Vec2, std::collections::HashSet};
+use {
+    crate::math::Vec2,
+    image::RgbaImage,
+    std::{
+        collections::HashSet,
+        time::{Duration, Instant},
+    },
+};
 
 pub use self::glfw_window::GlfwWindow;
 
+/// Window properties which must be chosen before the window is created.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WindowHints {
+    /// When false, the window has no border or title bar.
+    pub decorated: bool,
+
+    /// When true, the window stays above all other windows.
+    pub always_on_top: bool,
+
+    /// When true, the window's framebuffer has an alpha channel which is
+    /// composited with the desktop. Colors should be premultiplied by alpha
+    /// so transparent regions blend correctly.
+    pub transparent: bool,
+}
+
+impl Default for WindowHints {
+    fn default() -> Self {
+        Self {
+            decorated: true,
+            always_on_top: false,
+            transparent: false,
+        }
+    }
+}
+
+/// A mouse gesture recognized from raw button presses and releases.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum MouseGesture {
+    /// The left mouse button was pressed and released without dragging.
+    Click,
+
+    /// The left mouse button was clicked twice in quick succession.
+    DoubleClick,
+}
+
+/// The parts of the window state replaced by `WindowState::enter_viewport`.
+pub(crate) struct ViewportRestore {
+    width: f32,
+    height: f32,
+    mouse_pos: Vec2,
+    needs_resized: bool,
+}
+
 /// Represents the Window's state.
 ///
 /// Sketches can modify the state to change properties about the window.
@@ -13,6 +65,7 @@ pub struct WindowState {
     // Window Size and fullscreen variables
     toggle_fullscreen: bool,
     is_fullscreen: bool,
+    fullscreen_monitor: Option<usize>,
     needs_resized: bool,
 
     // Set to true when the window should be closed.
@@ -28,14 +81,54 @@ pub struct WindowState {
     width: f32,
     height: f32,
 
+    // The opacity of the whole window, including decorations.
+    opacity: f32,
+    needs_opacity_update: bool,
+
+    // The ratio between the current DPI and the platform's default DPI.
+    content_scale: Vec2,
+
     // Input state variables
     mouse_pos: Vec2,
+    mouse_delta: Vec2,
+    cursor_pos: (f64, f64),
+    raw_mouse_motion: bool,
+    needs_raw_mouse_motion_update: bool,
     left_button_pressed: bool,
     right_button_pressed: bool,
     middle_button_pressed: bool,
 
+    // Click and drag detection for the left mouse button.
+    double_click_time: Duration,
+    click_distance: f32,
+    press_pos: Option<Vec2>,
+    press_was_double_click: bool,
+    is_dragging: bool,
+    last_click: Option<(Instant, Vec2)>,
+    mouse_gesture: Option<MouseGesture>,
+
+    // Cursor appearance and behavior.
+    cursor_mode: glfw::CursorMode,
+    needs_cursor_mode_update: bool,
+    cursor_image: Option<(RgbaImage, u32, u32)>,
+    needs_cursor_image_update: bool,
+
+    // Stylus state while a pen is near a graphics tablet.
+    pen_pressure: Option<f32>,
+    pen_tilt: Option<Vec2>,
+
+    // The total scroll offset accumulated since the window was created.
+    scroll: Vec2,
+
     keyboard_button_pressed: bool,
     pressed_keys: HashSet<glfw::Key>,
+
+    // Keys which changed state since the last frame was updated.
+    just_pressed_keys: HashSet<glfw::Key>,
+    just_released_keys: HashSet<glfw::Key>,
+
+    // Text entered since the last frame.
+    typed_text: String,
 }
 
 // Public API
@@ -68,10 +161,158 @@ impl WindowState {
         self.mouse_pos
     }
 
+    /// The mouse position in screen pixels with the origin at the top left of
+    /// the window and y pointing down.
+    ///
+    /// Use this with `math::picking::screen_to_world` to map the mouse
+    /// through a custom projection.
+    pub fn mouse_screen_pos(&self) -> Vec2 {
+        Vec2::new(self.cursor_pos.0 as f32, self.cursor_pos.1 as f32)
+    }
+
+    /// Returns true while the left mouse button is held and the mouse has
+    /// moved further than the click distance since it was pressed.
+    pub fn is_dragging(&self) -> bool {
+        self.is_dragging
+    }
+
+    /// Set the maximum time between two clicks for them to count as a
+    /// double-click.
+    pub fn set_double_click_time(&mut self, double_click_time: Duration) {
+        self.double_click_time = double_click_time;
+    }
+
+    /// Set how far (in pixels) the mouse can move while pressed and still
+    /// count as a click rather than a drag. This is also the maximum distance
+    /// between the two clicks of a double-click.
+    pub fn set_click_distance(&mut self, click_distance: f32) {
+        self.click_distance = click_distance;
+    }
+
+    /// How far the mouse moved since the last frame.
+    ///
+    /// Unlike the mouse position, the delta keeps accumulating when the
+    /// cursor is grabbed and would otherwise be stuck at the window's edge.
+    pub fn mouse_delta(&self) -> Vec2 {
+        self.mouse_delta
+    }
+
+    /// Enable or disable raw (unaccelerated, unscaled) mouse motion.
+    ///
+    /// Raw motion only applies while the cursor is grabbed and is ignored on
+    /// platforms which don't support it.
+    pub fn set_raw_mouse_motion(&mut self, enabled: bool) {
+        self.raw_mouse_motion = enabled;
+        self.needs_raw_mouse_motion_update = true;
+    }
+
+    /// Returns true if raw mouse motion has been requested.
+    pub fn is_raw_mouse_motion_enabled(&self) -> bool {
+        self.raw_mouse_motion
+    }
+
+    /// The stylus pressure, from 0 with no pressure to 1 at full pressure.
+    ///
+    /// This is None unless a pen is near a graphics tablet. Pen pressure is
+    /// only read on Linux with the `tablet` feature. The pen also moves the
+    /// mouse, so use `mouse_pos` for its position.
+    pub fn pen_pressure(&self) -> Option<f32> {
+        self.pen_pressure
+    }
+
+    /// How far the stylus leans, from -1 to 1 across the tablet's range on
+    /// each axis. Positive x leans right and positive y leans up.
+    ///
+    /// This is None unless a pen which reports tilt is near a graphics
+    /// tablet.
+    pub fn pen_tilt(&self) -> Option<Vec2> {
+        self.pen_tilt
+    }
+
+    /// The cumulative scroll offset since the window was created.
+    ///
+    /// Positive y values correspond to scrolling up.
+    pub fn scroll(&self) -> Vec2 {
+        self.scroll
+    }
+
+    /// Hide the cursor while it is over the window.
+    pub fn hide_cursor(&mut self) {
+        self.set_cursor_mode(glfw::CursorMode::Hidden);
+    }
+
+    /// Show the cursor and release it if it was grabbed.
+    pub fn show_cursor(&mut self) {
+        self.set_cursor_mode(glfw::CursorMode::Normal);
+    }
+
+    /// Hide the cursor and capture it so it cannot leave the window.
+    ///
+    /// The mouse position is no longer limited to the window bounds while the
+    /// cursor is grabbed, which is useful for relative mouse-look controls.
+    pub fn grab_cursor(&mut self) {
+        self.set_cursor_mode(glfw::CursorMode::Disabled);
+    }
+
+    /// Returns true when the cursor is hidden or grabbed.
+    pub fn is_cursor_hidden(&self) -> bool {
+        self.cursor_mode != glfw::CursorMode::Normal
+    }
+
+    /// Returns true when the cursor is grabbed by the window.
+    pub fn is_cursor_grabbed(&self) -> bool {
+        self.cursor_mode == glfw::CursorMode::Disabled
+    }
+
+    /// Use an image as the cursor while it is over the window.
+    ///
+    /// # Params
+    ///
+    /// * `image` - the cursor image
+    /// * `hotspot_x` - the x coordinate of the cursor's hotspot, in pixels from
+    ///   the left of the image
+    /// * `hotspot_y` - the y coordinate of the cursor's hotspot, in pixels from
+    ///   the top of the image
+    pub fn set_cursor_image(
+        &mut self,
+        image: RgbaImage,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    ) {
+        self.cursor_image = Some((image, hotspot_x, hotspot_y));
+        self.needs_cursor_image_update = true;
+    }
+
+    /// Restore the system's default cursor image.
+    pub fn reset_cursor_image(&mut self) {
+        self.cursor_image = None;
+        self.needs_cursor_image_update = true;
+    }
+
     pub fn toggle_fullscreen(&mut self) {
         self.toggle_fullscreen = true;
     }
 
+    /// Switch to fullscreen or windowed mode.
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.toggle_fullscreen = self.is_fullscreen != fullscreen;
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.is_fullscreen
+    }
+
+    /// Choose the monitor used for fullscreen mode.
+    ///
+    /// # Params
+    ///
+    /// * `monitor` - the index into GLFW's list of connected monitors, or None
+    ///   to use the primary monitor. The primary monitor is also used when the
+    ///   index is out of range.
+    pub fn set_fullscreen_monitor(&mut self, monitor: Option<usize>) {
+        self.fullscreen_monitor = monitor;
+    }
+
     pub fn width(&self) -> f32 {
         self.width
     }
@@ -80,7 +321,201 @@ impl WindowState {
         self.height
     }
 
+    /// Set the opacity of the whole window, including decorations.
+    ///
+    /// # Params
+    ///
+    /// * `opacity` - the window opacity, clamped to the range [0, 1] where 0 is
+    ///   fully transparent and 1 is fully opaque.
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self.needs_opacity_update = true;
+    }
+
+    /// The opacity of the whole window, including decorations.
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// The ratio between the window's current DPI and the platform's default
+    /// DPI.
+    ///
+    /// This is 1.0 on most standard displays and 2.0 on a 200% HiDPI display.
+    /// Sketches can multiply sizes by the content scale so shapes and text
+    /// don't render tiny on high-resolution monitors.
+    pub fn content_scale(&self) -> Vec2 {
+        self.content_scale
+    }
+
     pub fn is_key_pressed(&self, key: glfw::Key) -> bool {
         self.pressed_keys.contains(&key)
     }
+
+    /// Returns true while either shift key is held down.
+    pub fn is_shift_down(&self) -> bool {
+        self.is_key_down(glfw::Key::LeftShift)
+            || self.is_key_down(glfw::Key::RightShift)
+    }
+
+    /// Returns true while either control key is held down.
+    pub fn is_control_down(&self) -> bool {
+        self.is_key_down(glfw::Key::LeftControl)
+            || self.is_key_down(glfw::Key::RightControl)
+    }
+
+    /// Returns true while either alt key is held down.
+    pub fn is_alt_down(&self) -> bool {
+        self.is_key_down(glfw::Key::LeftAlt)
+            || self.is_key_down(glfw::Key::RightAlt)
+    }
+
+    /// Returns true while either super (Windows/Command) key is held down.
+    pub fn is_super_down(&self) -> bool {
+        self.is_key_down(glfw::Key::LeftSuper)
+            || self.is_key_down(glfw::Key::RightSuper)
+    }
+
+    /// The modifier keys which are currently held down.
+    pub fn modifiers(&self) -> glfw::Modifiers {
+        let mut modifiers = glfw::Modifiers::empty();
+        modifiers.set(glfw::Modifiers::Shift, self.is_shift_down());
+        modifiers.set(glfw::Modifiers::Control, self.is_control_down());
+        modifiers.set(glfw::Modifiers::Alt, self.is_alt_down());
+        modifiers.set(glfw::Modifiers::Super, self.is_super_down());
+        modifiers
+    }
+
+    /// Returns true while the key is held down.
+    ///
+    /// An alias for `is_key_pressed` which reads better next to
+    /// `was_key_pressed`.
+    pub fn is_key_down(&self, key: glfw::Key) -> bool {
+        self.is_key_pressed(key)
+    }
+
+    /// Returns true if the key was pressed since the last frame.
+    pub fn was_key_pressed(&self, key: glfw::Key) -> bool {
+        self.just_pressed_keys.contains(&key)
+    }
+
+    /// Returns true if the key was released since the last frame.
+    pub fn was_key_released(&self, key: glfw::Key) -> bool {
+        self.just_released_keys.contains(&key)
+    }
+
+    /// The text entered since the last frame.
+    ///
+    /// Only committed text is reported. Characters composed with an input
+    /// method (like CJK text entry) appear once the composition is finished,
+    /// never as intermediate pre-edit characters.
+    pub fn typed_text(&self) -> &str {
+        &self.typed_text
+    }
+}
+
+// Private API
+// -----------
+
+impl WindowState {
+    fn set_cursor_mode(&mut self, cursor_mode: glfw::CursorMode) {
+        self.cursor_mode = cursor_mode;
+        self.needs_cursor_mode_update = true;
+    }
+
+    /// Report the pen's state, or None for both when no pen is near a
+    /// tablet.
+    #[cfg_attr(
+        not(all(feature = "tablet", target_os = "linux")),
+        allow(dead_code)
+    )]
+    pub(crate) fn set_pen(
+        &mut self,
+        pressure: Option<f32>,
+        tilt: Option<Vec2>,
+    ) {
+        self.pen_pressure = pressure;
+        self.pen_tilt = tilt;
+    }
+
+    /// Take the most recently recognized mouse gesture, if any.
+    pub(crate) fn take_mouse_gesture(&mut self) -> Option<MouseGesture> {
+        self.mouse_gesture.take()
+    }
+
+    /// Begin tracking a possible click when the left button is pressed.
+    fn left_button_down(&mut self) {
+        let now = Instant::now();
+        let is_double_click =
+            self.last_click.take().is_some_and(|(time, pos)| {
+                now - time <= self.double_click_time
+                    && (self.mouse_pos - pos).magnitude() <= self.click_distance
+            });
+        if is_double_click {
+            self.mouse_gesture = Some(MouseGesture::DoubleClick);
+        }
+        self.press_pos = Some(self.mouse_pos);
+        self.press_was_double_click = is_double_click;
+        self.is_dragging = false;
+    }
+
+    /// Start dragging once the mouse moves far enough from the press.
+    fn left_button_moved(&mut self) {
+        if let Some(press_pos) = self.press_pos {
+            if (self.mouse_pos - press_pos).magnitude() > self.click_distance {
+                self.is_dragging = true;
+            }
+        }
+    }
+
+    /// Recognize a click when the left button is released without dragging.
+    fn left_button_up(&mut self) {
+        if self.press_pos.take().is_some() && !self.is_dragging {
+            self.mouse_gesture = Some(MouseGesture::Click);
+            if !self.press_was_double_click {
+                self.last_click = Some((Instant::now(), self.mouse_pos));
+            }
+        }
+        self.is_dragging = false;
+    }
+
+    /// Make the window look like a smaller viewport centered at `center`.
+    ///
+    /// The width, height, and mouse position are reported relative to the
+    /// viewport until `exit_viewport` is called with the returned value.
+    pub(crate) fn enter_viewport(
+        &mut self,
+        center: Vec2,
+        size: Vec2,
+    ) -> ViewportRestore {
+        let restore = ViewportRestore {
+            width: self.width,
+            height: self.height,
+            mouse_pos: self.mouse_pos,
+            needs_resized: self.needs_resized,
+        };
+        self.width = size.x;
+        self.height = size.y;
+        self.mouse_pos -= center;
+        restore
+    }
+
+    /// Restore the window state saved by `enter_viewport`.
+    ///
+    /// Resize requests made inside the viewport are discarded.
+    pub(crate) fn exit_viewport(&mut self, restore: ViewportRestore) {
+        self.width = restore.width;
+        self.height = restore.height;
+        self.mouse_pos = restore.mouse_pos;
+        self.needs_resized = restore.needs_resized;
+    }
+
+    /// Forget per-frame input state like key presses and releases.
+    ///
+    /// Called once each frame after the sketch has been updated.
+    pub(crate) fn end_frame(&mut self) {
+        self.just_pressed_keys.clear();
+        self.just_released_keys.clear();
+        self.typed_text.clear();
+        self.mouse_delta = Vec2::zeros();
+    }
 }
@@ -0,0 +1,178 @@
+//! Stylus pressure and tilt from Linux evdev devices.
+//!
+//! GLFW reports a graphics tablet's pen as an ordinary mouse, so the extra
+//! axes are read straight from `/dev/input/event*`. The user needs read
+//! access to the tablet's device, usually by being in the `input` group.
+
+use {
+    crate::{math::Vec2, window::WindowState},
+    evdev::{AbsoluteAxisCode, Device, KeyCode},
+    std::{io::ErrorKind, path::Path},
+};
+
+/// Every pen tablet which could be opened.
+pub(crate) struct Tablet {
+    pens: Vec<Pen>,
+}
+
+// Public API
+// ----------
+
+impl Tablet {
+    /// Open every input device with a pen and a pressure axis.
+    ///
+    /// Devices which can't be opened are skipped, so this never fails. There
+    /// is just no pressure to report without a tablet.
+    pub fn open() -> Self {
+        let pens = evdev::enumerate()
+            .filter_map(|(path, device)| Pen::open(&path, device))
+            .collect::<Vec<_>>();
+        if pens.is_empty() {
+            log::info!("No readable pen tablet found in /dev/input");
+        }
+        Self { pens }
+    }
+
+    /// Read the pen events which arrived since the last update and report
+    /// the pen's state to the window.
+    pub fn update(&mut self, window_state: &mut WindowState) {
+        self.pens.retain_mut(|pen| pen.read_events());
+        let pen = self
+            .pens
+            .iter()
+            .find(|pen| pen.in_proximity())
+            .map(|pen| pen.state());
+        match pen {
+            Some((pressure, tilt)) => {
+                window_state.set_pen(Some(pressure), tilt)
+            }
+            None => window_state.set_pen(None, None),
+        }
+    }
+}
+
+// Private API
+// -----------
+
+/// One evdev device with a pen.
+///
+/// The device keeps a copy of its key and axis state which is updated as
+/// events are read. If the kernel's event buffer overflows, the kernel
+/// reports SYN_DROPPED and evdev reads the whole state back from the
+/// device on the next fetch, so the pen's state doesn't go stale.
+struct Pen {
+    device: Device,
+    name: String,
+    pressure: Range,
+    tilt: Option<(Range, Range)>,
+}
+
+/// The range of an absolute axis.
+struct Range {
+    code: AbsoluteAxisCode,
+    minimum: i32,
+    maximum: i32,
+}
+
+impl Pen {
+    /// Use `device` if it is a pen tablet.
+    fn open(path: &Path, device: Device) -> Option<Self> {
+        let has_pen = device
+            .supported_keys()
+            .is_some_and(|keys| keys.contains(KeyCode::BTN_TOOL_PEN));
+        let has_pressure = device
+            .supported_absolute_axes()
+            .is_some_and(|axes| axes.contains(AbsoluteAxisCode::ABS_PRESSURE));
+        if !has_pen || !has_pressure {
+            return None;
+        }
+
+        let abs_state = match device.get_abs_state() {
+            Ok(abs_state) => abs_state,
+            Err(error) => {
+                log::debug!("Skipping {:?}: {}", path, error);
+                return None;
+            }
+        };
+        let range = |code: AbsoluteAxisCode| {
+            let supported = device
+                .supported_absolute_axes()
+                .is_some_and(|axes| axes.contains(code));
+            let info = &abs_state[code.0 as usize];
+            (supported && info.maximum > info.minimum).then_some(Range {
+                code,
+                minimum: info.minimum,
+                maximum: info.maximum,
+            })
+        };
+        let pressure = range(AbsoluteAxisCode::ABS_PRESSURE)?;
+        let tilt = range(AbsoluteAxisCode::ABS_TILT_X)
+            .zip(range(AbsoluteAxisCode::ABS_TILT_Y));
+
+        if let Err(error) = device.set_nonblocking(true) {
+            log::debug!("Skipping {:?}: {}", path, error);
+            return None;
+        }
+        let name = device.name().unwrap_or("unnamed tablet").to_owned();
+        log::info!("Reading pen pressure from {} ({:?})", name, path);
+
+        Some(Self {
+            device,
+            name,
+            pressure,
+            tilt,
+        })
+    }
+
+    /// Apply every queued event. Returns false if the device is gone.
+    fn read_events(&mut self) -> bool {
+        loop {
+            match self.device.fetch_events() {
+                // The events update the device's cached state as they are
+                // consumed.
+                Ok(events) => events.for_each(drop),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    return true;
+                }
+                Err(error) if error.kind() == ErrorKind::Interrupted => (),
+                Err(error) => {
+                    log::warn!("Lost pen tablet {}: {}", self.name, error);
+                    return false;
+                }
+            }
+        }
+    }
+
+    /// True while the pen or eraser is close enough to the tablet to be
+    /// tracked.
+    fn in_proximity(&self) -> bool {
+        self.device.cached_state().key_vals().is_some_and(|keys| {
+            keys.contains(KeyCode::BTN_TOOL_PEN)
+                || keys.contains(KeyCode::BTN_TOOL_RUBBER)
+        })
+    }
+
+    /// The pressure in [0, 1] and the tilt in [-1, 1] on each axis.
+    fn state(&self) -> (f32, Option<Vec2>) {
+        let pressure = self.normalized(&self.pressure);
+        let tilt = self.tilt.as_ref().map(|(x, y)| {
+            // evdev's y axis points down and the window's points up.
+            Vec2::new(
+                self.normalized(x) * 2.0 - 1.0,
+                1.0 - self.normalized(y) * 2.0,
+            )
+        });
+        (pressure, tilt)
+    }
+
+    /// The axis' latest value mapped from its range to [0, 1].
+    fn normalized(&self, range: &Range) -> f32 {
+        let value = self
+            .device
+            .cached_state()
+            .abs_vals()
+            .map_or(range.minimum, |abs| abs[range.code.0 as usize].value);
+        let size = (range.maximum - range.minimum) as f32;
+        ((value - range.minimum) as f32 / size).clamp(0.0, 1.0)
+    }
+}